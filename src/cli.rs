@@ -0,0 +1,222 @@
+// Headless batch mode: `super_resolution_upscaler --input photos/ --model-name <name> --output-dir out/`
+// runs process_images straight from main, without opening the window, so cron jobs and shell
+// scripts can drive the tool. Release builds on Windows use the GUI subsystem and print nothing;
+// run a debug build there to see the output.
+
+use crate::*;
+use clap::{CommandFactory, Parser};
+
+// Exit codes for scripts
+const EXIT_SUCCESS: i32 = 0;
+const EXIT_PARTIAL: i32 = 1; // Some images failed, the rest were written
+const EXIT_FAILURE: i32 = 2; // Nothing was written; clap also exits with 2 on bad arguments
+
+#[derive(Debug, Parser)]
+#[command(name = "super_resolution_upscaler", about = "Upscale and enhance images with ONNX models")]
+struct CliArgs {
+    /// Image file or folder of images
+    #[arg(long)]
+    input: Option<PathBuf>,
+
+    /// Local .onnx file, run with the same defaults as "Add Custom Model" (4x, NCHW, 0-1 range)
+    #[arg(long, conflicts_with = "model_name")]
+    model: Option<PathBuf>,
+
+    /// Built-in or imported model, see --list-models
+    #[arg(long)]
+    model_name: Option<String>,
+
+    #[arg(long)]
+    output_dir: Option<PathBuf>,
+
+    /// png, jpeg, webp, webp-lossy or tiff (plus avif and jxl when built with those features)
+    #[arg(long, default_value = "png", value_parser = parse_output_format)]
+    output_format: OutputFormat,
+
+    /// Pixels per tile side; defaults to the tile size saved in the GUI config
+    #[arg(long)]
+    tile_size: Option<u32>,
+
+    /// Execution provider, same values as the ORT_EP environment variable
+    #[arg(long)]
+    ep: Option<String>,
+
+    /// Print the files that would be written without running the model
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Print the available model names and exit
+    #[arg(long)]
+    list_models: bool,
+}
+
+// Only the CLI's own flags switch to headless mode, so `--help` also stays in the terminal and
+// anything else (e.g. flags the OS or a launcher adds) still opens the window
+pub fn requested() -> bool {
+    let command = CliArgs::command();
+    let flags: Vec<&str> = command.get_arguments()
+        .filter_map(|arg| arg.get_long())
+        .chain(["help"])
+        .collect();
+    std::env::args().skip(1).any(|arg| {
+        arg.strip_prefix("--")
+            .map(|flag| flag.split_once('=').map_or(flag, |(name, _)| name))
+            .is_some_and(|flag| flags.contains(&flag))
+    })
+}
+
+// Returns the process exit code
+pub fn run() -> i32 {
+    let args = CliArgs::parse();
+    let config = Config::load();
+    OFFLINE_MODE.store(config.offline_mode, Ordering::Relaxed);
+    let model_cache = ModelCache::from_config(&config);
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("Failed to start the async runtime: {}", e);
+            return EXIT_FAILURE;
+        }
+    };
+
+    let mut models = model_cache.available_models();
+    if let Some(url) = config.registry_url.as_ref().filter(|_| !config.offline_mode) {
+        match runtime.block_on(fetch_model_registry(url)) {
+            Ok(registry) => {
+                merge_models(&mut models, registry);
+            }
+            Err(e) => eprintln!("Failed to fetch the model registry: {}", e),
+        }
+    }
+
+    if args.list_models {
+        for model in models {
+            let cached = if model_cache.is_cached(&model) { " (cached)" } else { "" };
+            println!("{:<40} {}{}", model.name, model, cached);
+        }
+        return EXIT_SUCCESS;
+    }
+
+    let (Some(input), Some(output_dir)) = (&args.input, &args.output_dir) else {
+        eprintln!("--input and --output-dir are required, see --help");
+        return EXIT_FAILURE;
+    };
+
+    let (model, model_cache) = match resolve_model(&args, model_cache, models) {
+        Ok(resolved) => resolved,
+        Err(e) => {
+            eprintln!("{}", e);
+            return EXIT_FAILURE;
+        }
+    };
+
+    let files = if input.is_dir() {
+        list_folder_images(input)
+    } else if input.is_file() {
+        vec![input.clone()]
+    } else {
+        eprintln!("Input {} does not exist", input.display());
+        return EXIT_FAILURE;
+    };
+    if files.is_empty() {
+        eprintln!("No images found in {}", input.display());
+        return EXIT_FAILURE;
+    }
+
+    let options = ProcessOptions {
+        output_dir: output_dir.clone(),
+        output_format: args.output_format.clone(),
+        tile_size: args.tile_size.or(config.tile_size),
+        conflict_mode: config.conflict_mode,
+        ..ProcessOptions::default()
+    };
+
+    if args.dry_run {
+        let download = if model.url != "local" && !model_cache.is_cached(&model) { ", downloaded first" } else { "" };
+        println!("Model: {} ({}{})", model.name, model_cache.model_path(&model).display(), download);
+        for file in &files {
+            // Only the header is read; {width}/{height} assume the model's nominal scale
+            let (w, h) = image::image_dimensions(file).unwrap_or((0, 0));
+            match output_path_for(file, &model, &options.output_dir, &options.filename_template, None,
+                options.output_format.extension(model.bit_depth), (w * model.scale, h * model.scale)) {
+                Ok(output) => println!("{} -> {}", file.display(), output.display()),
+                Err(e) => println!("{} -> {}", file.display(), e),
+            }
+        }
+        return EXIT_SUCCESS;
+    }
+
+    // ort_config_from_env already knows every EP name and which ones this build supports
+    let ort_config = ort_config_from_env(&config, args.ep.as_deref());
+
+    // Nobody is there to answer the low disk space prompt, so a full drive aborts the run
+    let disk_space = Arc::new(DiskSpaceGate::default());
+    disk_space.decide(false);
+
+    let total = files.len();
+    let result = runtime.block_on(process_images(
+        files,
+        model,
+        options,
+        ort_config,
+        model_cache,
+        None,
+        None,
+        None,
+        Arc::new(AtomicU32::new(0)),
+        disk_space,
+        Arc::new(AtomicBool::new(false)),
+        Arc::new(AtomicBool::new(false)),
+        Arc::new(Mutex::new(ProgressState { total, ..ProgressState::default() })),
+    ));
+
+    let (completed, error) = match result {
+        Ok(results) => (results.len(), None),
+        Err(e) => (e.completed.len(), Some(e.to_string())),
+    };
+    if let Some(error) = &error {
+        eprintln!("Error: {}", error);
+    }
+    println!("Completed {}/{} image(s)", completed, total);
+
+    match completed {
+        0 => EXIT_FAILURE,
+        n if n < total => EXIT_PARTIAL,
+        _ => EXIT_SUCCESS,
+    }
+}
+
+// --model points the cache at the file's own folder, so model_path resolves to it unchanged
+fn resolve_model(args: &CliArgs, model_cache: ModelCache, models: Vec<ModelInfo>) -> Result<(ModelInfo, ModelCache), String> {
+    if let Some(path) = &args.model {
+        if path.extension().and_then(|ext| ext.to_str()) != Some("onnx") || !path.is_file() {
+            return Err(format!("--model {} is not an .onnx file", path.display()));
+        }
+        let model = CustomModelForm::new(path.clone()).to_model_info()?;
+        let cache_dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        return Ok((model, ModelCache { cache_dir }));
+    }
+
+    let Some(name) = &args.model_name else {
+        return Err("Pass --model <file.onnx> or --model-name <name>, see --list-models".to_string());
+    };
+    models.into_iter()
+        .find(|m| &m.name == name)
+        .map(|model| (model, model_cache))
+        .ok_or_else(|| format!("Unknown model '{}', see --list-models", name))
+}
+
+fn parse_output_format(value: &str) -> Result<OutputFormat, String> {
+    match value.to_lowercase().as_str() {
+        "png" => Ok(OutputFormat::Png),
+        "jpeg" | "jpg" => Ok(OutputFormat::Jpeg(DEFAULT_OUTPUT_QUALITY)),
+        "webp" => Ok(OutputFormat::WebpLossless),
+        "webp-lossy" => Ok(OutputFormat::WebpLossy(DEFAULT_OUTPUT_QUALITY)),
+        "tiff" | "tif" => Ok(OutputFormat::Tiff),
+        #[cfg(feature = "avif")]
+        "avif" => Ok(OutputFormat::Avif(DEFAULT_OUTPUT_QUALITY)),
+        #[cfg(feature = "jxl")]
+        "jxl" => Ok(OutputFormat::Jxl { quality: DEFAULT_OUTPUT_QUALITY as f32, lossless: false }),
+        other => Err(format!("unknown output format '{}'", other)),
+    }
+}
@@ -0,0 +1,584 @@
+// ONNX Runtime sessions and the tensor conversions around them, kept apart from the iced GUI
+// so they can be exercised on their own
+
+use crate::*;
+
+pub fn ensure_model_file(model: &ModelInfo, model_cache: &ModelCache, speed_limit_kbps: Option<u32>) -> Result<()> {
+    if !model_cache.is_cached(model) {
+        log_message(&format!("Model not found locally, downloading: {}", model.name));
+        download_model(&model.url, &model_cache.model_path(model), model.checksum.as_deref(), speed_limit_kbps).map_err(|e| {
+            log_error(&format!("Failed to download model: {}", e));
+            e
+        })?;
+        log_message("Model downloaded successfully");
+    }
+    Ok(())
+}
+
+// The execution provider and its options come from ort_config, device picks its GPU or CPU side
+pub fn build_session(model_path: &Path, ort_config: &OrtConfig, device: ExecutionDevice) -> Result<Session> {
+    let session = Session::builder()
+        .map_err(log_failure("create session builder"))?
+        .with_optimization_level(ort_config.optimization_level())
+        .map_err(log_failure("set optimization level"))?
+        .with_execution_providers(ort_config.execution_providers(device))
+        .map_err(log_failure("set execution provider"))?
+        .commit_from_file(model_path)
+        .map_err(log_failure(&format!("load model from {}", model_path.display())))?;
+    Ok(session)
+}
+
+// Logs which builder step failed and passes the error on
+fn log_failure<E: std::fmt::Display>(step: &str) -> impl FnOnce(E) -> E + '_ {
+    move |e| {
+        log_error(&format!("Failed to {}: {}", step, e));
+        e
+    }
+}
+
+pub fn create_session(model: &ModelInfo, device: ExecutionDevice, ort_config: &OrtConfig, model_cache: &ModelCache) -> Result<Session> {
+    let model_path = model_cache.model_path(model);
+    model.validate()?;
+    ensure_model_file(model, model_cache, None)?;
+
+    log_message(&format!("Creating ONNX session ({}, ep={})...", device, ort_config.ep));
+    
+    let build = |device: ExecutionDevice| build_session(&model_path, ort_config, device);
+    
+    // CoreML is missing before macOS 10.13
+    #[cfg(feature = "coreml")]
+    if device == ExecutionDevice::Gpu && ort_config.ep == ExecutionProvider::CoreML {
+        return build(device)
+            .or_else(|e| {
+                log_error(&format!("⚠ CoreML unavailable, falling back to CPU: {}", e));
+                build(ExecutionDevice::Cpu)
+            })
+            .inspect(|session| check_tensor_dtype(session, model));
+    }
+    
+    build(device).inspect(|session| check_tensor_dtype(session, model))
+}
+
+// A wrong tensor_dtype makes ORT reject every input, so say which one the graph wants
+fn check_tensor_dtype(session: &Session, model: &ModelInfo) {
+    let Some(input) = session.inputs.first() else {
+        return;
+    };
+    let declared = TensorSpec::new(&input.name, &input.input_type).dtype;
+    if declared.is_some_and(|dtype| dtype != model.tensor_dtype) {
+        log_message(&format!("⚠ Model {} is configured as {} but its input '{}' is {}",
+            model.name, model.tensor_dtype, input.name, declared.map_or(String::new(), |dtype| dtype.to_string())));
+    }
+}
+
+// FIXED: Correct normalization for different model types
+pub fn preprocess_image_for_model(img: &DynamicImage, model: &ModelInfo) -> Result<Array4<f32>> {
+    let (w, h) = img.dimensions();
+    let channels = if model.input_channels == 1 { 1 } else { 3 };
+    // Interleaved samples (luma or RGB) at the model's bit depth
+    let samples: Vec<f32> = match (model.bit_depth, channels) {
+        (BitDepth::Bit8, 1) => img.to_luma8().into_raw().into_iter().map(f32::from).collect(),
+        (BitDepth::Bit16, 1) => img.to_luma16().into_raw().into_iter().map(f32::from).collect(),
+        (BitDepth::Bit8, _) => img.to_rgb8().into_raw().into_iter().map(f32::from).collect(),
+        (BitDepth::Bit16, _) => img.to_rgb16().into_raw().into_iter().map(f32::from).collect(),
+    };
+    let max_value = model.bit_depth.max_value();
+    let half_max = max_value / 2.0;
+    
+    // Choose normalization function, called with (value, channel)
+    let normalize_fn: Box<dyn Fn(f32, usize) -> f32 + Sync> = match (model.input_standardization(), &model.input_norm) {
+        (Some((mean, std)), _) => {
+            log_message(&format!("Input standardization: mean {:?}, std {:?} for model: {}", mean, std, model.name));
+            Box::new(move |val: f32, c: usize| (val / max_value - mean[c]) / std[c])
+        }
+        (None, NormalizationRange::MinusOneOne) => {
+            log_message(&format!("Input normalization: [-1, 1] for model: {}", model.name));
+            Box::new(move |val: f32, _| (val / half_max) - 1.0)
+        }
+        (None, NormalizationRange::ZeroOne) => {
+            log_message(&format!("Input normalization: [0, 1] for model: {}", model.name));
+            Box::new(move |val: f32, _| val / max_value)
+        }
+    };
+    
+    // Create tensor based on format, filling the flat buffer in parallel; samples are
+    // interleaved, so NCHW gathers each plane from every channels-th sample
+    let (h, w) = (h as usize, w as usize);
+    let tensor = match model.tensor_format {
+        TensorFormat::NCHW => {
+            log_message(&format!("Creating NCHW tensor [1, {}, {}, {}]", channels, h, w));
+            let plane = h * w;
+            let data = (0..channels * plane).into_par_iter()
+                .map(|i| {
+                    let (c, pixel) = (i / plane, i % plane);
+                    normalize_fn(samples[pixel * channels + c], c)
+                })
+                .collect();
+            Array4::from_shape_vec((1, channels, h, w), data)?
+        }
+        TensorFormat::NHWC => {
+            log_message(&format!("Creating NHWC tensor [1, {}, {}, {}]", h, w, channels));
+            let data = samples.par_iter().enumerate()
+                .map(|(i, &val)| normalize_fn(val, i % channels))
+                .collect();
+            Array4::from_shape_vec((1, h, w, channels), data)?
+        }
+    };
+    
+    // Diagnostic: Check tensor range
+    let shape = tensor.shape();
+    let mut min_val = f32::MAX;
+    let mut max_val = f32::MIN;
+    let mut sum = 0.0;
+    let mut count = 0;
+    
+    for idx in tensor.iter() {
+        min_val = min_val.min(*idx);
+        max_val = max_val.max(*idx);
+        sum += *idx;
+        count += 1;
+    }
+    
+    let mean = sum / count as f32;
+    log_message(&format!("🔍 Input tensor - shape: {:?}, range: [{:.4}, {:.4}], mean: {:.4}", 
+        shape, min_val, max_val, mean));
+    
+    Ok(tensor)
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TensorHealth {
+    nan_count: usize,
+    inf_count: usize,
+    clip_fraction: f32, // Share of finite values outside the model's output range
+}
+
+fn check_tensor_health(tensor: &Array4<f32>, norm: &NormalizationRange) -> TensorHealth {
+    let (low, high) = match norm {
+        NormalizationRange::ZeroOne => (0.0, 1.0),
+        NormalizationRange::MinusOneOne => (-1.0, 1.0),
+    };
+    
+    let mut health = TensorHealth { nan_count: 0, inf_count: 0, clip_fraction: 0.0 };
+    let mut clipped = 0usize;
+    
+    for &val in tensor.iter() {
+        if val.is_nan() {
+            health.nan_count += 1;
+        } else if val.is_infinite() {
+            health.inf_count += 1;
+        } else if val < low || val > high {
+            clipped += 1;
+        }
+    }
+    
+    health.clip_fraction = clipped as f32 / tensor.len().max(1) as f32;
+    health
+}
+
+// NaN output means the model or provider is broken, so the image is failed rather than saved
+pub fn verify_output_tensor(tensor: &Array4<f32>, model: &ModelInfo) -> Result<()> {
+    let health = check_tensor_health(tensor, &model.output_norm);
+    
+    if health.nan_count > 0 {
+        log_error(&format!("Output tensor contains {} NaN value(s) ({} Inf)", health.nan_count, health.inf_count));
+        return Err(anyhow::anyhow!("Model produced {} NaN value(s)", health.nan_count));
+    }
+    if health.inf_count > 0 {
+        log_message(&format!("⚠ Output tensor contains {} Inf value(s)", health.inf_count));
+    }
+    if health.clip_fraction > 0.05 {
+        log_message(&format!("⚠ {:.1}% of output values fall outside the expected range and will be clipped",
+            health.clip_fraction * 100.0));
+    }
+    
+    Ok(())
+}
+
+// Enhanced postprocessing that handles both formats
+pub fn postprocess_tensor_for_model(tensor: Array4<f32>, model: &ModelInfo) -> Result<DynamicImage> {
+    let shape = tensor.shape();
+    
+    // Diagnostic: Check output tensor range
+    let mut min_val = f32::MAX;
+    let mut max_val = f32::MIN;
+    let mut sum = 0.0;
+    let mut count = 0;
+    
+    for val in tensor.iter() {
+        if val.is_finite() {
+            min_val = min_val.min(*val);
+            max_val = max_val.max(*val);
+            sum += *val;
+            count += 1;
+        }
+    }
+    
+    let mean = if count > 0 { sum / count as f32 } else { 0.0 };
+    log_message(&format!("🔍 Output tensor - shape: {:?}, range: [{:.4}, {:.4}], mean: {:.4}", 
+        shape, min_val, max_val, mean));
+    
+    // Check for invalid values
+    if !min_val.is_finite() || !max_val.is_finite() {
+        return Err(anyhow::anyhow!("Output tensor contains NaN or Inf values"));
+    }
+    
+    // Choose denormalization function, called with (value, channel), returning a sample
+    // in 0..=max_value for the model's bit depth
+    let max_value = model.bit_depth.max_value();
+    let half_max = max_value / 2.0;
+    let denormalize_fn: Box<dyn Fn(f32, usize) -> f32 + Sync> = match (model.output_standardization(), &model.output_norm) {
+        (Some((mean, std)), _) => {
+            log_message(&format!("Output destandardization: mean {:?}, std {:?} → [0, {}]", mean, std, max_value));
+            Box::new(move |val: f32, c: usize| ((val * std[c] + mean[c]) * max_value).clamp(0.0, max_value))
+        }
+        (None, NormalizationRange::MinusOneOne) => {
+            log_message(&format!("Output denormalization: [-1, 1] → [0, {}]", max_value));
+            Box::new(move |val: f32, _| ((val + 1.0) * half_max).clamp(0.0, max_value))
+        }
+        (None, NormalizationRange::ZeroOne) => {
+            log_message(&format!("Output denormalization: [0, 1] → [0, {}]", max_value));
+            Box::new(move |val: f32, _| (val * max_value).clamp(0.0, max_value))
+        }
+    };
+    
+    let channels = if model.output_channels == 1 { 1 } else { 3 };
+    let tensor_channels = match model.tensor_format {
+        TensorFormat::NCHW => shape[1],
+        TensorFormat::NHWC => shape[3],
+    };
+    if tensor_channels != channels {
+        return Err(anyhow::anyhow!("Model {} produced {} channel(s), expected {}", model.name, tensor_channels, channels));
+    }
+    
+    // Extract dimensions, then gather interleaved samples in parallel based on format
+    let (h, w) = match model.tensor_format {
+        TensorFormat::NCHW => (shape[2], shape[3]),
+        TensorFormat::NHWC => (shape[1], shape[2]),
+    };
+    log_message(&format!("Postprocessing {:?}: {}x{}", model.tensor_format, w, h));
+    
+    let samples: Vec<f32> = (0..h * w * channels).into_par_iter()
+        .map(|i| {
+            let (pixel, c) = (i / channels, i % channels);
+            let (y, x) = (pixel / w, pixel % w);
+            let val = match model.tensor_format {
+                TensorFormat::NCHW => tensor[[0, c, y, x]],
+                TensorFormat::NHWC => tensor[[0, y, x, c]],
+            };
+            denormalize_fn(val, c)
+        })
+        .collect();
+    let (w, h) = (w as u32, h as u32);
+    
+    let to_u8 = || samples.iter().map(|&v| v as u8).collect::<Vec<_>>();
+    let to_u16 = || samples.iter().map(|&v| v as u16).collect::<Vec<_>>();
+    let img = match (model.bit_depth, channels) {
+        (BitDepth::Bit8, 1) => ImageBuffer::from_raw(w, h, to_u8()).map(DynamicImage::ImageLuma8),
+        (BitDepth::Bit16, 1) => ImageBuffer::from_raw(w, h, to_u16()).map(DynamicImage::ImageLuma16),
+        (BitDepth::Bit8, _) => ImageBuffer::from_raw(w, h, to_u8()).map(DynamicImage::ImageRgb8),
+        (BitDepth::Bit16, _) => ImageBuffer::from_raw(w, h, to_u16()).map(DynamicImage::ImageRgb16),
+    };
+    Ok(img.expect("one sample per channel per pixel"))
+}
+
+// (padded image, padded dimensions, (pad_right, pad_bottom))
+pub type PaddedImage = (DynamicImage, (u32, u32), (u32, u32));
+
+// How the border added by pad_to_multiple is filled; the padding is cropped off the output again.
+// Defaults to Symmetric rather than Replicate: pad_to_multiple mirrored the edge before it
+// took a mode, and models.json entries without a pad_mode must keep producing the same output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PadMode {
+    #[default]
+    Symmetric, // Mirrored including the edge: a b c | c b, what pad_to_multiple always did before the modes
+    Reflect, // Mirrored without repeating the edge: a b c | b a
+    Replicate, // Edge pixel repeated: a b c | c c
+    Zero, // Black
+    Wrap, // Image tiled: a b c | a b
+}
+
+impl PadMode {
+    // Source index for position i on an axis of len pixels, None = zero fill
+    fn source_index(self, i: u32, len: u32) -> Option<u32> {
+        if i < len {
+            return Some(i);
+        }
+        
+        match self {
+            PadMode::Replicate => Some(len - 1),
+            PadMode::Zero => None,
+            PadMode::Wrap => Some(i % len),
+            PadMode::Symmetric => {
+                let k = i % (2 * len);
+                Some(if k < len { k } else { 2 * len - 1 - k })
+            }
+            // Bounces between the edges, so any padding width stays inside the image
+            PadMode::Reflect if len == 1 => Some(0),
+            PadMode::Reflect => {
+                let period = 2 * (len - 1);
+                let k = i % period;
+                Some(if k < len { k } else { period - k })
+            }
+        }
+    }
+}
+
+pub fn pad_to_multiple(img: &DynamicImage, multiple: u32, mode: PadMode) -> Result<PaddedImage> {
+    let (w, h) = img.dimensions();
+    let pad_w = w.div_ceil(multiple) * multiple;
+    let pad_h = h.div_ceil(multiple) * multiple;
+    let pad_r = pad_w - w;
+    let pad_b = pad_h - h;
+    
+    if pad_r == 0 && pad_b == 0 {
+        return Ok((img.clone(), (w, h), (0, 0)));
+    }
+    
+    let source = |x: u32, y: u32| Some((mode.source_index(x, w)?, mode.source_index(y, h)?));
+    
+    // 16-bit inputs stay 16-bit, everything else is padded as 8-bit RGB
+    let padded = if matches!(img, DynamicImage::ImageRgb16(_) | DynamicImage::ImageRgba16(_) | DynamicImage::ImageLuma16(_) | DynamicImage::ImageLumaA16(_)) {
+        let rgb = img.to_rgb16();
+        DynamicImage::ImageRgb16(ImageBuffer::from_fn(pad_w, pad_h, |x, y| {
+            source(x, y).map_or(Rgb([0u16; 3]), |(src_x, src_y)| *rgb.get_pixel(src_x, src_y))
+        }))
+    } else {
+        let rgb = img.to_rgb8();
+        DynamicImage::ImageRgb8(ImageBuffer::from_fn(pad_w, pad_h, |x, y| {
+            source(x, y).map_or(Rgb([0u8; 3]), |(src_x, src_y)| *rgb.get_pixel(src_x, src_y))
+        }))
+    };
+    
+    Ok((padded, (pad_w, pad_h), (pad_r, pad_b)))
+}
+
+// The caller owns the session so the graph is loaded once and reused across images
+pub fn process_single_image(
+    session: &mut Session,
+    input_path: &Path,
+    model: &ModelInfo,
+    opts: &ProcessOptions,
+) -> Result<ProcessResult> {
+    let prepared = prepare_input(input_path, model, opts)?;
+    run_prepared_image(input_path, prepared, model, session, opts, None, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn test_model(tensor_format: TensorFormat) -> ModelInfo {
+        ModelInfo {
+            name: "test".to_string(),
+            url: "local".to_string(),
+            model_type: ModelType::Denoising,
+            scale: 1,
+            window_size: 1,
+            description: "Test".to_string(),
+            category: "Test".to_string(),
+            tensor_format,
+            input_norm: NormalizationRange::ZeroOne,
+            output_norm: NormalizationRange::ZeroOne,
+            min_dimension: None,
+            channel_independent: false,
+            quantized: false,
+            ycbcr_mode: false,
+            max_tile_size: None,
+            input_mean: None,
+            input_std: None,
+            output_mean: None,
+            output_std: None,
+            bit_depth: BitDepth::Bit8,
+            input_channels: 3,
+            output_channels: 3,
+            checksum: None,
+            output_index: 0,
+            tensor_dtype: TensorDtype::F32,
+            pad_mode: PadMode::Symmetric,
+        }
+    }
+    
+    // 3x2 image with a distinct colour per pixel, so swapped axes show up as wrong values
+    fn test_image() -> DynamicImage {
+        DynamicImage::ImageRgb8(ImageBuffer::from_fn(3, 2, |x, y| {
+            Rgb([(x * 80) as u8, (y * 200) as u8, 255 - (x * 40 + y * 10) as u8])
+        }))
+    }
+    
+    #[test]
+    fn preprocess_nchw_puts_channels_first() {
+        let tensor = preprocess_image_for_model(&test_image(), &test_model(TensorFormat::NCHW)).unwrap();
+        
+        assert_eq!(tensor.shape(), &[1, 3, 2, 3]);
+        assert_eq!(tensor[[0, 0, 1, 2]], 160.0 / 255.0);
+        assert_eq!(tensor[[0, 1, 1, 2]], 200.0 / 255.0);
+        assert_eq!(tensor[[0, 2, 1, 2]], 165.0 / 255.0);
+    }
+    
+    #[test]
+    fn preprocess_nhwc_puts_channels_last() {
+        let tensor = preprocess_image_for_model(&test_image(), &test_model(TensorFormat::NHWC)).unwrap();
+        
+        assert_eq!(tensor.shape(), &[1, 2, 3, 3]);
+        assert_eq!(tensor[[0, 1, 2, 0]], 160.0 / 255.0);
+        assert_eq!(tensor[[0, 1, 2, 1]], 200.0 / 255.0);
+        assert_eq!(tensor[[0, 1, 2, 2]], 165.0 / 255.0);
+    }
+    
+    #[test]
+    fn postprocess_round_trips_nchw() {
+        let model = test_model(TensorFormat::NCHW);
+        let tensor = preprocess_image_for_model(&test_image(), &model).unwrap();
+        let output = postprocess_tensor_for_model(tensor, &model).unwrap();
+        
+        assert_eq!(output.to_rgb8(), test_image().to_rgb8());
+    }
+    
+    #[test]
+    fn postprocess_round_trips_nhwc() {
+        let model = test_model(TensorFormat::NHWC);
+        let tensor = preprocess_image_for_model(&test_image(), &model).unwrap();
+        let output = postprocess_tensor_for_model(tensor, &model).unwrap();
+        
+        assert_eq!(output.to_rgb8(), test_image().to_rgb8());
+    }
+    
+    // Every pixel [128, 64, 200], so any position can be checked against the same values
+    fn uniform_image() -> DynamicImage {
+        DynamicImage::ImageRgb8(ImageBuffer::from_pixel(4, 4, Rgb([128, 64, 200])))
+    }
+    
+    fn model_with_norm(norm: NormalizationRange) -> ModelInfo {
+        ModelInfo { input_norm: norm, output_norm: norm, ..test_model(TensorFormat::NCHW) }
+    }
+    
+    #[test]
+    fn preprocess_zero_one_divides_by_max() {
+        let tensor = preprocess_image_for_model(&uniform_image(), &model_with_norm(NormalizationRange::ZeroOne)).unwrap();
+        
+        assert_eq!(tensor.shape(), &[1, 3, 4, 4]);
+        assert_eq!(tensor[[0, 0, 0, 0]], 128.0 / 255.0);
+        assert_eq!(tensor[[0, 1, 3, 3]], 64.0 / 255.0);
+        assert_eq!(tensor[[0, 2, 2, 1]], 200.0 / 255.0);
+    }
+    
+    #[test]
+    fn preprocess_minus_one_one_centres_on_zero() {
+        let tensor = preprocess_image_for_model(&uniform_image(), &model_with_norm(NormalizationRange::MinusOneOne)).unwrap();
+        
+        assert_eq!(tensor[[0, 0, 0, 0]], 128.0 / 127.5 - 1.0);
+        assert_eq!(tensor[[0, 1, 3, 3]], 64.0 / 127.5 - 1.0);
+        assert_eq!(tensor[[0, 2, 2, 1]], 200.0 / 127.5 - 1.0);
+    }
+    
+    // An identity model: the preprocessed tensor fed straight back must give the original pixels
+    #[test]
+    fn postprocess_round_trips_both_normalizations() {
+        let original = uniform_image().to_rgb8();
+        
+        for norm in [NormalizationRange::ZeroOne, NormalizationRange::MinusOneOne] {
+            let model = model_with_norm(norm);
+            let tensor = preprocess_image_for_model(&uniform_image(), &model).unwrap();
+            let output = postprocess_tensor_for_model(tensor, &model).unwrap().to_rgb8();
+            
+            assert_eq!(output.dimensions(), original.dimensions());
+            // Samples are truncated to u8, not rounded, so an f32 round trip that lands one ULP
+            // under the original value (e.g. 199.99998) comes back one lower
+            for (out, orig) in output.pixels().zip(original.pixels()) {
+                for c in 0..3 {
+                    assert!(out[c].abs_diff(orig[c]) <= 1, "{:?}: {:?} became {:?}", norm, orig, out);
+                }
+            }
+        }
+    }
+    
+    // Distinct value per pixel so the mirrored source of each padding pixel can be identified
+    fn gradient_image(w: u32, h: u32) -> DynamicImage {
+        DynamicImage::ImageRgb8(ImageBuffer::from_fn(w, h, |x, y| Rgb([(x * 30) as u8, (y * 30) as u8, 7])))
+    }
+    
+    #[test]
+    fn pad_to_multiple_rounds_up_each_side() {
+        let (padded, dims, padding) = pad_to_multiple(&gradient_image(5, 7), 8, PadMode::Replicate).unwrap();
+        
+        assert_eq!(padded.dimensions(), (8, 8));
+        assert_eq!(dims, (8, 8));
+        assert_eq!(padding, (3, 1));
+    }
+    
+    #[test]
+    fn pad_to_multiple_mirrors_edge_pixels() {
+        let original = gradient_image(5, 7).to_rgb8();
+        let (padded, _, _) = pad_to_multiple(&gradient_image(5, 7), 8, PadMode::default()).unwrap();
+        let padded = padded.to_rgb8();
+        
+        for y in 0..7 {
+            assert_eq!(padded.get_pixel(5, y), original.get_pixel(4, y));
+            assert_eq!(padded.get_pixel(6, y), original.get_pixel(3, y));
+            assert_eq!(padded.get_pixel(7, y), original.get_pixel(2, y));
+        }
+        assert_eq!(padded.get_pixel(2, 7), original.get_pixel(2, 6));
+        assert_eq!(padded.get_pixel(6, 7), original.get_pixel(3, 6));
+    }
+    
+    // Columns 5..8 of a 5-wide image padded to 8, for each mode; None = zero fill
+    #[test]
+    fn pad_to_multiple_fills_border_per_mode() {
+        let original = gradient_image(5, 7).to_rgb8();
+        let cases = [
+            (PadMode::Symmetric, [Some(4), Some(3), Some(2)]),
+            (PadMode::Reflect, [Some(3), Some(2), Some(1)]),
+            (PadMode::Replicate, [Some(4), Some(4), Some(4)]),
+            (PadMode::Zero, [None, None, None]),
+            (PadMode::Wrap, [Some(0), Some(1), Some(2)]),
+        ];
+        
+        for (mode, sources) in cases {
+            let (padded, _, _) = pad_to_multiple(&gradient_image(5, 7), 8, mode).unwrap();
+            let padded = padded.to_rgb8();
+            
+            for y in 0..7 {
+                for (x, source) in (5..8).zip(sources) {
+                    let expected = source.map_or(Rgb([0, 0, 0]), |src_x| *original.get_pixel(src_x, y));
+                    assert_eq!(*padded.get_pixel(x, y), expected, "{:?} at ({}, {})", mode, x, y);
+                }
+            }
+        }
+    }
+    
+    // A tall padding band on a 2-pixel axis bounces back and forth instead of running off the image
+    #[test]
+    fn pad_reflect_bounces_between_edges() {
+        let indices: Vec<_> = (0..8).map(|i| PadMode::Reflect.source_index(i, 2)).collect();
+        
+        assert_eq!(indices, [0, 1, 0, 1, 0, 1, 0, 1].map(Some));
+    }
+    
+    #[test]
+    fn pad_to_multiple_leaves_aligned_images_unchanged() {
+        let img = gradient_image(16, 16);
+        let (padded, dims, padding) = pad_to_multiple(&img, 8, PadMode::Reflect).unwrap();
+        
+        assert_eq!(dims, (16, 16));
+        assert_eq!(padding, (0, 0));
+        assert_eq!(padded.to_rgb8(), img.to_rgb8());
+        
+        let (padded, dims, padding) = pad_to_multiple(&gradient_image(5, 7), 1, PadMode::Reflect).unwrap();
+        assert_eq!(padded.dimensions(), (5, 7));
+        assert_eq!(dims, (5, 7));
+        assert_eq!(padding, (0, 0));
+    }
+    
+    // Reflect has no neighbour to mirror on a 1x1 image, so it repeats the pixel like the other modes
+    #[test]
+    fn pad_to_multiple_handles_single_pixel() {
+        for mode in [PadMode::Symmetric, PadMode::Reflect, PadMode::Replicate, PadMode::Wrap] {
+            let (padded, dims, padding) = pad_to_multiple(&gradient_image(1, 1), 8, mode).unwrap();
+            
+            assert_eq!(dims, (8, 8));
+            assert_eq!(padding, (7, 7));
+            assert!(padded.to_rgb8().pixels().all(|p| *p == Rgb([0, 0, 7])), "{:?}", mode);
+        }
+    }
+}
@@ -15,27 +15,38 @@ num_cpus = "1.16"
 
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod cli;
+mod inference;
+mod models;
+
 use iced::{
     executor, font, theme,
-    widget::{button, column, container, pick_list, row, text, scrollable, Space, image as iced_image},
-    Alignment, Application, Color, Command, Element, Font, Length, Settings, Size, Theme, Background,
+    widget::{button, canvas, checkbox, column, container, mouse_area, pick_list, progress_bar, radio, row, slider, text, text_input, scrollable, Space, image as iced_image},
+    Alignment, Application, Color, Command, Element, Font, Length, Settings, Size, Subscription, Theme, Background,
 };
-use image::{DynamicImage, GenericImageView, ImageBuffer, Rgb};
-use ndarray::Array4;
+use image::{DynamicImage, GenericImageView, ImageBuffer, Luma, Rgb};
+use ndarray::{Array2, Array4, Axis, Slice};
 use ort::{session::Session, value::Value};
 use std::path::{Path, PathBuf};
 use std::{fs, io};
 use std::time::Duration;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
 use anyhow::Result;
-use iced::widget::scrollable::{Direction, Properties};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use iced::widget::scrollable::{Direction, Properties, RelativeOffset};
 use std::process::{Command as ProcessCommand, Stdio};
 use std::io::Write;
 use rayon::prelude::*;
 
 use std::fs::OpenOptions;
-use std::io::Write as IoWrite;
 use chrono::Local;
+use inference::*;
+use models::*;
 
 // Font definitions
 const HEADING_FONT: Font = Font {
@@ -54,12 +65,154 @@ const BODY_FONT: Font = Font {
 
 // Theme colors
 const PRIMARY_COLOR: Color = Color::from_rgb(0.2, 0.5, 0.9);
-const BACKGROUND_COLOR: Color = Color::from_rgb(0.97, 0.97, 0.98);
-const CARD_COLOR: Color = Color::WHITE;
-const TEXT_COLOR: Color = Color::from_rgb(0.2, 0.2, 0.3);
-const TEXT_SECONDARY: Color = Color::from_rgb(0.4, 0.4, 0.5);
+
+// Colors that change with the dark mode toggle, PRIMARY_COLOR is shared by both
+#[derive(Debug, Clone, Copy)]
+struct ColorScheme {
+    background: Color,
+    card: Color,
+    text: Color,
+    text_secondary: Color,
+}
+
+const LIGHT_COLORS: ColorScheme = ColorScheme {
+    background: Color::from_rgb(0.97, 0.97, 0.98),
+    card: Color::WHITE,
+    text: Color::from_rgb(0.2, 0.2, 0.3),
+    text_secondary: Color::from_rgb(0.4, 0.4, 0.5),
+};
+
+const DARK_COLORS: ColorScheme = ColorScheme {
+    background: Color::from_rgb(0.102, 0.102, 0.180), // #1a1a2e
+    card: Color::from_rgb(0.086, 0.129, 0.243), // #16213e
+    text: Color::from_rgb(0.90, 0.90, 0.95),
+    text_secondary: Color::from_rgb(0.62, 0.64, 0.72),
+};
+
+impl ColorScheme {
+    // Style sheets only see the theme, which App::theme picks from dark_mode
+    fn of(theme: &Theme) -> ColorScheme {
+        if *theme == Theme::Dark { DARK_COLORS } else { LIGHT_COLORS }
+    }
+}
+
+const DEFAULT_VIDEO_SEGMENT_SECS: f32 = 600.0;
+// Segments upscaled at once; a second one keeps ffmpeg's decode and encode of one segment
+// overlapping inference on the other, more mostly compete for the same GPU
+const MAX_PARALLEL_SEGMENTS: usize = 2;
+
+const MAX_RECENT_ENTRIES: usize = 10;
+
+// Single-pass video work directories, kept after a failed run so it can be resumed
+const VIDEO_TEMP_FRAMES_DIR: &str = "./temp_frames";
+const VIDEO_TEMP_UPSCALED_DIR: &str = "./temp_upscaled";
+const VIDEO_CHECKPOINT_FILE: &str = "checkpoint.json";
+const VIDEO_CHECKPOINT_INTERVAL: Duration = Duration::from_secs(2);
+
+// x264 rate control for the reassembled video
+const DEFAULT_VIDEO_CRF: u8 = 18;
+const MAX_VIDEO_CRF: u8 = 51;
+const DEFAULT_VIDEO_BITRATE_KBPS: u32 = 8000;
+
+// GPU temperature throttling
+const DEFAULT_GPU_TEMP_LIMIT: u32 = 85;
+const GPU_TEMP_POLL_INTERVAL: Duration = Duration::from_secs(10);
+const GPU_COOLDOWN_PAUSE: Duration = Duration::from_secs(60);
+
+// The batch pauses before writing when the output drive has less than this free
+const DEFAULT_MIN_FREE_DISK_GB: f64 = 1.0;
+const DISK_SPACE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+// How often a paused batch checks whether it was resumed
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// Status bar progress: how often it's read, and how many recent images the ETA averages over
+const PROGRESS_POLL_INTERVAL: Duration = Duration::from_millis(500);
+const PROGRESS_AVERAGE_WINDOW: usize = 20;
+
+// Throughput graph shown while a batch runs
+const THROUGHPUT_WINDOW: Duration = Duration::from_secs(60);
+
+// Model downloads
+const DEFAULT_DOWNLOAD_SPEED_LIMIT_KBPS: u32 = 1024;
+const DOWNLOAD_PROGRESS_BUFFER: usize = 64; // Progress updates queued for the UI before new ones are dropped
+
+// Preview zoom bounds, and how far one wheel notch moves it
+const MIN_ZOOM: f32 = 0.1;
+const MAX_ZOOM: f32 = 5.0;
+const SCROLL_ZOOM_STEP: f32 = 0.1;
+const SCROLL_PIXELS_PER_LINE: f32 = 20.0; // Touchpads report pixels instead of wheel lines
+
+// Scrollable ids for the side-by-side preview, kept at the same position
+const BEFORE_PREVIEW_SCROLL_ID: &str = "before_preview";
+const AFTER_PREVIEW_SCROLL_ID: &str = "after_preview";
+
+// Thumbnail strip for folder, ZIP and cloud inputs
+const THUMBNAIL_SIZE: u32 = 96;
+const THUMBNAIL_BATCH: usize = 10; // Loaded up front, and ahead of the scroll position after that
+const THUMBNAIL_SCROLL_ID: &str = "thumbnails";
+
+// In-app log panel
+const MAX_LOG_ENTRIES: usize = 500;
+const LOG_SCROLL_ID: &str = "log";
+
+// Model and tile size changes are written to the config once they settle for this long
+const VIDEO_EXTENSIONS: [&str; 5] = ["mp4", "avi", "mkv", "mov", "webm"];
+
+const DEFAULT_OUTPUT_SUBFOLDER: &str = "processed";
+const OUTPUT_PATH_LABEL_CHARS: usize = 48;
+
+const CONFIG_SAVE_DEBOUNCE: Duration = Duration::from_secs(2);
+
+// Watch folder: new files are queued once the folder has been quiet this long, so images that
+// are still being written in several chunks aren't picked up half-finished
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(500);
+
+// log_message and log_error run on worker threads, this hands their entries to the UI
+static LOG_SENDER: OnceLock<tokio::sync::mpsc::UnboundedSender<(LogLevel, String)>> = OnceLock::new();
+type LogReceiver = tokio::sync::mpsc::UnboundedReceiver<(LogLevel, String)>;
+
+// Mirrors Config::offline_mode for the download and cloud functions, which run without the config
+static OFFLINE_MODE: AtomicBool = AtomicBool::new(false);
+
+// Blink comparator between before and after, in switches per second
+const DEFAULT_FLICKER_HZ: f32 = 2.0;
+
+// Palette shown under the after image
+const PALETTE_SIZE: u8 = 16;
+const PALETTE_SAMPLE_SIZE: u32 = 256;
+
+// PNGs above this many pixels are upscaled stripe by stripe instead of being decoded whole
+const STREAMING_PIXEL_THRESHOLD: u64 = 100_000_000;
+const STREAMING_MIN_STEP: u32 = 32;
+
+// dHash bits that may differ for two images to count as near-duplicates
+const DUPLICATE_HAMMING_THRESHOLD: u32 = 5;
+
+// Tiled inference
+const DEFAULT_TILE_SIZE: u32 = 512;
+const MIN_TILE_SIZE: u32 = 128;
+const MAX_AUTO_TILE_SIZE: u32 = 1024; // The VRAM formula ignores model activations, so cap it
+const TILE_OVERLAP: u32 = 32; // Input pixels shared by neighbouring tiles, cross-faded to hide seams
+const DEFAULT_MAX_INPUT_DIM: u32 = 512; // Untiled inputs larger than this are downscaled first
+const DEFAULT_OUTPUT_QUALITY: u8 = 90; // JPEG, lossy WebP, AVIF and JPEG XL (distance 1.0)
+
+// Side of the all-zero input used to warm up new sessions, see warmup_session
+const WARMUP_SIZE: u32 = 64;
+
+// Split GPU/CPU batches: images up to this many pixels go to the CPU worker
+const SPLIT_CPU_MAX_PIXELS: u64 = 512 * 512;
+
+// Crop to content for scanned pages
+const CROP_BORDER_STRIP: u32 = 10;
+const CROP_BACKGROUND_THRESHOLD: u8 = 24;
+const CROP_PADDING: u32 = 20;
 
 pub fn main() -> iced::Result {
+    if cli::requested() {
+        std::process::exit(cli::run());
+    }
+    
     let mut settings = Settings::default();
     settings.window.size = Size::new(1200.0, 800.0);
     settings.fonts = vec![
@@ -68,28 +221,166 @@ pub fn main() -> iced::Result {
     ];
     settings.default_font = BODY_FONT;
     settings.default_text_size = 14.into();
-    App::run(settings)
+    let result = App::run(settings);
+    
+    // Extracted ZIPs stay on disk while they're the input, see App::discard_zip_extraction
+    let _ = std::fs::remove_dir_all(zip_temp_root());
+    result
 }
 
+// (before, after) a background computation was started for, compared by pointer on arrival
+type ImagePair = (Arc<DynamicImage>, Arc<DynamicImage>);
+
 #[derive(Debug, Clone)]
 enum Message {
     BrowseFile,
     BrowseFolder,
+    BrowseZip,
     FileSelected(Option<PathBuf>),
     FolderSelected(Option<PathBuf>),
+    RecentSelected(RecentEntry),
+    FileDropped(PathBuf),
+    FileHovered,
+    FileDragLeft,
+    PresetNameChanged(String),
+    SavePreset,
+    PresetSelected(String),
+    DeletePreset,
+    ToggleDarkMode,
+    SaveConfig,
+    ToggleRecursiveScan(bool),
+    BrowseOutputDir,
+    OutputDirSelected(Option<PathBuf>),
+    OutputLocationSelected(OutputLocation),
+    FilenameTemplateChanged(String),
+    ConflictModeSelected(ConflictMode),
+    StartWatching,
+    StopWatching,
+    WatchedFilesArrived(Vec<PathBuf>),
+    WatchFailed(String),
+    ZipSelected(Option<PathBuf>),
+    ZipExtracted(Result<(PathBuf, Vec<PathBuf>), String>), // (archive, extracted images)
+    ZipRepacked(usize, Result<PathBuf, String>), // (completed images, written archive)
     CategorySelected(ModelType),
     ModelSelected(ModelInfo),
+    PipelineStepAdded,
+    PipelineStepSelected(usize, ModelInfo),
+    PipelineStepRemoved(usize),
     PreviewFileSelected(String),
+    ThumbnailLoaded(Result<(PathBuf, iced_image::Handle), String>),
+    ThumbnailsScrolled(RelativeOffset),
     Process,
-    ProcessComplete(Result<Vec<ProcessResult>, String>),
+    CancelProcess,
+    PauseProcess,
+    LogEntry(LogLevel, String),
+    ProgressUpdate { current: usize, total: usize, processed: usize, avg_duration: Duration }, // processed leaves out skipped images
+    ToggleLogPanel,
+    ResumeProcess,
+    ProcessComplete(Result<Vec<ProcessResult>, BatchError>),
+    DownloadModel,
+    DownloadProgress(String, u64, Option<u64>), // Model name, bytes received, total bytes
+    DownloadComplete(String, Result<(), String>),
+    RegistryFetched(Result<Vec<ModelInfo>, String>),
     PreviewLoaded(Result<(DynamicImage, PathBuf), String>),
     ZoomIn,
     ZoomOut,
     ResetZoom,
+    ScrollZoom(f32), // Wheel lines, positive zooms in
+    PreviewHovered(bool),
+    ScrollMoved(RelativeOffset),
     BrowseVideo,
     VideoSelected(Option<PathBuf>),
     ProcessVideo,
+    ResumeVideo,
     VideoProcessComplete(Result<String, String>),
+    RateResult { output_path: PathBuf, rating: u8 },
+    ExportStarred,
+    DeleteUnrated,
+    ToggleGpuThrottle(bool),
+    GpuTempLimitChanged(u32),
+    Tick,
+    ToggleVideoSegments(bool),
+    VideoSegmentMinutesChanged(u32),
+    ToggleAutoSelectModel(bool),
+    ClassifierRouteChanged(ImageClass, ModelInfo),
+    OutputFormatSelected(OutputFormat),
+    OutputQualityChanged(u8),
+    CopyDataUri,
+    ToggleDeinterlace(bool),
+    TogglePreserveSubtitles(bool),
+    VideoCrfChanged(u8),
+    ToggleVideoBitrateMode(bool),
+    VideoBitrateChanged(u32),
+    VideoStartFrameChanged(String),
+    VideoEndFrameChanged(String),
+    SortResults(SortColumn, SortOrder),
+    ToggleRatedOnly(bool),
+    ToggleCropToContent(bool),
+    CloudUrlChanged(String),
+    LoadCloudUrls,
+    CloudDownloaded(Result<Vec<(PathBuf, String)>, String>),
+    ToggleUploadToCloud(bool),
+    CloudUploadComplete(Result<usize, String>),
+    CopyPalette,
+    ToggleFailFast(bool),
+    ToggleOfflineMode(bool),
+    HashesComputed(u64, HashMap<PathBuf, u64>), // (hash_generation it was started for, hashes)
+    SkipDuplicates,
+    ToggleTiling(bool),
+    TileSizeChanged(u32),
+    ToggleOptimizePng(bool),
+    ToggleAutoOpenOutput(bool),
+    ResetSettings,
+    ToggleChangeHeatmap(bool),
+    ToggleDiffView,
+    SsimComputed(ImagePair, Option<(f64, Arc<DynamicImage>)>),
+    PsnrComputed(ImagePair, Option<f64>),
+    ChangeHeatmapComputed(ImagePair, Option<Arc<DynamicImage>>),
+    PaletteExtracted(Arc<DynamicImage>, Vec<[u8; 3]>), // (after image it was extracted from, palette)
+    ToggleComparisonMode,
+    SplitCursorMoved(f32), // Cursor x over the split preview, in display pixels
+    SplitDragStarted,
+    SplitDragEnded,
+    ExportComparison,
+    ComparisonSaved(Option<Result<PathBuf, String>>), // None when the save dialog was cancelled
+    ToggleDownloadSpeedLimit(bool),
+    DownloadSpeedLimitChanged(u32),
+    QuantizeModel,
+    DeleteCachedModel,
+    ImportCustomModel,
+    CustomModelFileSelected(Option<PathBuf>),
+    CustomModelFormChanged(CustomModelForm),
+    SaveCustomModel,
+    CancelCustomModel,
+    InspectModel(ModelInfo),
+    ModelInspected(Result<ModelInspection, String>),
+    ToggleInspectorCollapsed,
+    CloseInspector,
+    NormOverrideSelected(Option<NormalizationRange>),
+    QuantizeComplete(Result<QuantizeReport, String>),
+    Benchmark,
+    BenchmarkRunsChanged(u32),
+    BenchmarkComplete(Result<BenchmarkResult, String>),
+    ChecksumFailed(String),
+    ToggleSplitGpuCpu(bool),
+    BatchSizeChanged(u32),
+    ExifModeSelected(ExifMode),
+    ColorProfileModeSelected(ColorProfileMode),
+    ExecutionProviderSelected(ExecutionProvider),
+    #[cfg(feature = "coreml")]
+    CoreMLChecked(Result<(), String>),
+    #[cfg(feature = "openvino")]
+    OpenVinoDeviceSelected(String),
+    ExecutionProviderProbed(ExecutionProvider),
+    VideoCodecsProbed(Vec<VideoCodec>),
+    VideoCodecSelected(VideoCodec),
+    DiskSpaceLow { free_gb: f64 },
+    DiskSpaceContinue,
+    DiskSpaceAbort,
+    StartFlickerMode,
+    StopFlickerMode,
+    FlickerRateChanged(f32),
+    FlickerTick,
 }
 
 struct App {
@@ -100,28 +391,123 @@ struct App {
     selected_model: Option<ModelInfo>,
     image_files: Vec<PathBuf>,
     selected_preview_file: Option<String>,
+    thumbnail_cache: HashMap<PathBuf, iced_image::Handle>,
+    thumbnails_requested: HashSet<PathBuf>, // Loading or loaded, so scrolling doesn't queue them twice
     before_image: Option<Arc<DynamicImage>>,
     after_image: Option<Arc<DynamicImage>>,
     process_results: Vec<ProcessResult>,
     processing: bool,
     status_message: String,
     zoom_level: f32,
+    preview_hovered: bool, // Wheel and +/- keys zoom while the pointer is over the preview
+    // Relative rather than absolute, the after image is larger than the before image by the scale
+    scroll_offset: RelativeOffset,
+    gpu_temp_limit: Option<u32>, // Celsius, None = never throttle
+    gpu_temp: Arc<AtomicU32>,    // Latest reading from the batch worker, 0 = unknown
+    batch_started: Option<std::time::Instant>,
+    images_processed: usize, // As of the last ProgressUpdate, newer counts are new completion_times
+    completion_times: VecDeque<std::time::Instant>, // Within THROUGHPUT_WINDOW
+    throughput_history: VecDeque<(std::time::Instant, f32)>, // images/minute samples
+    disk_space: Arc<DiskSpaceGate>,
+    cancel_token: Option<Arc<AtomicBool>>, // Set while a batch runs, storing true stops it before the next image
+    pause_flag: Arc<AtomicBool>, // Holds the batch between images while true
+    progress: Arc<Mutex<ProgressState>>, // Written by process_images, polled by a subscription
+    batch_progress: Option<(usize, usize, Duration)>, // Last ProgressUpdate: done, total, average per image
+    disk_space_low: Option<f64>, // Free GB reported by a paused batch, shown as a prompt
+    video_segment_duration: Option<f32>, // Seconds per segment, None = process in one pass
+    auto_select_model: bool,
+    classifier_routing: ClassifierRouting,
+    output_format: OutputFormat,
+    deinterlace_video: bool,
+    video_options: VideoProcessOptions,
+    video_checkpoint: Option<(usize, usize)>, // Frames done and total in a resumable run of the loaded video
+    results_sort: (SortColumn, SortOrder),
+    results_rated_only: bool,
+    crop_to_content: bool,
+    config: Config,
+    recent: VecDeque<RecentEntry>, // Mirrored into config.recent whenever it changes
+    dark_mode: bool,
+    model_cache: ModelCache,
+    cloud_url_input: String,
+    cloud_sources: HashMap<PathBuf, String>, // Downloaded local file -> source URL
+    upload_output_to_cloud: bool,
+    after_palette: Vec<[u8; 3]>, // Dominant colours of after_image
+    fail_fast: bool,
+    image_hashes: HashMap<PathBuf, u64>,
+    hash_generation: u64, // Bumped whenever image_files is replaced, older HashesComputed are dropped
+    duplicate_pairs: Vec<(PathBuf, PathBuf)>, // (kept, near-duplicate of it)
+    tile_size: Option<u32>, // Pixels per tile side, None = downscale large images instead of tiling
+    last_tile_size: Option<u32>, // Restored when tiling is re-enabled
+    optimize_png: bool,
+    split_gpu_cpu: bool,
+    ort_config: OrtConfig,
+    #[cfg(feature = "openvino")]
+    openvino_available: bool, // Greys out OpenVINO when the runtime lacks it
+    active_ep: Option<ExecutionProvider>, // What Auto resolved to at startup, None while probing
+    available_video_codecs: Vec<VideoCodec>, // Encoders the installed ffmpeg has, empty while probing
+    process_options: ProcessOptions,
+    flicker_mode: bool,
+    flicker_hz: f32,
+    flicker_show_after: bool,
+    auto_open_output: bool, // Open the output in the default viewer once processed
+    show_change_heatmap: bool,
+    change_heatmap: Option<Arc<DynamicImage>>, // After image with the change heatmap blended in
+    show_diff_view: bool,
+    ssim_map: Option<Arc<DynamicImage>>, // Local SSIM heatmap at the before image's resolution
+    ssim_score: Option<f64>,
+    comparison_mode: ComparisonMode,
+    split_position: f32, // Divider position in SplitLine mode, 0.0 = all after, 1.0 = all before
+    split_before: Option<Arc<image::RgbaImage>>, // Before image upsampled to the after size for SplitLine
+    split_image: Option<iced_image::Handle>, // Composited split preview, rebuilt when the divider or images change
+    split_cursor_x: f32, // Last cursor x over the split preview, in display pixels
+    split_dragging: bool,
+    download_speed_limit_kbps: Option<u32>, // None = unlimited
+    download_progress: Option<(String, u64, Option<u64>)>, // Model name, bytes received, total bytes
+    process_after_download: Option<Vec<PathBuf>>, // Files of the batch that started the download, run once it's done
+    quantizing: bool,
+    quantize_report: Option<QuantizeReport>,
+    benchmark_runs: u32,
+    benchmarking: bool,
+    benchmark_result: Option<BenchmarkResult>,
+    output_convention: Option<String>, // Naming template found in the current output directory
+    norm_override: Option<NormalizationRange>, // Replaces the model's input and output norm, cleared on model change
+    custom_model_form: Option<CustomModelForm>,
+    model_inspection: Option<ModelInspection>,
+    inspector_collapsed: bool,
+    pipeline: Vec<ModelInfo>, // Models run in order when there are two or more steps
+    last_psnr: Option<f64>, // Previewed before/after pair from the last run, infinite when identical
+    log_entries: VecDeque<(LogLevel, String)>, // Newest last, at most MAX_LOG_ENTRIES
+    log_receiver: Arc<Mutex<Option<LogReceiver>>>, // Taken by the log subscription
+    log_collapsed: bool,
+    presets: Vec<Preset>,
+    preset_name_input: String,
+    selected_preset: Option<String>,
+    output_dir: Option<PathBuf>, // Picked custom output folder, kept while another location is selected
+    output_location: OutputLocation,
+    drag_hover: bool, // A file is being dragged over the window, the preview shows the drop target
+    recursive_scan: bool, // Folder inputs include subfolders, mirrored below the output dir
+    watching: Option<PathBuf>, // Only active while it is still the loaded folder, see watched_folder
+    watch_queue: Vec<PathBuf>, // New files waiting for the running batch to finish
+    config_save_due: Option<std::time::Instant>, // Set by schedule_config_save, pushed back by each change
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 enum InputType {
     None,
     File,
     Folder,
-    Video
+    Video,
+    CloudUrl,
+    Zip,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 enum ModelType {
     Upscaling,
     Denoising,
 	Deblur,
     Enhancement,
+    Classification, // Helper models, not listed as a processing category
 }
 
 impl std::fmt::Display for ModelType {
@@ -131,25 +517,159 @@ impl std::fmt::Display for ModelType {
             ModelType::Denoising => write!(f, "Denoising"),
             ModelType::Enhancement => write!(f, "Enhancement"),
             ModelType::Deblur => write!(f, "Deblur"),
+            ModelType::Classification => write!(f, "Classification"),
+        }
+    }
+}
+
+// Output classes of the image-type classifier, in logit order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum ImageClass {
+    Photo,
+    Anime,
+    Text,
+    Art,
+}
+
+impl ImageClass {
+    const ALL: [ImageClass; 4] = [ImageClass::Photo, ImageClass::Anime, ImageClass::Text, ImageClass::Art];
+}
+
+impl std::fmt::Display for ImageClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImageClass::Photo => write!(f, "Photo"),
+            ImageClass::Anime => write!(f, "Anime"),
+            ImageClass::Text => write!(f, "Text"),
+            ImageClass::Art => write!(f, "Art"),
+        }
+    }
+}
+
+// Which model handles each image class when auto-select is on (by model name)
+#[derive(Debug, Clone)]
+struct ClassifierRouting {
+    photo: String,
+    anime: String,
+    text: String,
+    art: String,
+}
+
+impl Default for ClassifierRouting {
+    fn default() -> Self {
+        Self {
+            photo: "RealESRGAN-4x".to_string(),
+            anime: "4x_APISR_GRL_GAN_generator".to_string(),
+            text: "UltraSharp-4x".to_string(),
+            art: "RealESR-General-4x".to_string(),
+        }
+    }
+}
+
+impl ClassifierRouting {
+    fn model_for(&self, class: ImageClass) -> &str {
+        match class {
+            ImageClass::Photo => &self.photo,
+            ImageClass::Anime => &self.anime,
+            ImageClass::Text => &self.text,
+            ImageClass::Art => &self.art,
+        }
+    }
+    
+    fn set(&mut self, class: ImageClass, model_name: String) {
+        match class {
+            ImageClass::Photo => self.photo = model_name,
+            ImageClass::Anime => self.anime = model_name,
+            ImageClass::Text => self.text = model_name,
+            ImageClass::Art => self.art = model_name,
         }
     }
 }
 
+// Classifier and routing table resolved to concrete models for a batch
+#[derive(Debug, Clone)]
+struct AutoSelect {
+    classifier: ModelInfo,
+    routes: Vec<(ImageClass, ModelInfo)>,
+}
+
 // Add this enum near ModelType
-#[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 enum TensorFormat {
     NCHW, // Standard: [batch, channels, height, width]
     NHWC, // Alternative: [batch, height, width, channels]
 }
 
+impl std::fmt::Display for TensorFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TensorFormat::NCHW => write!(f, "NCHW"),
+            TensorFormat::NHWC => write!(f, "NHWC"),
+        }
+    }
+}
+
+// Element type of the model's input and output tensors; F16 is converted at the session
+// boundary, preprocessing and postprocessing always work in f32
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+enum TensorDtype {
+    #[default]
+    F32,
+    F16, // Half-precision export, roughly halves VRAM
+}
+
+impl TensorDtype {
+    fn from_element_type(ty: &ort::tensor::TensorElementType) -> Option<Self> {
+        match ty {
+            ort::tensor::TensorElementType::Float32 => Some(TensorDtype::F32),
+            ort::tensor::TensorElementType::Float16 => Some(TensorDtype::F16),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for TensorDtype {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TensorDtype::F32 => write!(f, "float32"),
+            TensorDtype::F16 => write!(f, "float16"),
+        }
+    }
+}
+
 // Add these enums near ModelType
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 enum NormalizationRange {
     ZeroOne,      // [0, 1]
     MinusOneOne,  // [-1, 1]
 }
 
-#[derive(Debug, Clone, PartialEq)]
+// Sample depth the model is fed and produces; Bit16 keeps 16-bit PNG/TIFF dynamic range
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum BitDepth {
+    Bit8,
+    Bit16,
+}
+
+impl BitDepth {
+    fn max_value(self) -> f32 {
+        match self {
+            BitDepth::Bit8 => u8::MAX as f32,
+            BitDepth::Bit16 => u16::MAX as f32,
+        }
+    }
+    
+    // PNG for 8-bit; 16-bit output goes to TIFF so it is never downsampled
+    fn output_extension(self) -> &'static str {
+        match self {
+            BitDepth::Bit8 => "png",
+            BitDepth::Bit16 => "tiff",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 struct ModelInfo {
     name: String,
     url: String,
@@ -162,13 +682,311 @@ struct ModelInfo {
     input_norm: NormalizationRange,  // NEW: Input normalization
     output_norm: NormalizationRange,
 	min_dimension: Option<u32>, // NEW: Minimum width/height requirement
+    channel_independent: bool, // Run a 1-channel model on R, G and B separately
+    quantized: bool, // INT8 copy made with "Quantize Model", stored locally
+    ycbcr_mode: bool, // Run a 1-channel model on luma only, chroma is bicubic-upscaled
+    max_tile_size: Option<u32>, // Larger inputs are tiled instead of downscaled, None = single pass
+    // Per-channel standardization, (pixel/255 - mean) / std; None = use input_norm/output_norm
+    input_mean: Option<[f32; 3]>,
+    input_std: Option<[f32; 3]>,
+    output_mean: Option<[f32; 3]>,
+    output_std: Option<[f32; 3]>,
+    bit_depth: BitDepth,
+    input_channels: u8, // 1 = grayscale tensor fed from the luma of the image, otherwise RGB
+    output_channels: u8,
+    checksum: Option<String>, // Hex SHA-256 of the .onnx file, verified after download
+    #[serde(default)]
+    output_index: usize, // Graph output holding the result, for models with auxiliary outputs
+    #[serde(default)]
+    tensor_dtype: TensorDtype, // F16 for half-precision exports
+    #[serde(default)]
+    pad_mode: PadMode, // How pad_to_multiple fills the border, Reflect suits models trained with reflect padding
+}
+
+impl ModelInfo {
+    // A zero std would turn every pixel into Inf/NaN, so reject such models up front
+    fn validate(&self) -> Result<()> {
+        for (field, std) in [("input_std", self.input_std), ("output_std", self.output_std)] {
+            if std.is_some_and(|std| std.iter().any(|&s| s == 0.0 || !s.is_finite())) {
+                return Err(anyhow::anyhow!("Model {} has an invalid {}: values must be non-zero", self.name, field));
+            }
+        }
+        // run_ycbcr splits the RGB tensor into luma and chroma itself
+        if self.ycbcr_mode && (self.input_channels != 3 || self.output_channels != 3) {
+            return Err(anyhow::anyhow!("Model {} uses YCbCr mode, which expects 3 input and output channels", self.name));
+        }
+        Ok(())
+    }
+    
+    fn input_standardization(&self) -> Option<([f32; 3], [f32; 3])> {
+        (self.input_mean.is_some() || self.input_std.is_some())
+            .then(|| (self.input_mean.unwrap_or([0.0; 3]), self.input_std.unwrap_or([1.0; 3])))
+    }
+    
+    fn output_standardization(&self) -> Option<([f32; 3], [f32; 3])> {
+        (self.output_mean.is_some() || self.output_std.is_some())
+            .then(|| (self.output_mean.unwrap_or([0.0; 3]), self.output_std.unwrap_or([1.0; 3])))
+    }
 }
 
 impl std::fmt::Display for ModelInfo {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self.model_type {
             ModelType::Upscaling => write!(f, "{} - {} ({}x)", self.category, self.description, self.scale),
-            ModelType::Denoising | ModelType::Deblur | ModelType::Enhancement => write!(f, "{} - {}", self.category, self.description), 
+            ModelType::Denoising | ModelType::Deblur | ModelType::Enhancement | ModelType::Classification => write!(f, "{} - {}", self.category, self.description), 
+        }
+    }
+}
+
+// Category of user-imported models; they are persisted in custom_models.json
+const CUSTOM_MODEL_CATEGORY: &str = "Custom";
+
+// Field combinations no model can have, as messages for the custom model form; empty = valid
+fn validate_model_info(info: &ModelInfo) -> Vec<String> {
+    let mut problems = Vec::new();
+    
+    if info.name.is_empty() {
+        problems.push("Name is required".to_string());
+    } else if info.name.contains(|c: char| "/\\:*?\"<>|".contains(c)) {
+        problems.push("Name cannot contain / \\ : * ? \" < > |, it becomes the file name".to_string());
+    }
+    if info.scale == 0 {
+        problems.push("Scale must be at least 1".to_string());
+    } else if info.model_type == ModelType::Upscaling && info.scale == 1 {
+        problems.push("Upscaling models need a scale above 1".to_string());
+    } else if info.model_type != ModelType::Upscaling && info.scale != 1 {
+        problems.push(format!("{} models keep the image size, scale must be 1", info.model_type));
+    }
+    if info.window_size == 0 {
+        problems.push("Window size must be at least 1".to_string());
+    } else if info.window_size > 1 && info.tensor_format == TensorFormat::NHWC {
+        problems.push("Window padding is only supported for NCHW models, use window size 1 with NHWC".to_string());
+    }
+    if let Err(e) = info.validate() {
+        problems.push(e.to_string());
+    }
+    
+    problems
+}
+
+// "Add Custom Model" form for a picked .onnx file; numbers are kept as typed until saved
+#[derive(Debug, Clone)]
+struct CustomModelForm {
+    source_path: PathBuf,
+    name: String,
+    model_type: ModelType,
+    scale: String,
+    window_size: String,
+    tensor_format: TensorFormat,
+    norm: NormalizationRange,
+    channel_independent: bool, // A grayscale model run on R, G and B separately, see ModelInfo
+}
+
+impl CustomModelForm {
+    fn new(source_path: PathBuf) -> Self {
+        let name = source_path.file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        
+        Self {
+            source_path,
+            name,
+            model_type: ModelType::Upscaling,
+            scale: "4".to_string(),
+            window_size: "1".to_string(),
+            tensor_format: TensorFormat::NCHW,
+            norm: NormalizationRange::ZeroOne,
+            channel_independent: false,
+        }
+    }
+    
+    fn to_model_info(&self) -> Result<ModelInfo, String> {
+        let scale = self.scale.trim().parse()
+            .map_err(|_| format!("Scale '{}' is not a whole number", self.scale))?;
+        let window_size = self.window_size.trim().parse()
+            .map_err(|_| format!("Window size '{}' is not a whole number", self.window_size))?;
+        let name = self.name.trim().to_string();
+        
+        Ok(ModelInfo {
+            name: name.clone(),
+            url: "local".to_string(),
+            model_type: self.model_type.clone(),
+            scale,
+            window_size,
+            description: name,
+            category: CUSTOM_MODEL_CATEGORY.to_string(),
+            tensor_format: self.tensor_format.clone(),
+            input_norm: self.norm,
+            output_norm: self.norm,
+            min_dimension: None,
+            channel_independent: self.channel_independent,
+            quantized: false,
+            ycbcr_mode: false,
+            max_tile_size: Some(DEFAULT_TILE_SIZE),
+            input_mean: None,
+            input_std: None,
+            output_mean: None,
+            output_std: None,
+            bit_depth: BitDepth::Bit8,
+            input_channels: 3,
+            output_channels: 3,
+            checksum: None,
+            output_index: 0,
+            tensor_dtype: TensorDtype::F32,
+            pad_mode: PadMode::Symmetric,
+        })
+    }
+    
+    // Everything that blocks saving, including a name already taken by another model
+    fn problems(&self, existing: &[ModelInfo]) -> Vec<String> {
+        match self.to_model_info() {
+            Ok(info) => {
+                let mut problems = validate_model_info(&info);
+                if existing.iter().any(|m| m.name == info.name) {
+                    problems.push(format!("A model named {} already exists", info.name));
+                }
+                problems
+            }
+            Err(e) => vec![e],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+enum OutputFormat {
+    Png, // TIFF for 16-bit models, see BitDepth::output_extension
+    Jpeg(u8), // Quality 1-100
+    WebpLossless,
+    WebpLossy(u8),
+    Tiff,
+    #[cfg(feature = "avif")]
+    Avif(u8),
+    #[cfg(feature = "jxl")]
+    Jxl { quality: f32, lossless: bool }, // Quality on the cjxl -q scale, ignored when lossless
+    Base64DataUri { mime: String }, // Kept in memory instead of written to disk
+}
+
+impl OutputFormat {
+    // Picker entries; lossy formats start at the given quality
+    fn all(quality: u8) -> Vec<OutputFormat> {
+        let mut formats = vec![
+            OutputFormat::Png,
+            OutputFormat::Jpeg(quality),
+            OutputFormat::WebpLossless,
+            OutputFormat::WebpLossy(quality),
+            OutputFormat::Tiff,
+        ];
+        #[cfg(feature = "avif")]
+        formats.push(OutputFormat::Avif(quality));
+        #[cfg(feature = "jxl")]
+        formats.extend([
+            OutputFormat::Jxl { quality: quality as f32, lossless: false },
+            OutputFormat::Jxl { quality: 100.0, lossless: true },
+        ]);
+        formats.push(OutputFormat::Base64DataUri { mime: "image/png".to_string() });
+        formats
+    }
+    
+    fn quality(&self) -> Option<u8> {
+        match self {
+            OutputFormat::Jpeg(quality) | OutputFormat::WebpLossy(quality) => Some(*quality),
+            #[cfg(feature = "avif")]
+            OutputFormat::Avif(quality) => Some(*quality),
+            #[cfg(feature = "jxl")]
+            OutputFormat::Jxl { quality, lossless: false } => Some(quality.round() as u8),
+            _ => None,
+        }
+    }
+    
+    fn with_quality(&self, quality: u8) -> OutputFormat {
+        match self {
+            OutputFormat::Jpeg(_) => OutputFormat::Jpeg(quality),
+            OutputFormat::WebpLossy(_) => OutputFormat::WebpLossy(quality),
+            #[cfg(feature = "avif")]
+            OutputFormat::Avif(_) => OutputFormat::Avif(quality),
+            #[cfg(feature = "jxl")]
+            OutputFormat::Jxl { lossless: false, .. } => OutputFormat::Jxl { quality: quality as f32, lossless: false },
+            other => other.clone(),
+        }
+    }
+    
+    // Whether save_output keeps an alpha channel; data URIs switch to PNG on their own
+    fn keeps_alpha(&self) -> bool {
+        !matches!(self, OutputFormat::Jpeg(_))
+    }
+    
+    fn extension(&self, bit_depth: BitDepth) -> &'static str {
+        match self {
+            OutputFormat::Png | OutputFormat::Base64DataUri { .. } => bit_depth.output_extension(),
+            OutputFormat::Jpeg(_) => "jpg",
+            OutputFormat::WebpLossless | OutputFormat::WebpLossy(_) => "webp",
+            OutputFormat::Tiff => "tiff",
+            #[cfg(feature = "avif")]
+            OutputFormat::Avif(_) => "avif",
+            #[cfg(feature = "jxl")]
+            OutputFormat::Jxl { .. } => "jxl",
+        }
+    }
+}
+
+impl std::fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutputFormat::Png => write!(f, "PNG file"),
+            OutputFormat::Jpeg(_) => write!(f, "JPEG file"),
+            OutputFormat::WebpLossless => write!(f, "WebP file (lossless)"),
+            OutputFormat::WebpLossy(_) => write!(f, "WebP file (lossy)"),
+            OutputFormat::Tiff => write!(f, "TIFF file"),
+            #[cfg(feature = "avif")]
+            OutputFormat::Avif(_) => write!(f, "AVIF file"),
+            #[cfg(feature = "jxl")]
+            OutputFormat::Jxl { lossless: true, .. } => write!(f, "JPEG XL file (lossless)"),
+            #[cfg(feature = "jxl")]
+            OutputFormat::Jxl { lossless: false, .. } => write!(f, "JPEG XL file (lossy)"),
+            OutputFormat::Base64DataUri { mime } => write!(f, "Data URI ({})", mime),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortColumn {
+    Filename,
+    InputSize,
+    OutputSize,
+    Scale,
+    Duration,
+    FileSize,
+    Rating,
+}
+
+impl SortColumn {
+    fn label(&self) -> &'static str {
+        match self {
+            SortColumn::Filename => "filename",
+            SortColumn::InputSize => "input size",
+            SortColumn::OutputSize => "output size",
+            SortColumn::Scale => "scale",
+            SortColumn::Duration => "duration",
+            SortColumn::FileSize => "file size",
+            SortColumn::Rating => "rating",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortOrder {
+    Ascending,
+    Descending,
+    None,
+}
+
+impl SortOrder {
+    // Header clicks cycle ascending -> descending -> unsorted
+    fn next(self) -> Self {
+        match self {
+            SortOrder::Ascending => SortOrder::Descending,
+            SortOrder::Descending => SortOrder::None,
+            SortOrder::None => SortOrder::Ascending,
         }
     }
 }
@@ -180,461 +998,778 @@ struct ProcessResult {
     input_dims: (u32, u32),
     output_dims: (u32, u32),
     duration: f32,
+    input_bytes: u64,
+    output_bytes: u64,
+    rating: Option<u8>, // 1-5 stars, None = unrated
+    data_uri: Option<String>, // Set instead of writing output_path for OutputFormat::Base64DataUri
+    bytes_saved: u64, // By the oxipng pass, 0 when it didn't run
+    pipeline_steps: Vec<String>, // Model names in order for chained runs, empty for a single model
+    status: ProcessResultStatus,
 }
 
-impl Application for App {
-    type Executor = executor::Default;
-    type Message = Message;
-    type Theme = Theme;
-    type Flags = ();
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ProcessResultStatus {
+    Processed,
+    Skipped, // ConflictMode::Skip found the output already there, output_path is that file
+}
 
-    fn new(_flags: ()) -> (Self, Command<Message>) {
-        let models = vec![
-            // ===== UPSCALING MODELS =====
-            ModelInfo {
-                name: "swin2SR-realworld-sr-x4-64-bsrgan-psnr".to_string(),
-                url: "https://huggingface.co/Xenova/swin2SR-realworld-sr-x4-64-bsrgan-psnr/resolve/main/onnx/model.onnx".to_string(),
-                model_type: ModelType::Upscaling,
-                scale: 4,
-                window_size: 8,
-                description: "Real-world photos (4x)".to_string(),
-                category: "Swin2SR".to_string(),
-				tensor_format: TensorFormat::NCHW,
-				input_norm: NormalizationRange::ZeroOne,
-				output_norm: NormalizationRange::ZeroOne,
-				min_dimension: None, // No minimum for most models
-            },
-            ModelInfo {
-                name: "swin2SR-classical-sr-x4-64".to_string(),
-                url: "https://huggingface.co/Xenova/swin2SR-classical-sr-x4-64/resolve/main/onnx/model.onnx".to_string(),
-                model_type: ModelType::Upscaling,
-                scale: 4,
-                window_size: 8,
-                description: "Clean images (4x)".to_string(),
-                category: "Swin2SR".to_string(),
-				tensor_format: TensorFormat::NCHW,
-				input_norm: NormalizationRange::ZeroOne,
-				output_norm: NormalizationRange::ZeroOne,
-				min_dimension: None, // No minimum for most models
-            },
-            ModelInfo {
-                name: "swin2SR-lightweight-x2-64".to_string(),
-                url: "https://huggingface.co/Xenova/swin2SR-lightweight-x2-64/resolve/main/onnx/model.onnx".to_string(),
-                model_type: ModelType::Upscaling,
-                scale: 2,
-                window_size: 8,
-                description: "Lightweight (2x)".to_string(),
-                category: "Swin2SR".to_string(),
-				tensor_format: TensorFormat::NCHW,
-				input_norm: NormalizationRange::ZeroOne,
-				output_norm: NormalizationRange::ZeroOne,
-				min_dimension: None, // No minimum for most models
-            },
-            ModelInfo {
-                name: "swin2SR-compressed-sr-x4-48".to_string(),
-                url: "https://huggingface.co/Xenova/swin2SR-compressed-sr-x4-48/resolve/main/onnx/model.onnx".to_string(),
-                model_type: ModelType::Upscaling,
-                scale: 4,
-                window_size: 8,
-                description: "Compressed/JPEG (4x)".to_string(),
-                category: "Swin2SR".to_string(),
-				tensor_format: TensorFormat::NCHW,
-				input_norm: NormalizationRange::ZeroOne,
-				output_norm: NormalizationRange::ZeroOne,
-				min_dimension: None, // No minimum for most models
-            },
-            ModelInfo {
-                name: "2x_APISR_RRDB_GAN_generator".to_string(),
-                url: "https://huggingface.co/Xenova/2x_APISR_RRDB_GAN_generator-onnx/resolve/main/onnx/model.onnx".to_string(),
-                model_type: ModelType::Upscaling,
-                scale: 2,
-                window_size: 1,
-                description: "APISR GAN (2x) Anime".to_string(),
-                category: "APISR".to_string(),
-				tensor_format: TensorFormat::NCHW,
-				input_norm: NormalizationRange::ZeroOne,
-				output_norm: NormalizationRange::ZeroOne,
-				min_dimension: None, // No minimum for most models
-            },
-            ModelInfo {
-                name: "4x_APISR_GRL_GAN_generator".to_string(),
-                url: "https://huggingface.co/Xenova/4x_APISR_GRL_GAN_generator-onnx/resolve/main/onnx/model.onnx".to_string(),
-                model_type: ModelType::Upscaling,
-                scale: 4,
-                window_size: 1,
-                description: "APISR GAN (4x) Anime".to_string(),
-                category: "APISR".to_string(),
-				tensor_format: TensorFormat::NCHW,
-				input_norm: NormalizationRange::ZeroOne,
-				output_norm: NormalizationRange::ZeroOne,
-				min_dimension: None, // No minimum for most models
-            },
-            
-            // ===== RESTORATION & ENHANCEMENT MODELS (TensorStack) =====
-            ModelInfo {
-                name: "SwinIR-Noise".to_string(),
-                url: "https://huggingface.co/TensorStack/Upscale-amuse/resolve/main/SwinIR-Noise/model.onnx".to_string(),
-                model_type: ModelType::Denoising,
-                scale: 1,
-                window_size: 8,
-                description: "Noise reduction".to_string(),
-                category: "SwinIR".to_string(),
-				tensor_format: TensorFormat::NCHW,
-				input_norm: NormalizationRange::ZeroOne,
-				output_norm: NormalizationRange::ZeroOne,
-				min_dimension: None, // No minimum for most models
-            },
-            ModelInfo {
-                name: "SwinIR-BSRGAN-4x".to_string(),
-                url: "https://huggingface.co/TensorStack/Upscale-amuse/resolve/main/SwinIR-BSRGAN-4x/model.onnx".to_string(),
-                model_type: ModelType::Enhancement,
-                scale: 4,
-                window_size: 8,
-                description: "Real degradations (4x)".to_string(),
-                category: "SwinIR".to_string(),
-				tensor_format: TensorFormat::NCHW,
-				input_norm: NormalizationRange::ZeroOne,
-				output_norm: NormalizationRange::ZeroOne,
-				min_dimension: None, // No minimum for most models
-            },
-            ModelInfo {
-                name: "BSRGAN-2x".to_string(),
-                url: "https://huggingface.co/TensorStack/Upscale-amuse/resolve/main/BSRGAN-2x/model.onnx".to_string(),
-                model_type: ModelType::Enhancement,
-                scale: 2,
-                window_size: 1,
-                description: "Blind SR (2x)".to_string(),
-                category: "BSRGAN".to_string(),
-				tensor_format: TensorFormat::NCHW,
-				input_norm: NormalizationRange::ZeroOne,
-				output_norm: NormalizationRange::ZeroOne,
-				min_dimension: None, // No minimum for most models
-            },
-            ModelInfo {
-                name: "RealESRGAN-2x".to_string(),
-                url: "https://huggingface.co/TensorStack/Upscale-amuse/resolve/main/RealESRGAN-2x/model.onnx".to_string(),
-                model_type: ModelType::Enhancement,
-                scale: 2,
-                window_size: 1,
-                description: "Real-world SR (2x)".to_string(),
-                category: "RealESRGAN".to_string(),
-				tensor_format: TensorFormat::NCHW,
-				input_norm: NormalizationRange::ZeroOne,
-				output_norm: NormalizationRange::ZeroOne,
-				min_dimension: None, // No minimum for most models
-            },
-            ModelInfo {
-                name: "RealESRGAN-4x".to_string(),
-                url: "https://huggingface.co/TensorStack/Upscale-amuse/resolve/main/RealESRGAN-4x/model.onnx".to_string(),
-                model_type: ModelType::Enhancement,
-                scale: 4,
-                window_size: 1,
-                description: "Real-world SR (4x)".to_string(),
-                category: "RealESRGAN".to_string(),
-				tensor_format: TensorFormat::NCHW,
-				input_norm: NormalizationRange::ZeroOne,
-				output_norm: NormalizationRange::ZeroOne,
-				min_dimension: None, // No minimum for most models
-            },
-            ModelInfo {
-                name: "RealESR-General-4x".to_string(),
-                url: "https://huggingface.co/TensorStack/Upscale-amuse/resolve/main/RealESR-General-4x/model.onnx".to_string(),
-                model_type: ModelType::Enhancement,
-                scale: 4,
-                window_size: 1,
-                description: "General purpose (4x)".to_string(),
-                category: "RealESRGAN".to_string(),
-				tensor_format: TensorFormat::NCHW,
-				input_norm: NormalizationRange::ZeroOne,
-				output_norm: NormalizationRange::ZeroOne,
-				min_dimension: None, // No minimum for most models
-            },
-            ModelInfo {
-                name: "Swin2SR-Classical-2x".to_string(),
-                url: "https://huggingface.co/TensorStack/Upscale-amuse/resolve/main/Swin2SR-Classical-2x/model.onnx".to_string(),
-                model_type: ModelType::Upscaling,
-                scale: 2,
-                window_size: 8,
-                description: "Classical SR (2x)".to_string(),
-                category: "Swin2SR-TS".to_string(),
-				tensor_format: TensorFormat::NCHW,
-				input_norm: NormalizationRange::ZeroOne,
-				output_norm: NormalizationRange::ZeroOne,
-				min_dimension: None, // No minimum for most models
-            },
-            ModelInfo {
-                name: "Swin2SR-Classical-4x".to_string(),
-                url: "https://huggingface.co/TensorStack/Upscale-amuse/resolve/main/Swin2SR-Classical-4x/model.onnx".to_string(),
-                model_type: ModelType::Upscaling,
-                scale: 4,
-                window_size: 8,
-                description: "Classical SR (4x)".to_string(),
-                category: "Swin2SR-TS".to_string(),
-				tensor_format: TensorFormat::NCHW,
-				input_norm: NormalizationRange::ZeroOne,
-				output_norm: NormalizationRange::ZeroOne,
-				min_dimension: None, // No minimum for most models
-            },
-            ModelInfo {
-                name: "UltraSharp-4x".to_string(),
-                url: "https://huggingface.co/TensorStack/Upscale-amuse/resolve/main/UltraSharp-4x/model.onnx".to_string(),
-                model_type: ModelType::Enhancement,
-                scale: 4,
-                window_size: 1,
-                description: "Ultra sharp details (4x)".to_string(),
-                category: "Custom".to_string(),
-				tensor_format: TensorFormat::NCHW,
-				input_norm: NormalizationRange::ZeroOne,
-				output_norm: NormalizationRange::ZeroOne,
-				min_dimension: None, // No minimum for most models
-            },
-            ModelInfo {
-                name: "UltraMix-Smooth-4x".to_string(),
-                url: "https://huggingface.co/TensorStack/Upscale-amuse/resolve/main/UltraMix-Smooth-4x/model.onnx".to_string(),
-                model_type: ModelType::Enhancement,
-                scale: 4,
-                window_size: 1,
-                description: "Ultra smooth details (4x)".to_string(),
-                category: "Custom".to_string(),
-				tensor_format: TensorFormat::NCHW,
-				input_norm: NormalizationRange::ZeroOne,
-				output_norm: NormalizationRange::ZeroOne,
-				min_dimension: None, // No minimum for most models
-            },
-			ModelInfo {
-                name: "denoiser".to_string(),
-                url: "denoiser".to_string(),
-                model_type: ModelType::Denoising,
-                scale: 1,
-                window_size: 64,
-                description: "(Train)".to_string(),
-                category: "Denoiser".to_string(),
-				tensor_format: TensorFormat::NHWC,
-				input_norm: NormalizationRange::ZeroOne,
-				output_norm: NormalizationRange::ZeroOne,
-				min_dimension: None, // No minimum for most models
-            },
-            ModelInfo {
-                name: "deblurring_nafnet_2025may".to_string(),
-                url: "https://huggingface.co/opencv/deblurring_nafnet/resolve/main/deblurring_nafnet_2025may.onnx".to_string(),
-                model_type: ModelType::Deblur,
-                scale: 1,
-                window_size: 512,
-                description: "Motion deblur (GoPro)".to_string(),
-                category: "NAFNet - Motion deblur".to_string(),
-				tensor_format: TensorFormat::NCHW,							
-				input_norm: NormalizationRange::ZeroOne,  // Input: [-1, 1]
-				output_norm: NormalizationRange::ZeroOne,     // Output: [0, 1]
-				min_dimension: Some(512),
-            },
-			ModelInfo {
-				name: "deblurgan_mobilenet".to_string(),
-				url: "local".to_string(),
-				model_type: ModelType::Deblur,
-				scale: 1,
-				window_size: 16,
-				description: "Motion deblur (fast)".to_string(),
-				category: "DeblurGAN-v2".to_string(),
-				tensor_format: TensorFormat::NCHW,
-				input_norm: NormalizationRange::ZeroOne,  // Input: [-1, 1]
-				output_norm: NormalizationRange::ZeroOne,     // Output: [0, 1] ← FIX
-				min_dimension: None, // No minimum for most models
-			},
-            ModelInfo {
-                name: "restormer_deraining".to_string(),
-                url: "local".to_string(),
-                model_type: ModelType::Denoising,
-                scale: 1,
-                window_size: 64,
-                description: "Restormer deraining".to_string(),
-                category: "NAFNet".to_string(),
-				tensor_format: TensorFormat::NCHW,
-				input_norm: NormalizationRange::ZeroOne,
-				output_norm: NormalizationRange::ZeroOne,
-				min_dimension: None, // No minimum for most models
-            },
-            ModelInfo {
-                name: "restormer_denoising_real".to_string(),
-                url: "local".to_string(),
-                model_type: ModelType::Denoising,
-                scale: 1,
-                window_size: 64,
-                description: "Restormer denoising (real)".to_string(),
-                category: "NAFNet".to_string(),
-				tensor_format: TensorFormat::NCHW,
-				input_norm: NormalizationRange::ZeroOne,
-				output_norm: NormalizationRange::ZeroOne,
-				min_dimension: None, // No minimum for most models
-            },
-            ModelInfo {
-                name: "restormer_defocus_dual".to_string(),
-                url: "local".to_string(),
-                model_type: ModelType::Denoising,
-                scale: 1,
-                window_size: 64,
-                description: "Restormer defocus (dual)".to_string(),
-                category: "NAFNet".to_string(),
-				tensor_format: TensorFormat::NCHW,
-				input_norm: NormalizationRange::ZeroOne,
-				output_norm: NormalizationRange::ZeroOne,
-				min_dimension: None, // No minimum for most models
-            },
-            ModelInfo {
-                name: "restormer_defocus_single".to_string(),
-                url: "local".to_string(),
-                model_type: ModelType::Denoising,
-                scale: 1,
-                window_size: 64,
-                description: "Restormer defocus (single)".to_string(),
-                category: "NAFNet".to_string(),
-				tensor_format: TensorFormat::NCHW,
-				input_norm: NormalizationRange::ZeroOne,
-				output_norm: NormalizationRange::ZeroOne,
-				min_dimension: None, // No minimum for most models
-            },
-            ModelInfo {
-                name: "restormer_denoising_color_blind".to_string(),
-                url: "local".to_string(),
-                model_type: ModelType::Denoising,
-                scale: 1,
-                window_size: 64,
-                description: "Restormer denoising (color blind)".to_string(),
-                category: "NAFNet".to_string(),
-				tensor_format: TensorFormat::NCHW,
-				input_norm: NormalizationRange::ZeroOne,
-				output_norm: NormalizationRange::ZeroOne,
-				min_dimension: None, // No minimum for most models
-            },
-            ModelInfo {
-                name: "restormer_denoising_color_sigma15".to_string(),
-                url: "local".to_string(),
-                model_type: ModelType::Denoising,
-                scale: 1,
-                window_size: 64,
-                description: "Restormer denoising (color sigma15)".to_string(),
-                category: "NAFNet".to_string(),
-				tensor_format: TensorFormat::NCHW,
-				input_norm: NormalizationRange::ZeroOne,
-				output_norm: NormalizationRange::ZeroOne,
-				min_dimension: None, // No minimum for most models
-            },
-            ModelInfo {
-                name: "restormer_denoising_color_sigma25".to_string(),
-                url: "local".to_string(),
-                model_type: ModelType::Denoising,
-                scale: 1,
-                window_size: 64,
-                description: "Restormer denoising (color sigma25)".to_string(),
-                category: "NAFNet".to_string(),
-				tensor_format: TensorFormat::NCHW,
-				input_norm: NormalizationRange::ZeroOne,
-				output_norm: NormalizationRange::ZeroOne,
-				min_dimension: None, // No minimum for most models
-            },
-            ModelInfo {
-                name: "restormer_denoising_color_sigma50".to_string(),
-                url: "local".to_string(),
-                model_type: ModelType::Denoising,
-                scale: 1,
-                window_size: 64,
-                description: "Restormer denoising (color sigma50)".to_string(),
-                category: "NAFNet".to_string(),
-				tensor_format: TensorFormat::NCHW,
-				input_norm: NormalizationRange::ZeroOne,
-				output_norm: NormalizationRange::ZeroOne,
-				min_dimension: None, // No minimum for most models
-            },
-            ModelInfo {
-                name: "restormer_denoising_gray_blind".to_string(),
-                url: "local".to_string(),
-                model_type: ModelType::Denoising,
-                scale: 1,
-                window_size: 64,
-                description: "Restormer denoising (gray blind)".to_string(),
-                category: "NAFNet".to_string(),
-				tensor_format: TensorFormat::NCHW,
-				input_norm: NormalizationRange::ZeroOne,
-				output_norm: NormalizationRange::ZeroOne,
-				min_dimension: None, // No minimum for most models
-            },
-            ModelInfo {
-                name: "restormer_denoising_gray_sigma15".to_string(),
-                url: "local".to_string(),
-                model_type: ModelType::Denoising,
-                scale: 1,
-                window_size: 64,
-                description: "Restormer denoising (gray sigma15)".to_string(),
-                category: "NAFNet".to_string(),
-				tensor_format: TensorFormat::NCHW,
-				input_norm: NormalizationRange::ZeroOne,
-				output_norm: NormalizationRange::ZeroOne,
-				min_dimension: None, // No minimum for most models
-            },
-            ModelInfo {
-                name: "restormer_denoising_gray_sigma25".to_string(),
-                url: "local".to_string(),
-                model_type: ModelType::Denoising,
-                scale: 1,
-                window_size: 64,
-                description: "Restormer denoising (gray sigma25)".to_string(),
-                category: "NAFNet".to_string(),
-				tensor_format: TensorFormat::NCHW,
-				input_norm: NormalizationRange::ZeroOne,
-				output_norm: NormalizationRange::ZeroOne,
-				min_dimension: None, // No minimum for most models
-            },
-            ModelInfo {
-                name: "restormer_denoising_gray_sigma50".to_string(),
-                url: "local".to_string(),
-                model_type: ModelType::Denoising,
-                scale: 1,
-                window_size: 64,
-                description: "Restormer denoising (gray sigma50)".to_string(),
-                category: "NAFNet".to_string(),
-				tensor_format: TensorFormat::NCHW,
-				input_norm: NormalizationRange::ZeroOne,
-				output_norm: NormalizationRange::ZeroOne,
-				min_dimension: None, // No minimum for most models
-            }
-        ];
+impl ProcessResult {
+    fn scale(&self) -> f32 {
+        self.output_dims.0 as f32 / self.input_dims.0.max(1) as f32
+    }
+    
+    fn compare_by(&self, other: &Self, column: SortColumn) -> std::cmp::Ordering {
+        // Mirrored subfolders would otherwise group results by folder before name
+        let file_name = |result: &Self| result.output_path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        match column {
+            SortColumn::Filename => natural_sort_key(&file_name(self)).cmp(&natural_sort_key(&file_name(other))),
+            SortColumn::InputSize => (self.input_dims.0 * self.input_dims.1).cmp(&(other.input_dims.0 * other.input_dims.1)),
+            SortColumn::OutputSize => (self.output_dims.0 * self.output_dims.1).cmp(&(other.output_dims.0 * other.output_dims.1)),
+            SortColumn::Scale => self.scale().total_cmp(&other.scale()),
+            SortColumn::Duration => self.duration.total_cmp(&other.duration),
+            SortColumn::FileSize => self.output_bytes.cmp(&other.output_bytes),
+            SortColumn::Rating => self.rating.cmp(&other.rating),
+        }
+    }
+}
 
-        let default_category = ModelType::Upscaling;
-        let default_model = models.iter()
-            .find(|m| m.model_type == default_category)
-            .cloned();
+// Persisted settings, stored at <config dir>/super_resolution_upscaler/config.toml
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+struct Config {
+    cloud: CloudCredentials,
+    model_cache_dir: Option<PathBuf>, // None = ./models in the working directory
+    preferred_ep: ExecutionProvider, // ORT_EP takes precedence
+    openvino_device: String, // "GPU", "CPU" or "NPU", kept without the openvino feature like preferred_ep
+    recent: VecDeque<RecentEntry>, // Newest first, at most MAX_RECENT_ENTRIES
+    dark_mode: bool,
+    selected_model: Option<String>, // Restored by name, ignored if the model is no longer listed
+    output_dir: Option<PathBuf>, // Last folder picked with "Browse Output Dir"
+    output_location: OutputLocation,
+    log_level: LogLevel, // Lowest level shown in the log panel
+    tile_size: Option<u32>, // None = no tiling
+    conflict_mode: ConflictMode,
+    #[serde(deserialize_with = "deserialize_presets")]
+    presets: Vec<Preset>,
+    offline_mode: bool, // No model downloads or cloud transfers, for air-gapped and metered setups
+    registry_url: Option<String>, // models.json merged into the built-in list at startup, None = built-in only
+}
 
-        (
-            Self {
-                input_path: None,
-                input_type: InputType::None,
-                available_models: models.clone(),
-                selected_category: Some(default_category),
-                selected_model: default_model,
-                image_files: Vec::new(),
-                selected_preview_file: None,
-                before_image: None,
-                after_image: None,
-                process_results: Vec::new(),
-                processing: false,
-                status_message: "Select an image or folder to begin".to_string(),
-                zoom_level: 1.0,
-            },
-            Command::none(),
-        )
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            cloud: CloudCredentials::default(),
+            model_cache_dir: None,
+            preferred_ep: ExecutionProvider::default(),
+            openvino_device: OPENVINO_DEVICES[0].to_string(),
+            recent: VecDeque::new(),
+            dark_mode: false,
+            selected_model: None,
+            output_dir: None,
+            output_location: OutputLocation::default(),
+            log_level: LogLevel::Info,
+            tile_size: None,
+            conflict_mode: ConflictMode::Skip,
+            presets: Vec::new(),
+            offline_mode: false,
+            registry_url: None,
+        }
     }
+}
 
-    fn title(&self) -> String {
-        "Super-Resolution Upscaler".to_string()
+// Where outputs go, relative to the loaded input
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+enum OutputLocation {
+    SiblingFolder(String), // <input folder>/<name>, ./<name> for single files
+    SameDirectory, // Next to the input images
+    CustomPath(PathBuf),
+}
+
+impl Default for OutputLocation {
+    fn default() -> Self {
+        OutputLocation::SiblingFolder(DEFAULT_OUTPUT_SUBFOLDER.to_string())
     }
+}
 
-    fn update(&mut self, message: Message) -> Command<Message> {
-        match message {
-            Message::BrowseVideo => {
-                return Command::perform(
-                    async {
-                        rfd::AsyncFileDialog::new()
-                            .add_filter("Videos", &["mp4", "avi", "mkv", "mov", "webm"])
-                            .pick_file()
+impl OutputLocation {
+    // Identifies the radio button, the values themselves aren't Copy
+    fn index(&self) -> u8 {
+        match self {
+            OutputLocation::SiblingFolder(_) => 0,
+            OutputLocation::SameDirectory => 1,
+            OutputLocation::CustomPath(_) => 2,
+        }
+    }
+}
+
+// A named model and settings combination, e.g. "4K anime upscale" or "document scan restore"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Preset {
+    name: String,
+    model_name: String,
+    category: ModelType,
+    process_options: ProcessOptions,
+}
+
+// A preset this build can't read, e.g. one saving AVIF from a build with the avif feature, is
+// dropped on its own instead of failing the whole config
+fn deserialize_presets<'de, D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Vec<Preset>, D::Error> {
+    let values = Vec::<toml::Value>::deserialize(deserializer)?;
+    Ok(values.into_iter()
+        .filter_map(|value| {
+            value.try_into::<Preset>()
+                .map_err(|e| log_error(&format!("Skipping a saved preset this build can't load: {}", e)))
+                .ok()
+        })
+        .collect())
+}
+
+// A file, folder or video that was opened, offered again from the input card
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct RecentEntry {
+    path: PathBuf,
+    input_kind: InputType,
+    timestamp: chrono::DateTime<Local>,
+}
+
+impl std::fmt::Display for RecentEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self.input_kind {
+            InputType::Folder => "Folder",
+            InputType::Video => "Video",
+            _ => "File",
+        };
+        write!(f, "{}: {} ({})", kind, self.path.display(), self.timestamp.format("%Y-%m-%d %H:%M"))
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct CloudCredentials {
+    aws_access_key_id: String,
+    aws_secret_access_key: String,
+    aws_region: String,
+    aws_endpoint_url: Option<String>, // For S3-compatible stores (MinIO, R2, ...)
+    gcs_access_token: Option<String>, // OAuth bearer token for storage.googleapis.com
+    azure_sas_token: Option<String>,  // Appended to blob URLs that carry no query string
+}
+
+impl Config {
+    fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("super_resolution_upscaler").join("config.toml"))
+    }
+    
+    fn load() -> Self {
+        let Some(path) = Self::path() else {
+            return Self::default();
+        };
+        
+        match fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str(&contents).unwrap_or_else(|e| {
+                log_error(&format!("Ignoring invalid config {}: {}", path.display(), e));
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+    
+    fn save(&self) -> Result<()> {
+        let path = Self::path().ok_or_else(|| anyhow::anyhow!("No config directory on this system"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+// Downloaded and locally created .onnx files, one <model name>.onnx per model
+#[derive(Debug, Clone)]
+struct ModelCache {
+    cache_dir: PathBuf,
+}
+
+impl ModelCache {
+    fn from_config(config: &Config) -> Self {
+        Self { cache_dir: config.model_cache_dir.clone().unwrap_or_else(|| PathBuf::from("./models")) }
+    }
+    
+    fn model_path(&self, info: &ModelInfo) -> PathBuf {
+        self.cache_dir.join(format!("{}.onnx", info.name))
+    }
+    
+    fn is_cached(&self, info: &ModelInfo) -> bool {
+        self.model_path(info).exists()
+    }
+    
+    fn cached_size(&self, info: &ModelInfo) -> Option<u64> {
+        fs::metadata(self.model_path(info)).ok().map(|m| m.len())
+    }
+    
+    fn delete_cached(&self, info: &ModelInfo) -> Result<()> {
+        fs::remove_file(self.model_path(info))?;
+        Ok(())
+    }
+    
+    // (model name, bytes) of every .onnx file in the cache, sorted by name
+    fn list_all_cached(&self) -> Vec<(String, u64)> {
+        let Ok(entries) = fs::read_dir(&self.cache_dir) else {
+            return Vec::new();
+        };
+        
+        let mut cached: Vec<(String, u64)> = entries.flatten()
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "onnx"))
+            .filter_map(|entry| Some((
+                entry.path().file_stem()?.to_string_lossy().into_owned(),
+                entry.metadata().ok()?.len(),
+            )))
+            .collect();
+        cached.sort();
+        cached
+    }
+    
+    fn custom_models_path(&self) -> PathBuf {
+        self.cache_dir.join("custom_models.json")
+    }
+    
+    // Built-in catalog followed by imported custom and quantized models; neither shadows a built-in
+    fn available_models(&self) -> Vec<ModelInfo> {
+        let mut models = builtin_models();
+        merge_models(&mut models, self.load_custom_models());
+        models
+    }
+    
+    fn load_custom_models(&self) -> Vec<ModelInfo> {
+        let Ok(contents) = fs::read_to_string(self.custom_models_path()) else {
+            return Vec::new();
+        };
+        
+        serde_json::from_str(&contents).unwrap_or_else(|e| {
+            log_error(&format!("Ignoring invalid {}: {}", self.custom_models_path().display(), e));
+            Vec::new()
+        })
+    }
+    
+    fn save_custom_models(&self, models: &[ModelInfo]) -> Result<()> {
+        fs::create_dir_all(&self.cache_dir)?;
+        fs::write(self.custom_models_path(), serde_json::to_string_pretty(models)?)?;
+        Ok(())
+    }
+}
+
+impl App {
+    // Mirrors App::new started from a default config, so the ORT_* env overrides still apply.
+    // Cloud credentials, history, presets and where models live are kept, they aren't settings.
+    fn reset_settings(&mut self) {
+        self.config = Config {
+            cloud: self.config.cloud.clone(),
+            model_cache_dir: self.config.model_cache_dir.clone(),
+            recent: self.config.recent.clone(),
+            presets: self.config.presets.clone(),
+            registry_url: self.config.registry_url.clone(),
+            ..Config::default()
+        };
+        OFFLINE_MODE.store(self.config.offline_mode, Ordering::Relaxed);
+        
+        self.selected_category = Some(ModelType::Upscaling);
+        self.selected_model = self.available_models.iter()
+            .find(|m| m.model_type == ModelType::Upscaling)
+            .cloned();
+        self.zoom_level = 1.0;
+        self.gpu_temp_limit = Some(DEFAULT_GPU_TEMP_LIMIT);
+        self.video_segment_duration = None;
+        self.auto_select_model = false;
+        self.classifier_routing = ClassifierRouting::default();
+        self.output_format = OutputFormat::Png;
+        self.deinterlace_video = false;
+        self.video_options = VideoProcessOptions::default();
+        self.results_sort = (SortColumn::Filename, SortOrder::None);
+        self.results_rated_only = false;
+        self.crop_to_content = false;
+        self.upload_output_to_cloud = false;
+        self.fail_fast = false;
+        self.optimize_png = false;
+        self.split_gpu_cpu = false;
+        self.ort_config = ort_config_from_env(&self.config, None);
+        self.process_options = ProcessOptions {
+            conflict_mode: self.config.conflict_mode,
+            ..ProcessOptions::default()
+        };
+        self.tile_size = self.config.tile_size;
+        self.last_tile_size = self.config.tile_size;
+        self.dark_mode = self.config.dark_mode;
+        self.output_dir = self.config.output_dir.clone();
+        self.output_location = self.config.output_location.clone();
+        self.output_convention = detect_output_convention(&self.output_dir());
+        self.recursive_scan = false;
+        self.pipeline.clear();
+        self.flicker_mode = false;
+        self.flicker_hz = DEFAULT_FLICKER_HZ;
+        self.auto_open_output = false;
+        self.show_change_heatmap = false;
+        self.change_heatmap = None;
+        self.show_diff_view = false;
+        self.ssim_map = None;
+        self.ssim_score = None;
+        self.comparison_mode = ComparisonMode::SideBySide;
+        self.split_position = DEFAULT_SPLIT_POSITION;
+        self.split_before = None;
+        self.split_image = None;
+        self.split_dragging = false;
+        self.download_speed_limit_kbps = None;
+        self.norm_override = None;
+        
+        if let Err(e) = self.config.save() {
+            log_error(&format!("Failed to save config: {}", e));
+        }
+    }
+    
+    // Rewrites custom_models.json with every model that only exists in this model cache; INT8
+    // copies keep the category of the model they were quantized from
+    fn save_local_models(&self) -> Result<()> {
+        let local_models: Vec<ModelInfo> = self.available_models.iter()
+            .filter(|m| m.category == CUSTOM_MODEL_CATEGORY || m.quantized)
+            .cloned()
+            .collect();
+        self.model_cache.save_custom_models(&local_models)
+    }
+    
+    // Copies the picked file into the model cache and rewrites custom_models.json
+    fn import_custom_model(&mut self, form: &CustomModelForm) -> Result<ModelInfo> {
+        let model = form.to_model_info().map_err(anyhow::Error::msg)?;
+        
+        fs::create_dir_all(&self.model_cache.cache_dir)?;
+        fs::copy(&form.source_path, self.model_cache.model_path(&model))?;
+        self.available_models.push(model.clone());
+        self.save_local_models()?;
+        
+        log_message(&format!("Imported custom model {} from {}", model.name, form.source_path.display()));
+        Ok(model)
+    }
+    
+    // Same ONNX weights, different normalization; no session state depends on it
+    fn with_norm_override(&self, mut model: ModelInfo) -> ModelInfo {
+        if let Some(norm) = self.norm_override {
+            model.input_norm = norm;
+            model.output_norm = norm;
+        }
+        model
+    }
+    
+    // Called before another input replaces an extracted ZIP, its outputs are in the repacked archive
+    fn discard_zip_extraction(&self) {
+        if let (InputType::Zip, Some(path)) = (&self.input_type, &self.input_path) {
+            let _ = std::fs::remove_dir_all(zip_temp_dir(path));
+        }
+    }
+    
+    fn output_dir(&self) -> PathBuf {
+        let input_dir = match (&self.input_type, &self.input_path) {
+            (InputType::Folder | InputType::CloudUrl, Some(path)) => Some(path.clone()),
+            (InputType::Zip, Some(path)) => Some(zip_temp_dir(path)),
+            (InputType::File, Some(path)) => path.parent().map(Path::to_path_buf),
+            _ => None,
+        };
+        
+        match &self.output_location {
+            OutputLocation::SiblingFolder(name) => match input_dir {
+                Some(dir) if self.input_type != InputType::File => dir.join(name),
+                _ => PathBuf::from(".").join(name),
+            },
+            OutputLocation::SameDirectory => input_dir.unwrap_or_else(|| PathBuf::from(".")),
+            OutputLocation::CustomPath(path) => path.clone(),
+        }
+    }
+    
+    // Same Directory, or a custom path pointing back at the loaded folder
+    fn outputs_in_input_folder(&self) -> bool {
+        let Some(input) = &self.input_path else {
+            return false;
+        };
+        let output = self.output_dir();
+        output == *input || matches!((output.canonicalize(), input.canonicalize()), (Ok(a), Ok(b)) if a == b)
+    }
+    
+    // Outputs already inside a folder input that a recursive scan must not pick up as inputs
+    fn output_subfolder_of(&self, folder: &Path) -> Option<PathBuf> {
+        match &self.output_location {
+            OutputLocation::SiblingFolder(name) => Some(folder.join(name)),
+            OutputLocation::SameDirectory => None,
+            OutputLocation::CustomPath(path) => Some(path.clone()),
+        }
+    }
+    
+    // The name the previewed image would get, or why the typed template can't be used
+    fn filename_template_preview(&self) -> Option<Result<String>> {
+        let template = self.process_options.filename_template.trim();
+        let model = self.selected_model.as_ref()?;
+        if template.is_empty() {
+            return None;
+        }
+        
+        let input_path = self.selected_preview_file.as_ref()
+            .and_then(|name| self.image_files.iter().find(|p| p.file_name().and_then(|n| n.to_str()) == Some(name)))
+            .cloned()
+            .unwrap_or_else(|| PathBuf::from("image.png"));
+        let dims = self.before_image.as_ref()
+            .map(|img| (img.width() * model.scale, img.height() * model.scale))
+            .unwrap_or((0, 0));
+        Some(render_output_filename(&with_extension_token(template), &input_path, model,
+            self.output_format.extension(model.bit_depth), dims))
+    }
+    
+    fn save_output_location(&mut self) {
+        self.config.output_dir = self.output_dir.clone();
+        self.config.output_location = self.output_location.clone();
+        if let Err(e) = self.config.save() {
+            log_error(&format!("Failed to save config: {}", e));
+        }
+        self.output_convention = detect_output_convention(&self.output_dir());
+    }
+    
+    // Debounced: only the SaveConfig that fires after the last change writes the file
+    fn schedule_config_save(&mut self) -> Command<Message> {
+        self.config.selected_model = self.selected_model.as_ref().map(|m| m.name.clone());
+        self.config.tile_size = self.tile_size;
+        self.config_save_due = Some(std::time::Instant::now() + CONFIG_SAVE_DEBOUNCE);
+        Command::perform(tokio::time::sleep(CONFIG_SAVE_DEBOUNCE), |_| Message::SaveConfig)
+    }
+    
+    // Images/minute over the last THROUGHPUT_WINDOW, or since batch start while warming up.
+    // Fed by ProgressUpdate, so images finished between two polls share one timestamp.
+    fn sample_throughput(&mut self, processed: usize) {
+        let Some(started) = self.batch_started else {
+            return;
+        };
+        let now = std::time::Instant::now();
+        
+        let new_completions = processed.saturating_sub(self.images_processed);
+        self.images_processed = processed;
+        self.completion_times.extend(std::iter::repeat_n(now, new_completions));
+        while self.completion_times.front().is_some_and(|t| now.duration_since(*t) > THROUGHPUT_WINDOW) {
+            self.completion_times.pop_front();
+        }
+        while self.throughput_history.front().is_some_and(|(t, _)| now.duration_since(*t) > THROUGHPUT_WINDOW) {
+            self.throughput_history.pop_front();
+        }
+        
+        let window_secs = now.duration_since(started).min(THROUGHPUT_WINDOW).as_secs_f32().max(1.0);
+        let images_per_minute = self.completion_times.len() as f32 * 60.0 / window_secs;
+        self.throughput_history.push_back((now, images_per_minute));
+    }
+    
+    fn set_after_image(&mut self, img: Option<DynamicImage>) {
+        self.after_palette.clear();
+        self.after_image = img.map(Arc::new);
+        self.flicker_mode &= self.after_image.is_some();
+        self.change_heatmap = None;
+        self.show_diff_view &= self.after_image.is_some();
+        self.ssim_map = None;
+        self.ssim_score = None;
+        self.split_before = None;
+        self.refresh_split_before();
+    }
+    
+    // Downsampling a large output for the palette would stall the UI, see Message::PaletteExtracted
+    fn refresh_palette(&self) -> Command<Message> {
+        let Some(after) = self.after_image.clone() else {
+            return Command::none();
+        };
+        
+        let image = after.clone();
+        Command::perform(
+            async move {
+                tokio::task::spawn_blocking(move || extract_palette(&image, PALETTE_SIZE)).await.unwrap_or_default()
+            },
+            move |palette| Message::PaletteExtracted(after, palette),
+        )
+    }
+    
+    // Built on demand only and off the UI thread, the bicubic upsample is expensive for large
+    // outputs; the heatmap lands in Message::ChangeHeatmapComputed
+    fn refresh_change_heatmap(&self) -> Command<Message> {
+        if !self.show_change_heatmap || self.change_heatmap.is_some() {
+            return Command::none();
+        }
+        let Some(pair) = self.current_pair() else {
+            return Command::none();
+        };
+        
+        let (before, after) = pair.clone();
+        Command::perform(
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    let (w, h) = after.dimensions();
+                    let before_upsampled = before.resize_exact(w, h, image::imageops::FilterType::CatmullRom);
+                    Arc::new(build_change_heatmap(&before_upsampled, &after))
+                })
+                .await
+                .ok()
+            },
+            move |heatmap| Message::ChangeHeatmapComputed(pair, heatmap),
+        )
+    }
+    
+    // Same on-demand rule as the change heatmap; the Gaussian windows are slow on large images,
+    // so the map is computed off the UI thread and lands in Message::SsimComputed
+    fn refresh_ssim_map(&self) -> Command<Message> {
+        if !self.show_diff_view || self.ssim_map.is_some() {
+            return Command::none();
+        }
+        let Some(pair) = self.current_pair() else {
+            return Command::none();
+        };
+        
+        let (before, after) = pair.clone();
+        Command::perform(
+            async move {
+                tokio::task::spawn_blocking(move || compute_ssim(&before, &after)).await
+                    .map(|(score, map)| (score, Arc::new(map)))
+                    .ok()
+            },
+            move |result| Message::SsimComputed(pair, result),
+        )
+    }
+    
+    // The Lanczos3 resize back to the input size is slow for large outputs, so this runs off the UI thread too
+    fn refresh_psnr(&self) -> Command<Message> {
+        let Some(pair) = self.current_pair() else {
+            return Command::none();
+        };
+        
+        let (before, after) = pair.clone();
+        Command::perform(
+            async move {
+                tokio::task::spawn_blocking(move || compute_psnr(&before, &after)).await.ok()
+            },
+            move |psnr| Message::PsnrComputed(pair, psnr),
+        )
+    }
+    
+    fn current_pair(&self) -> Option<ImagePair> {
+        Some((self.before_image.clone()?, self.after_image.clone()?))
+    }
+    
+    // A result computed for images that have since been replaced is dropped
+    fn is_current_pair(&self, pair: &ImagePair) -> bool {
+        self.current_pair().is_some_and(|(before, after)| Arc::ptr_eq(&before, &pair.0) && Arc::ptr_eq(&after, &pair.1))
+    }
+    
+    // Cached because the split preview is rebuilt on every cursor move while dragging
+    fn refresh_split_before(&mut self) {
+        if self.comparison_mode == ComparisonMode::SplitLine && self.split_before.is_none() {
+            if let (Some(before), Some(after)) = (&self.before_image, &self.after_image) {
+                let (w, h) = after.dimensions();
+                let upsampled = before.resize_exact(w, h, image::imageops::FilterType::CatmullRom).to_rgba8();
+                self.split_before = Some(Arc::new(upsampled));
+            }
+        }
+        self.refresh_split_image();
+    }
+    
+    // Composited here rather than in view, which iced calls far more often than the divider moves
+    fn refresh_split_image(&mut self) {
+        self.split_image = match (&self.split_before, &self.after_image) {
+            (Some(before), Some(after)) if self.comparison_mode == ComparisonMode::SplitLine => {
+                let [r, g, b, _] = PRIMARY_COLOR.into_rgba8();
+                let split = build_split_image(before, after, self.split_position, image::Rgba([r, g, b, 255]));
+                Some(iced_image::Handle::from_pixels(split.width(), split.height(), split.into_raw()))
+            }
+            _ => None,
+        };
+    }
+    
+    fn set_split_from_cursor(&mut self) {
+        if let Some(after) = &self.after_image {
+            let display_w = after.width() as f32 * self.zoom_level;
+            let split_position = (self.split_cursor_x / display_w.max(1.0)).clamp(0.0, 1.0);
+            if split_position != self.split_position {
+                self.split_position = split_position;
+                self.refresh_split_image();
+            }
+        }
+    }
+}
+
+impl Application for App {
+    type Executor = executor::Default;
+    type Message = Message;
+    type Theme = Theme;
+    type Flags = ();
+
+    fn new(_flags: ()) -> (Self, Command<Message>) {
+        let config = Config::load();
+        OFFLINE_MODE.store(config.offline_mode, Ordering::Relaxed);
+        let (log_sender, log_receiver) = tokio::sync::mpsc::unbounded_channel();
+        let _ = LOG_SENDER.set(log_sender);
+        let model_cache = ModelCache::from_config(&config);
+        let ort_config = ort_config_from_env(&config, None);
+        #[cfg(feature = "openvino")]
+        let openvino_available = check_openvino_available();
+        
+        #[cfg(feature = "coreml")]
+        let startup_command = if ort_config.ep == ExecutionProvider::CoreML {
+            Command::perform(check_coreml_available(), Message::CoreMLChecked)
+        } else {
+            Command::none()
+        };
+        #[cfg(not(feature = "coreml"))]
+        let startup_command = Command::none();
+        
+        let models = model_cache.available_models();
+        
+        let restored_model = config.selected_model.as_ref()
+            .and_then(|name| models.iter().find(|m| &m.name == name))
+            .cloned();
+        let default_category = restored_model.as_ref()
+            .map_or(ModelType::Upscaling, |m| m.model_type.clone());
+        let default_model = restored_model.or_else(|| models.iter()
+            .find(|m| m.model_type == default_category)
+            .cloned());
+        let process_options = ProcessOptions {
+            conflict_mode: config.conflict_mode,
+            ..ProcessOptions::default()
+        };
+        let tile_size = config.tile_size;
+        let registry_command = match &config.registry_url {
+            Some(url) if !config.offline_mode => {
+                let url = url.clone();
+                Command::perform(
+                    async move { fetch_model_registry(&url).await.map_err(|e| e.to_string()) },
+                    Message::RegistryFetched,
+                )
+            }
+            _ => Command::none(),
+        };
+
+        (
+            Self {
+                input_path: None,
+                input_type: InputType::None,
+                available_models: models.clone(),
+                selected_category: Some(default_category),
+                selected_model: default_model,
+                image_files: Vec::new(),
+                selected_preview_file: None,
+                thumbnail_cache: HashMap::new(),
+                thumbnails_requested: HashSet::new(),
+                before_image: None,
+                after_image: None,
+                process_results: Vec::new(),
+                processing: false,
+                status_message: "Select an image or folder to begin".to_string(),
+                zoom_level: 1.0,
+                preview_hovered: false,
+                scroll_offset: RelativeOffset::START,
+                gpu_temp_limit: Some(DEFAULT_GPU_TEMP_LIMIT),
+                gpu_temp: Arc::new(AtomicU32::new(0)),
+                batch_started: None,
+                images_processed: 0,
+                completion_times: VecDeque::new(),
+                throughput_history: VecDeque::new(),
+                disk_space: Arc::new(DiskSpaceGate::default()),
+                cancel_token: None,
+                pause_flag: Arc::new(AtomicBool::new(false)),
+                progress: Arc::new(Mutex::new(ProgressState::default())),
+                batch_progress: None,
+                disk_space_low: None,
+                video_segment_duration: None,
+                auto_select_model: false,
+                classifier_routing: ClassifierRouting::default(),
+                output_format: OutputFormat::Png,
+                deinterlace_video: false,
+                video_options: VideoProcessOptions::default(),
+                video_checkpoint: None,
+                results_sort: (SortColumn::Filename, SortOrder::None),
+                results_rated_only: false,
+                crop_to_content: false,
+                recent: config.recent.clone(),
+                dark_mode: config.dark_mode,
+                output_dir: config.output_dir.clone(),
+                output_location: config.output_location.clone(),
+                presets: config.presets.clone(),
+                config,
+                model_cache,
+                cloud_url_input: String::new(),
+                cloud_sources: HashMap::new(),
+                upload_output_to_cloud: false,
+                after_palette: Vec::new(),
+                fail_fast: false,
+                image_hashes: HashMap::new(),
+                hash_generation: 0,
+                duplicate_pairs: Vec::new(),
+                tile_size,
+                last_tile_size: tile_size,
+                optimize_png: false,
+                split_gpu_cpu: false,
+                ort_config,
+                #[cfg(feature = "openvino")]
+                openvino_available,
+                active_ep: None,
+                available_video_codecs: Vec::new(),
+                process_options,
+                flicker_mode: false,
+                flicker_hz: DEFAULT_FLICKER_HZ,
+                flicker_show_after: false,
+                auto_open_output: false,
+                show_change_heatmap: false,
+                change_heatmap: None,
+                show_diff_view: false,
+                ssim_map: None,
+                ssim_score: None,
+                comparison_mode: ComparisonMode::SideBySide,
+                split_position: DEFAULT_SPLIT_POSITION,
+                split_before: None,
+                split_image: None,
+                split_cursor_x: 0.0,
+                split_dragging: false,
+                download_speed_limit_kbps: None,
+                download_progress: None,
+                process_after_download: None,
+                quantizing: false,
+                quantize_report: None,
+                benchmark_runs: DEFAULT_BENCHMARK_RUNS,
+                benchmarking: false,
+                benchmark_result: None,
+                output_convention: None,
+                norm_override: None,
+                custom_model_form: None,
+                model_inspection: None,
+                inspector_collapsed: false,
+                pipeline: Vec::new(),
+                last_psnr: None,
+                log_entries: VecDeque::new(),
+                log_receiver: Arc::new(Mutex::new(Some(log_receiver))),
+                log_collapsed: true,
+                preset_name_input: String::new(),
+                selected_preset: None,
+                drag_hover: false,
+                recursive_scan: false,
+                watching: None,
+                watch_queue: Vec::new(),
+                config_save_due: None,
+            },
+            Command::batch([
+                startup_command,
+                registry_command,
+                Command::perform(detect_execution_provider(), Message::ExecutionProviderProbed),
+                Command::perform(detect_video_codecs(), Message::VideoCodecsProbed),
+            ]),
+        )
+    }
+
+    fn title(&self) -> String {
+        "Super-Resolution Upscaler".to_string()
+    }
+
+    fn update(&mut self, message: Message) -> Command<Message> {
+        match message {
+            Message::BrowseVideo => {
+                return Command::perform(
+                    async {
+                        rfd::AsyncFileDialog::new()
+                            .add_filter("Videos", &VIDEO_EXTENSIONS)
+                            .pick_file()
                             .await
                             .map(|f| f.path().to_path_buf())
                     },
@@ -644,39 +1779,27 @@ impl Application for App {
             
             Message::VideoSelected(path) => {
                 if let Some(path) = path {
+                    self.discard_zip_extraction();
                     self.input_path = Some(path.clone());
                     self.input_type = InputType::Video;
+                    self.push_recent(path.clone(), InputType::Video);
                     self.status_message = format!("Video loaded: {}", path.display());
-                    self.after_image = None;
+                    self.set_after_image(None);
                     self.process_results.clear();
+                    self.refresh_video_checkpoint();
                 }
             }
             
             Message::ProcessVideo => {
-                if self.processing || self.input_path.is_none() {
-                    return Command::none();
-                }
-                
-                let Some(model) = self.selected_model.clone() else {
-                    self.status_message = "No model selected".to_string();
-                    return Command::none();
-                };
-                
-                let Some(video_path) = self.input_path.clone() else {
-                    return Command::none();
-                };
-                
-                self.processing = true;
-                self.status_message = "Processing video...".to_string();
-                
-                return Command::perform(
-                    process_video(video_path, model),
-                    Message::VideoProcessComplete,
-                );
+                return self.start_video(false);
+            }
+            Message::ResumeVideo => {
+                return self.start_video(true);
             }
             
             Message::VideoProcessComplete(result) => {
                 self.processing = false;
+                self.refresh_video_checkpoint();
                 
                 match result {
                     Ok(output_path) => {
@@ -688,53 +1811,221 @@ impl Application for App {
                 }
             }
             
-            Message::CategorySelected(category) => {
-                self.selected_category = Some(category.clone());
-                // Select the first model of the new category
-                self.selected_model = self.available_models.iter()
-                    .find(|m| m.model_type == category)
-                    .cloned();
+            Message::QuantizeModel => {
+                let Some(model) = self.selected_model.clone().filter(|m| !m.quantized) else {
+                    return Command::none();
+                };
+                
+                self.quantizing = true;
+                self.quantize_report = None;
+                self.status_message = format!("Quantizing {} to INT8...", model.name);
+                return Command::perform(quantize_model(model, self.model_cache.clone()), Message::QuantizeComplete);
             }
-            
-            Message::BrowseFile => {
+            Message::Benchmark => {
+                let (Some(model), Some(image)) = (self.selected_model.clone(), self.before_image.clone()) else {
+                    return Command::none();
+                };
+                
+                self.benchmarking = true;
+                self.benchmark_result = None;
+                self.status_message = format!("Benchmarking {} ({} runs)...", model.name, self.benchmark_runs);
                 return Command::perform(
-                    async {
-                        rfd::AsyncFileDialog::new()
-                            .add_filter("Images", &["jpg", "jpeg", "png", "bmp", "webp"])
-                            .pick_file()
-                            .await
-                            .map(|f| f.path().to_path_buf())
-                    },
-                    Message::FileSelected,
+                    benchmark_inference(model, image, self.benchmark_runs, self.ort_config.clone(), self.model_cache.clone()),
+                    Message::BenchmarkComplete,
                 );
             }
-            Message::BrowseFolder => {
+            Message::BenchmarkRunsChanged(runs) => {
+                self.benchmark_runs = runs;
+            }
+            Message::BenchmarkComplete(result) => {
+                self.benchmarking = false;
+                
+                match result {
+                    Ok(result) => {
+                        self.status_message = format!("Benchmarked {}", result.model_name);
+                        self.benchmark_result = Some(result);
+                    }
+                    Err(e) => {
+                        self.status_message = format!("Benchmark failed: {}", e);
+                    }
+                }
+            }
+            Message::ImportCustomModel => {
                 return Command::perform(
                     async {
                         rfd::AsyncFileDialog::new()
-                            .pick_folder()
+                            .add_filter("ONNX model", &["onnx"])
+                            .pick_file()
                             .await
                             .map(|f| f.path().to_path_buf())
                     },
-                    Message::FolderSelected,
+                    Message::CustomModelFileSelected,
                 );
             }
-            Message::FileSelected(path) => {
+            Message::CustomModelFileSelected(path) => {
                 if let Some(path) = path {
-                    self.input_path = Some(path.clone());
-                    self.input_type = InputType::File;
-                    self.image_files = vec![path.clone()];
-                    self.selected_preview_file = path.file_name()
+                    self.custom_model_form = Some(CustomModelForm::new(path));
+                }
+            }
+            Message::CustomModelFormChanged(form) => {
+                self.custom_model_form = Some(form);
+            }
+            Message::CancelCustomModel => {
+                self.custom_model_form = None;
+            }
+            Message::SaveCustomModel => {
+                let Some(form) = self.custom_model_form.take() else {
+                    return Command::none();
+                };
+                
+                match self.import_custom_model(&form) {
+                    Ok(model) => {
+                        self.status_message = format!("Added custom model {}", model.name);
+                        self.selected_category = Some(model.model_type.clone());
+                        self.selected_model = Some(model);
+                        self.norm_override = None;
+                    }
+                    Err(e) => {
+                        log_error(&format!("Failed to import custom model: {}", e));
+                        self.status_message = format!("Failed to import model: {}", e);
+                        self.custom_model_form = Some(form);
+                    }
+                }
+            }
+            Message::InspectModel(model) => {
+                self.status_message = format!("Inspecting {}...", model.name);
+                return Command::perform(inspect_model(model, self.model_cache.clone()), Message::ModelInspected);
+            }
+            Message::ModelInspected(result) => {
+                match result {
+                    Ok(inspection) => {
+                        self.status_message = format!("Inspected {}", inspection.model.name);
+                        self.model_inspection = Some(inspection);
+                        self.inspector_collapsed = false;
+                    }
+                    Err(e) => {
+                        self.status_message = format!("Failed to inspect model: {}", e);
+                    }
+                }
+            }
+            Message::ToggleInspectorCollapsed => {
+                self.inspector_collapsed = !self.inspector_collapsed;
+            }
+            Message::CloseInspector => {
+                self.model_inspection = None;
+            }
+            Message::DeleteCachedModel => {
+                let Some(model) = self.selected_model.clone() else {
+                    return Command::none();
+                };
+                
+                self.status_message = match self.model_cache.delete_cached(&model) {
+                    Ok(()) => {
+                        let cached = self.model_cache.list_all_cached();
+                        let total: u64 = cached.iter().map(|(_, bytes)| bytes).sum();
+                        format!("Deleted {}, {} model(s) ({}) left in cache", model.name, cached.len(), format_file_size(total))
+                    }
+                    Err(e) => format!("Failed to delete {}: {}", model.name, e),
+                };
+            }
+            Message::QuantizeComplete(result) => {
+                self.quantizing = false;
+                
+                match result {
+                    Ok(report) => {
+                        if !self.available_models.iter().any(|m| m.name == report.model.name) {
+                            self.available_models.push(report.model.clone());
+                            if let Err(e) = self.save_local_models() {
+                                log_error(&format!("Failed to save {}: {}", report.model.name, e));
+                            }
+                        }
+                        self.selected_model = Some(report.model.clone());
+                        self.status_message = format!("Added {}", report.model.name);
+                        self.quantize_report = Some(report);
+                    }
+                    Err(e) => {
+                        self.status_message = format!("Quantization failed: {}", e);
+                    }
+                }
+            }
+            Message::CategorySelected(category) => {
+                self.selected_category = Some(category.clone());
+                // Select the first model of the new category
+                self.selected_model = self.available_models.iter()
+                    .find(|m| m.model_type == category)
+                    .cloned();
+                self.norm_override = None;
+                return self.schedule_config_save();
+            }
+            Message::SaveConfig => {
+                // A later change pushed the deadline back and has its own SaveConfig queued
+                if self.config_save_due.is_some_and(|due| std::time::Instant::now() >= due) {
+                    self.config_save_due = None;
+                    if let Err(e) = self.config.save() {
+                        log_error(&format!("Failed to save config: {}", e));
+                    }
+                }
+            }
+            
+            Message::BrowseFile => {
+                return Command::perform(
+                    async {
+                        rfd::AsyncFileDialog::new()
+                            .add_filter("Images", &["jpg", "jpeg", "png", "bmp", "webp"])
+                            .pick_file()
+                            .await
+                            .map(|f| f.path().to_path_buf())
+                    },
+                    Message::FileSelected,
+                );
+            }
+            Message::BrowseFolder => {
+                return Command::perform(
+                    async {
+                        rfd::AsyncFileDialog::new()
+                            .pick_folder()
+                            .await
+                            .map(|f| f.path().to_path_buf())
+                    },
+                    Message::FolderSelected,
+                );
+            }
+            Message::BrowseZip => {
+                return Command::perform(
+                    async {
+                        rfd::AsyncFileDialog::new()
+                            .add_filter("ZIP archives", &["zip"])
+                            .pick_file()
+                            .await
+                            .map(|f| f.path().to_path_buf())
+                    },
+                    Message::ZipSelected,
+                );
+            }
+            Message::FileSelected(path) => {
+                if let Some(path) = path {
+                    self.discard_zip_extraction();
+                    self.input_path = Some(path.clone());
+                    self.input_type = InputType::File;
+                    self.push_recent(path.clone(), InputType::File);
+                    self.image_files = vec![path.clone()];
+                    self.image_hashes.clear();
+                    self.hash_generation += 1;
+                    self.thumbnail_cache.clear();
+                    self.thumbnails_requested.clear();
+                    self.duplicate_pairs.clear();
+                    self.selected_preview_file = path.file_name()
                         .and_then(|n| n.to_str())
                         .map(|s| s.to_string());
-                    self.after_image = None;
+                    self.set_after_image(None);
                     self.process_results.clear();
                     self.status_message = format!("Loaded: {}", path.display());
+                    self.output_convention = detect_output_convention(&self.output_dir());
                     self.zoom_level = 1.0;
                     
                     return Command::perform(
                         async move { 
-                            image::open(&path)
+                            fast_open(&path)
                                 .map(|img| (img, path.clone()))
                                 .map_err(|e| e.to_string())
                         },
@@ -744,29 +2035,22 @@ impl Application for App {
             }
             Message::FolderSelected(path) => {
                 if let Some(path) = path {
-                    let extensions = ["jpg", "jpeg", "png", "bmp", "webp"];
-                    let mut files = Vec::new();
-                    
-                    if let Ok(entries) = std::fs::read_dir(&path) {
-                        for entry in entries.flatten() {
-                            let entry_path = entry.path();
-                            if entry_path.is_file() {
-                                if let Some(ext) = entry_path.extension() {
-                                    if let Some(ext_str) = ext.to_str() {
-                                        if extensions.contains(&ext_str.to_lowercase().as_str()) {
-                                            files.push(entry_path);
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    
-                    files.sort();
+                    let files = if self.recursive_scan {
+                        list_folder_images_recursive(&path, self.output_subfolder_of(&path).as_deref())
+                    } else {
+                        list_folder_images(&path)
+                    };
+                    let subfolders = files.iter()
+                        .filter_map(|file| file.parent())
+                        .filter(|parent| *parent != path)
+                        .collect::<HashSet<_>>()
+                        .len();
                     
                     if files.is_empty() {
                         self.status_message = "No images found in folder".to_string();
                     } else {
+                        self.push_recent(path.clone(), InputType::Folder);
+                        self.discard_zip_extraction();
                         self.input_path = Some(path);
                         self.input_type = InputType::Folder;
                         self.selected_preview_file = files.first()
@@ -774,27 +2058,223 @@ impl Application for App {
                             .and_then(|n| n.to_str())
                             .map(|s| s.to_string());
                         self.image_files = files.clone();
-                        self.after_image = None;
+                        self.image_hashes.clear();
+                        self.thumbnail_cache.clear();
+                        self.thumbnails_requested.clear();
+                        self.duplicate_pairs.clear();
+                        self.set_after_image(None);
                         self.process_results.clear();
-                        self.status_message = format!("Loaded {} images", self.image_files.len());
+                        self.status_message = if subfolders > 0 {
+                            format!("Loaded {} images in {} subfolders", self.image_files.len(), subfolders)
+                        } else {
+                            format!("Loaded {} images", self.image_files.len())
+                        };
+                        self.output_convention = detect_output_convention(&self.output_dir());
                         self.zoom_level = 1.0;
                         
+                        let hash_command = self.compute_hashes(files.clone());
+                        let thumbnail_command = self.request_thumbnails(THUMBNAIL_BATCH);
+                        
                         if let Some(first) = files.first() {
                             let path = first.clone();
-                            return Command::perform(
-                                async move {
-                                    image::open(&path)
-                                        .map(|img| (img, path.clone()))
-                                        .map_err(|e| e.to_string())
-                                },
-                                Message::PreviewLoaded,
-                            );
+                            return Command::batch([
+                                hash_command,
+                                thumbnail_command,
+                                Command::perform(
+                                    async move {
+                                        fast_open(&path)
+                                            .map(|img| (img, path.clone()))
+                                            .map_err(|e| e.to_string())
+                                    },
+                                    Message::PreviewLoaded,
+                                ),
+                            ]);
+                        }
+                    }
+                }
+            }
+            Message::ToggleDarkMode => {
+                self.dark_mode = !self.dark_mode;
+                self.config.dark_mode = self.dark_mode;
+                if let Err(e) = self.config.save() {
+                    log_error(&format!("Failed to save config: {}", e));
+                }
+            }
+            Message::FileHovered => {
+                self.drag_hover = true;
+            }
+            Message::FileDragLeft => {
+                self.drag_hover = false;
+            }
+            // Dropping several files sends one event each, so the last one is what stays loaded
+            Message::FileDropped(path) => {
+                self.drag_hover = false;
+                if self.processing {
+                    return Command::none();
+                }
+                
+                let extension = path.extension()
+                    .and_then(|e| e.to_str())
+                    .map(|e| e.to_lowercase())
+                    .unwrap_or_default();
+                let path = Some(path);
+                return if path.as_ref().is_some_and(|p| p.is_dir()) {
+                    self.update(Message::FolderSelected(path))
+                } else if extension == "zip" {
+                    self.update(Message::ZipSelected(path))
+                } else if VIDEO_EXTENSIONS.contains(&extension.as_str()) {
+                    self.update(Message::VideoSelected(path))
+                } else if path.as_deref().is_some_and(has_image_extension) {
+                    self.update(Message::FileSelected(path))
+                } else {
+                    self.status_message = format!("Unsupported file: .{}", extension);
+                    Command::none()
+                };
+            }
+            Message::RecentSelected(entry) => {
+                let path = Some(entry.path);
+                return match entry.input_kind {
+                    InputType::Folder => self.update(Message::FolderSelected(path)),
+                    InputType::Video => self.update(Message::VideoSelected(path)),
+                    _ => self.update(Message::FileSelected(path)),
+                };
+            }
+            Message::ZipSelected(path) => {
+                if let Some(path) = path {
+                    self.status_message = format!("Extracting {}...", path.display());
+                    return Command::perform(extract_zip_in_background(path), Message::ZipExtracted);
+                }
+            }
+            Message::ZipExtracted(result) => {
+                match result {
+                    Ok((path, files)) if files.is_empty() => {
+                        let _ = std::fs::remove_dir_all(zip_temp_dir(&path));
+                        self.status_message = "No images found in ZIP archive".to_string();
+                    }
+                    Ok((path, files)) => {
+                        // A re-opened archive was extracted over its own temp folder already
+                        if self.input_path.as_deref().map(zip_temp_dir) != Some(zip_temp_dir(&path)) {
+                            self.discard_zip_extraction();
+                        }
+                        self.input_path = Some(path);
+                        self.input_type = InputType::Zip;
+                        self.selected_preview_file = files.first()
+                            .and_then(|p| p.file_name())
+                            .and_then(|n| n.to_str())
+                            .map(|s| s.to_string());
+                        self.image_files = files.clone();
+                        self.image_hashes.clear();
+                        self.thumbnail_cache.clear();
+                        self.thumbnails_requested.clear();
+                        self.duplicate_pairs.clear();
+                        self.set_after_image(None);
+                        self.process_results.clear();
+                        self.status_message = format!("Extracted {} images", self.image_files.len());
+                        self.output_convention = detect_output_convention(&self.output_dir());
+                        self.zoom_level = 1.0;
+                        
+                        let hash_command = self.compute_hashes(files.clone());
+                        let thumbnail_command = self.request_thumbnails(THUMBNAIL_BATCH);
+                        
+                        if let Some(first) = files.first() {
+                            let path = first.clone();
+                            return Command::batch([
+                                hash_command,
+                                thumbnail_command,
+                                Command::perform(
+                                    async move {
+                                        fast_open(&path)
+                                            .map(|img| (img, path.clone()))
+                                            .map_err(|e| e.to_string())
+                                    },
+                                    Message::PreviewLoaded,
+                                ),
+                            ]);
                         }
                     }
+                    Err(e) => self.status_message = format!("Error: {}", e),
+                }
+            }
+            Message::ZipRepacked(completed, result) => {
+                self.status_message = match result {
+                    Ok(archive) => format!("Completed {} image(s), saved {}", completed, archive.display()),
+                    Err(e) => format!("Completed {} image(s), failed to write ZIP: {}", completed, e),
+                };
+            }
+            Message::PipelineStepAdded => {
+                let model = self.selected_model.clone()
+                    .or_else(|| self.available_models.first().cloned());
+                if let Some(model) = model {
+                    self.pipeline.push(model);
+                }
+            }
+            Message::PipelineStepSelected(index, model) => {
+                if let Some(step) = self.pipeline.get_mut(index) {
+                    *step = model;
+                }
+            }
+            Message::PipelineStepRemoved(index) => {
+                if index < self.pipeline.len() {
+                    self.pipeline.remove(index);
+                }
+            }
+            Message::PresetNameChanged(name) => {
+                self.preset_name_input = name;
+            }
+            Message::SavePreset => {
+                let name = self.preset_name_input.trim().to_string();
+                let Some(model) = self.selected_model.clone() else {
+                    self.status_message = "Select a model before saving a preset".to_string();
+                    return Command::none();
+                };
+                if name.is_empty() {
+                    return Command::none();
+                }
+                
+                let preset = Preset {
+                    name: name.clone(),
+                    model_name: model.name,
+                    category: model.model_type,
+                    process_options: self.current_process_options(),
+                };
+                // Saving under an existing name replaces that preset
+                match self.presets.iter_mut().find(|p| p.name == name) {
+                    Some(existing) => *existing = preset,
+                    None => self.presets.push(preset),
+                }
+                self.save_presets();
+                self.preset_name_input.clear();
+                self.status_message = format!("Saved preset \"{}\"", name);
+                self.selected_preset = Some(name);
+            }
+            Message::PresetSelected(name) => {
+                if let Some(preset) = self.presets.iter().find(|p| p.name == name).cloned() {
+                    self.apply_preset(&preset);
+                    self.status_message = if self.selected_model.is_some() {
+                        format!("Applied preset \"{}\"", name)
+                    } else {
+                        format!("Applied preset \"{}\", model {} is no longer available", name, preset.model_name)
+                    };
+                    self.selected_preset = Some(name);
+                    self.refresh_video_checkpoint();
+                    return self.schedule_config_save();
+                }
+            }
+            Message::DeletePreset => {
+                if let Some(name) = self.selected_preset.take() {
+                    self.presets.retain(|p| p.name != name);
+                    self.save_presets();
+                    self.status_message = format!("Deleted preset \"{}\"", name);
                 }
             }
             Message::ModelSelected(model) => {
                 self.selected_model = Some(model);
+                self.norm_override = None;
+                self.refresh_video_checkpoint();
+                return self.schedule_config_save();
+            }
+            Message::NormOverrideSelected(norm) => {
+                self.norm_override = norm;
             }
             Message::PreviewFileSelected(filename) => {
                 self.selected_preview_file = Some(filename.clone());
@@ -806,7 +2286,7 @@ impl Application for App {
                     
                     return Command::perform(
                         async move {
-                            image::open(&path)
+                            fast_open(&path)
                                 .map(|img| (img, path.clone()))
                                 .map_err(|e| e.to_string())
                         },
@@ -814,15 +2294,33 @@ impl Application for App {
                     );
                 }
             }
+            Message::ThumbnailLoaded(result) => {
+                match result {
+                    // A late result for a folder that has since been replaced is dropped
+                    Ok((path, handle)) if self.thumbnails_requested.contains(&path) => {
+                        self.thumbnail_cache.insert(path, handle);
+                    }
+                    Ok(_) => {}
+                    Err(e) => log_error(&format!("Failed to load thumbnail: {}", e)),
+                }
+            }
+            Message::ThumbnailsScrolled(offset) => {
+                // Every file has a tile, so the offset maps straight onto the file list
+                let first_visible = (offset.x.max(0.0) * self.image_files.len() as f32).ceil() as usize;
+                return self.request_thumbnails(first_visible + THUMBNAIL_BATCH);
+            }
             Message::PreviewLoaded(result) => {
                 match result {
                     Ok((img, path)) => {
                         self.before_image = Some(Arc::new(img));
+                        self.split_before = None;
+                        self.refresh_split_before();
                         
                         if let Some(result) = self.process_results.iter()
                             .find(|r| r.input_path == path) {
-                            if let Ok(after_img) = image::open(&result.output_path) {
-                                self.after_image = Some(Arc::new(after_img));
+                            if let Some(after_img) = load_result_image(result) {
+                                self.set_after_image(Some(after_img));
+                                return Command::batch([self.refresh_ssim_map(), self.refresh_change_heatmap(), self.refresh_palette()]);
                             }
                         }
                     }
@@ -832,112 +2330,915 @@ impl Application for App {
                 }
             }
             Message::Process => {
-                if self.processing || self.image_files.is_empty() {
+                return self.start_processing(self.image_files.clone());
+            }
+            Message::BrowseOutputDir => {
+                return Command::perform(
+                    async {
+                        rfd::AsyncFileDialog::new()
+                            .pick_folder()
+                            .await
+                            .map(|f| f.path().to_path_buf())
+                    },
+                    Message::OutputDirSelected,
+                );
+            }
+            Message::OutputDirSelected(path) => {
+                if let Some(path) = path {
+                    self.output_dir = Some(path.clone());
+                    self.output_location = OutputLocation::CustomPath(path);
+                    self.save_output_location();
+                }
+            }
+            Message::ToggleOfflineMode(enabled) => {
+                OFFLINE_MODE.store(enabled, Ordering::Relaxed);
+                self.config.offline_mode = enabled;
+                if let Err(e) = self.config.save() {
+                    log_error(&format!("Failed to save config: {}", e));
+                }
+            }
+            Message::ConflictModeSelected(mode) => {
+                self.process_options.conflict_mode = mode;
+                self.config.conflict_mode = mode;
+                if let Err(e) = self.config.save() {
+                    log_error(&format!("Failed to save config: {}", e));
+                }
+            }
+            Message::FilenameTemplateChanged(template) => {
+                self.process_options.filename_template = template;
+            }
+            Message::OutputLocationSelected(location) => {
+                self.output_location = location;
+                self.save_output_location();
+            }
+            Message::ToggleRecursiveScan(enabled) => {
+                self.recursive_scan = enabled;
+                // Rescan the loaded folder with the new setting
+                if let (InputType::Folder, Some(path)) = (&self.input_type, &self.input_path) {
+                    if !self.processing {
+                        let path = path.clone();
+                        return Command::perform(async move { Some(path) }, Message::FolderSelected);
+                    }
+                }
+            }
+            Message::StartWatching => {
+                if self.outputs_in_input_folder() {
+                    self.status_message = "Can't watch a folder that also receives the outputs, pick another output location".to_string();
+                } else if self.input_type == InputType::Folder {
+                    self.watching = self.input_path.clone();
+                    self.watch_queue.clear();
+                    if let Some(folder) = &self.watching {
+                        self.status_message = format!("Watching {} for new images", folder.display());
+                    }
+                }
+            }
+            Message::StopWatching => {
+                self.watching = None;
+                self.watch_queue.clear();
+                self.status_message = "Stopped watching".to_string();
+            }
+            Message::WatchedFilesArrived(paths) => {
+                if self.watched_folder().is_none() {
                     return Command::none();
                 }
-                
-                let Some(model) = self.selected_model.clone() else {
-                    self.status_message = "No model selected".to_string();
+                // The output location changed to the watched folder, every output would be upscaled again
+                if self.outputs_in_input_folder() {
+                    log_error("Stopped watching, the outputs are written to the watched folder");
+                    self.status_message = "Stopped watching, the outputs are written to the watched folder".to_string();
+                    self.watching = None;
+                    self.watch_queue.clear();
                     return Command::none();
-                };
+                }
+                let new_files: Vec<PathBuf> = paths.into_iter()
+                    .filter(|p| p.is_file() && !self.image_files.contains(p) && !self.watch_queue.contains(p))
+                    .collect();
+                if new_files.is_empty() {
+                    return Command::none();
+                }
                 
-                self.processing = true;
+                log_message(&format!("Watch folder: {} new image(s)", new_files.len()));
+                self.image_files.extend(new_files.iter().cloned());
+                sort_naturally(&mut self.image_files);
+                self.watch_queue.extend(new_files);
+                return self.process_watch_queue();
+            }
+            Message::WatchFailed(e) => {
+                log_error(&format!("Failed to watch folder: {}", e));
+                self.status_message = format!("Failed to watch folder: {}", e);
+                self.watching = None;
+                self.watch_queue.clear();
+            }
+            Message::CancelProcess => {
+                if let Some(cancel_token) = &self.cancel_token {
+                    cancel_token.store(true, Ordering::Relaxed);
+                    self.status_message = "Cancelling after the current image...".to_string();
+                }
+            }
+            Message::ProgressUpdate { current, total, processed, avg_duration } => {
+                if !self.processing {
+                    return Command::none();
+                }
+                self.batch_progress = Some((current, total, avg_duration));
+                self.sample_throughput(processed);
+                // Pause and low disk space have their own status text
+                if !self.pause_flag.load(Ordering::Relaxed) && self.disk_space_low.is_none() {
+                    let remaining = avg_duration * total.saturating_sub(current) as u32;
+                    self.status_message = if current == 0 {
+                        format!("Processing 0/{}", total)
+                    } else {
+                        format!("Processing {}/{} – est. {} remaining", current, total, format_eta(remaining))
+                    };
+                }
+            }
+            Message::LogEntry(level, message) => {
+                if level < self.config.log_level {
+                    return Command::none();
+                }
+                self.log_entries.push_back((level, message));
+                while self.log_entries.len() > MAX_LOG_ENTRIES {
+                    self.log_entries.pop_front();
+                }
+            }
+            Message::ToggleLogPanel => {
+                self.log_collapsed = !self.log_collapsed;
+            }
+            Message::PauseProcess => {
+                self.pause_flag.store(true, Ordering::Relaxed);
+                self.status_message = self.paused_status();
+            }
+            Message::ResumeProcess => {
+                self.pause_flag.store(false, Ordering::Relaxed);
                 self.status_message = "Processing...".to_string();
+            }
+            Message::DownloadModel => {
+                if let Some(model) = self.selected_model.clone() {
+                    if self.download_progress.is_none() && model.url != "local" && !self.model_cache.is_cached(&model) {
+                        return self.start_download(&model);
+                    }
+                }
+            }
+            Message::DownloadProgress(model_name, bytes_received, total_bytes) => {
+                // Updates still queued after DownloadComplete are dropped
+                if self.download_progress.as_ref().is_some_and(|(name, _, _)| *name == model_name) {
+                    self.download_progress = Some((model_name, bytes_received, total_bytes));
+                }
+            }
+            Message::DownloadComplete(model_name, result) => {
+                self.download_progress = None;
+                let process_after_download = std::mem::take(&mut self.process_after_download);
                 
-                let files = self.image_files.clone();
-                let output_dir = if self.input_type == InputType::Folder {
-                    self.input_path.as_ref()
-                        .map(|p| p.join("processed"))
-                        .unwrap_or_else(|| PathBuf::from("./processed"))
-                } else {
-                    PathBuf::from("./processed")
-                };
+                match result {
+                    Ok(()) => {
+                        self.status_message = format!("Downloaded {}", model_name);
+                        if let Some(files) = process_after_download {
+                            return self.start_processing(files);
+                        }
+                        return self.process_watch_queue();
+                    }
+                    Err(e) => {
+                        log_error(&format!("Failed to download model {}: {}", model_name, e));
+                        self.status_message = format!("Download of {} failed: {}", model_name, e);
+                    }
+                }
+            }
+            Message::RegistryFetched(Ok(models)) => {
+                let added = merge_models(&mut self.available_models, models);
+                log_message(&format!("Model registry added {} model(s)", added));
                 
-                return Command::perform(
-                    process_images(files, model, output_dir),
-                    Message::ProcessComplete,
-                );
+                // The saved model may only exist in the registry, so App::new couldn't restore it
+                let wanted = self.config.selected_model.as_ref()
+                    .filter(|&name| self.selected_model.as_ref().map(|m| &m.name) != Some(name));
+                if let Some(model) = wanted.and_then(|name| self.available_models.iter().find(|m| &m.name == name)) {
+                    self.selected_category = Some(model.model_type.clone());
+                    self.selected_model = Some(model.clone());
+                }
+            }
+            Message::RegistryFetched(Err(e)) => {
+                log_error(&format!("Failed to fetch the model registry: {}", e));
+            }
+            Message::ChecksumFailed(model_name) => {
+                self.processing = false;
+                self.batch_progress = None;
+                self.disk_space_low = None;
+                self.cancel_token = None;
+                self.status_message = format!("Download of model {} was corrupted or incomplete and has been deleted. Please try again.", model_name);
             }
             Message::ProcessComplete(result) => {
                 self.processing = false;
+                self.batch_progress = None;
+                self.disk_space_low = None;
+                let cancelled = self.cancel_token.take().is_some_and(|token| token.load(Ordering::Relaxed));
                 
                 match result {
-                    Ok(results) => {
-                        self.process_results = results.clone();
-                        self.status_message = format!("Completed {} image(s)", results.len());
+                    Ok(mut results) => {
+                        // Restore ratings from a previous run into the same output directory
+                        let output_root = self.output_dir();
+                        let ratings = load_ratings(&output_root);
+                        for result in results.iter_mut() {
+                            result.rating = rating_key(&output_root, &result.output_path)
+                                .and_then(|key| ratings.get(&key).copied());
+                        }
+                        
+                        self.merge_process_results(&results);
+                        self.last_psnr = None;
+                        let skipped = results.iter().filter(|r| r.status == ProcessResultStatus::Skipped).count();
+                        self.status_message = if cancelled {
+                            format!("Cancelled, {} image(s) completed", results.len())
+                        } else if skipped > 0 {
+                            format!("Completed {} image(s), skipped {} with existing output", results.len() - skipped, skipped)
+                        } else {
+                            format!("Completed {} image(s)", results.len())
+                        };
+                        
+                        if self.auto_open_output {
+                            if let Some(result) = results.iter().find(|r| r.data_uri.is_none()) {
+                                open_in_default_viewer(&result.output_path);
+                            }
+                        }
                         
+                        let upload_command = if self.input_type == InputType::CloudUrl && self.upload_output_to_cloud {
+                            let uploads: Vec<(PathBuf, String)> = results.iter()
+                                .filter(|r| r.data_uri.is_none())
+                                .filter_map(|r| self.cloud_sources.get(&r.input_path)
+                                    .map(|url| (r.output_path.clone(), url.clone())))
+                                .collect();
+                            
+                            self.status_message = format!("Completed {} image(s), uploading...", results.len());
+                            Command::perform(
+                                upload_cloud_outputs(uploads, self.config.cloud.clone()),
+                                Message::CloudUploadComplete,
+                            )
+                        } else {
+                            Command::none()
+                        };
+                        
+                        let mut preview_command = Command::none();
                         if let Some(filename) = &self.selected_preview_file {
                             if let Some(file_path) = self.image_files.iter()
                                 .find(|p| p.file_name().and_then(|n| n.to_str()) == Some(filename)) {
                                 
                                 if let Some(result) = results.iter().find(|r| &r.input_path == file_path) {
-                                    if let Ok(after_img) = image::open(&result.output_path) {
-                                        self.after_image = Some(Arc::new(after_img));
+                                    if let Some(after_img) = load_result_image(result) {
+                                        self.set_after_image(Some(after_img));
+                                        preview_command = Command::batch([
+                                            self.refresh_ssim_map(),
+                                            self.refresh_psnr(),
+                                            self.refresh_change_heatmap(),
+                                            self.refresh_palette(),
+                                        ]);
                                     }
                                 }
                             }
                         }
+                        
+                        // The extracted images stay until another input replaces them, see discard_zip_extraction
+                        let mut zip_command = Command::none();
+                        if let (InputType::Zip, Some(zip_path)) = (&self.input_type, &self.input_path) {
+                            let outputs: Vec<(PathBuf, PathBuf)> = results.iter()
+                                .filter(|r| r.data_uri.is_none())
+                                .map(|r| (r.input_path.clone(), r.output_path.clone()))
+                                .collect();
+                            let completed = results.len();
+                            
+                            self.status_message = format!("Completed {} image(s), writing ZIP...", completed);
+                            zip_command = Command::perform(
+                                repack_zip_in_background(zip_path.clone(), outputs),
+                                move |result| Message::ZipRepacked(completed, result),
+                            );
+                        }
+                        
+                        return Command::batch([upload_command, preview_command, zip_command, self.process_watch_queue()]);
                     }
                     Err(e) => {
+                        // Keep whatever finished before a fail-fast stop
+                        self.merge_process_results(&e.completed);
                         self.status_message = format!("Error: {}", e);
+                        return self.process_watch_queue();
                     }
                 }
             }
             Message::ZoomIn => {
-                self.zoom_level = (self.zoom_level * 1.2).min(5.0);
+                self.zoom_level = (self.zoom_level * 1.2).min(MAX_ZOOM);
             }
             Message::ZoomOut => {
-                self.zoom_level = (self.zoom_level / 1.2).max(0.1);
+                self.zoom_level = (self.zoom_level / 1.2).max(MIN_ZOOM);
+            }
+            Message::ScrollZoom(lines) => {
+                self.zoom_level = (self.zoom_level + lines * SCROLL_ZOOM_STEP).clamp(MIN_ZOOM, MAX_ZOOM);
+            }
+            Message::PreviewHovered(hovered) => {
+                self.preview_hovered = hovered;
+            }
+            Message::ScrollMoved(offset) => {
+                // Content that fits its viewport reports NaN
+                let offset = RelativeOffset {
+                    x: if offset.x.is_finite() { offset.x } else { 0.0 },
+                    y: if offset.y.is_finite() { offset.y } else { 0.0 },
+                };
+                if offset != self.scroll_offset {
+                    self.scroll_offset = offset;
+                    return Command::batch([
+                        scrollable::snap_to(scrollable::Id::new(BEFORE_PREVIEW_SCROLL_ID), offset),
+                        scrollable::snap_to(scrollable::Id::new(AFTER_PREVIEW_SCROLL_ID), offset),
+                    ]);
+                }
             }
             Message::ResetZoom => {
                 self.zoom_level = 1.0;
             }
-        }
-        
-        Command::none()
-    }
-
-    fn view(&self) -> Element<Message> {
-        let header = container(
-            column![
-                text("Super-Resolution Upscaler")
-                    .size(16)
-                    .font(HEADING_FONT)
-                    .style(Color::WHITE),
-                text("AI-powered upscaling, denoising & restoration")
-                    .size(11)
-                    .font(BODY_FONT)
-                    .style(Color::from_rgba(1.0, 1.0, 1.0, 0.8)),
-            ].spacing(4)
-        )
-        .width(Length::Fill)
-        .padding([18, 26])
-        .style(theme::Container::Custom(Box::new(GradientContainer)));
-
-        let file_btn = button("Browse File").on_press(Message::BrowseFile).padding(10);
+            Message::RateResult { output_path, rating } => {
+                if let Some(result) = self.process_results.iter_mut()
+                    .find(|r| r.output_path == output_path) {
+                    // Clicking the current rating again clears it
+                    result.rating = if result.rating == Some(rating) { None } else { Some(rating) };
+                }
+                
+                if let Err(e) = save_ratings(&self.output_dir(), &self.process_results) {
+                    log_error(&format!("Failed to save ratings: {}", e));
+                    self.status_message = format!("Error: {}", e);
+                }
+            }
+            Message::ExportStarred => {
+                if self.process_results.is_empty() {
+                    return Command::none();
+                }
+                
+                let export_dir = self.output_dir().join("starred");
+                match export_rated_results(&self.process_results, 4, &export_dir) {
+                    Ok(count) => {
+                        self.status_message = format!("Exported {} image(s) to {}", count, export_dir.display());
+                    }
+                    Err(e) => {
+                        log_error(&format!("Failed to export rated images: {}", e));
+                        self.status_message = format!("Error: {}", e);
+                    }
+                }
+            }
+            Message::DeleteUnrated => {
+                let (unrated, rated): (Vec<_>, Vec<_>) = self.process_results.drain(..)
+                    .partition(|r| r.rating.is_none());
+                
+                let mut deleted = 0;
+                for result in &unrated {
+                    match fs::remove_file(&result.output_path) {
+                        Ok(()) => deleted += 1,
+                        Err(e) => log_error(&format!("Failed to delete {}: {}", result.output_path.display(), e)),
+                    }
+                }
+                self.process_results = rated;
+                
+                // Drop the after preview if its output was just deleted
+                let preview_kept = self.selected_preview_file.as_ref().is_some_and(|filename| {
+                    self.process_results.iter().any(|r| {
+                        r.input_path.file_name().and_then(|n| n.to_str()) == Some(filename.as_str())
+                    })
+                });
+                if !preview_kept {
+                    self.set_after_image(None);
+                }
+                
+                self.status_message = format!("Deleted {} unrated image(s)", deleted);
+            }
+            Message::ToggleGpuThrottle(enabled) => {
+                self.gpu_temp_limit = enabled.then_some(DEFAULT_GPU_TEMP_LIMIT);
+            }
+            Message::GpuTempLimitChanged(limit) => {
+                self.gpu_temp_limit = Some(limit);
+            }
+            Message::ToggleDownloadSpeedLimit(enabled) => {
+                self.download_speed_limit_kbps = enabled.then_some(DEFAULT_DOWNLOAD_SPEED_LIMIT_KBPS);
+            }
+            Message::DownloadSpeedLimitChanged(limit) => {
+                self.download_speed_limit_kbps = Some(limit);
+            }
+            Message::Tick => {
+                if self.processing && self.pause_flag.load(Ordering::Relaxed) {
+                    self.status_message = self.paused_status();
+                }
+                
+                // The redraw picks up live batch stats such as GPU temperature; only a disk pause needs a prompt
+                if self.disk_space_low.is_none() {
+                    if let Some(free_gb) = self.disk_space.pending_prompt() {
+                        return Command::perform(async move { free_gb }, |free_gb| Message::DiskSpaceLow { free_gb });
+                    }
+                }
+            }
+            Message::DiskSpaceLow { free_gb } => {
+                self.disk_space_low = Some(free_gb);
+                self.status_message = format!("Paused: only {:.2} GB free on the output drive", free_gb);
+            }
+            Message::DiskSpaceContinue => {
+                self.disk_space_low = None;
+                self.disk_space.decide(true);
+                self.status_message = "Processing...".to_string();
+            }
+            Message::StartFlickerMode => {
+                self.flicker_mode = self.after_image.is_some();
+                self.flicker_show_after = false;
+            }
+            Message::StopFlickerMode => {
+                self.flicker_mode = false;
+            }
+            Message::FlickerRateChanged(hz) => {
+                self.flicker_hz = hz;
+            }
+            Message::FlickerTick => {
+                self.flicker_show_after = !self.flicker_show_after;
+            }
+            Message::DiskSpaceAbort => {
+                self.disk_space_low = None;
+                self.disk_space.decide(false);
+            }
+            Message::ToggleVideoSegments(enabled) => {
+                self.video_segment_duration = enabled.then_some(DEFAULT_VIDEO_SEGMENT_SECS);
+            }
+            Message::VideoSegmentMinutesChanged(minutes) => {
+                self.video_segment_duration = Some(minutes as f32 * 60.0);
+            }
+            Message::ToggleAutoSelectModel(enabled) => {
+                self.auto_select_model = enabled;
+            }
+            Message::ClassifierRouteChanged(class, model) => {
+                self.classifier_routing.set(class, model.name);
+            }
+            Message::OutputFormatSelected(format) => {
+                self.output_format = format;
+            }
+            Message::OutputQualityChanged(quality) => {
+                self.output_format = self.output_format.with_quality(quality);
+            }
+            Message::VideoCrfChanged(crf) => {
+                self.video_options.crf = crf.min(MAX_VIDEO_CRF);
+            }
+            Message::ToggleVideoBitrateMode(enabled) => {
+                self.video_options.target_bitrate_kbps = enabled.then_some(DEFAULT_VIDEO_BITRATE_KBPS);
+            }
+            Message::VideoBitrateChanged(kbps) => {
+                self.video_options.target_bitrate_kbps = Some(kbps);
+            }
+            Message::TogglePreserveSubtitles(enabled) => {
+                self.video_options.preserve_subtitles = enabled;
+            }
+            Message::ToggleDeinterlace(enabled) => {
+                self.deinterlace_video = enabled;
+                self.refresh_video_checkpoint();
+            }
+            // Empty clears the bound, anything but digits is ignored
+            Message::VideoStartFrameChanged(value) => {
+                if value.is_empty() {
+                    self.video_options.start_frame = None;
+                } else if let Ok(frame) = value.parse() {
+                    self.video_options.start_frame = Some(frame);
+                }
+                self.refresh_video_checkpoint();
+            }
+            Message::VideoEndFrameChanged(value) => {
+                if value.is_empty() {
+                    self.video_options.end_frame = None;
+                } else if let Ok(frame) = value.parse() {
+                    self.video_options.end_frame = Some(frame);
+                }
+                self.refresh_video_checkpoint();
+            }
+            Message::SortResults(column, order) => {
+                self.results_sort = (column, order);
+            }
+            Message::ToggleRatedOnly(enabled) => {
+                self.results_rated_only = enabled;
+            }
+            Message::HashesComputed(generation, hashes) => {
+                if generation != self.hash_generation {
+                    return Command::none();
+                }
+                self.duplicate_pairs = find_near_duplicates(&self.image_files, &hashes);
+                self.image_hashes = hashes;
+                
+                if !self.duplicate_pairs.is_empty() {
+                    log_message(&format!("Duplicates detected: {}", self.duplicate_pairs.len()));
+                }
+            }
+            Message::SkipDuplicates => {
+                let skipped: std::collections::HashSet<&PathBuf> = self.duplicate_pairs.iter()
+                    .map(|(_, duplicate)| duplicate)
+                    .collect();
+                let before = self.image_files.len();
+                
+                self.image_files.retain(|p| !skipped.contains(p));
+                self.status_message = format!("Skipped {} near-duplicate(s), {} image(s) queued",
+                    before - self.image_files.len(), self.image_files.len());
+                self.duplicate_pairs.clear();
+            }
+            Message::ToggleTiling(enabled) => {
+                self.tile_size = if enabled {
+                    // Auto-detect only the first time, afterwards keep the user's choice
+                    Some(*self.last_tile_size.get_or_insert_with(auto_tile_size))
+                } else {
+                    None
+                };
+                return self.schedule_config_save();
+            }
+            Message::TileSizeChanged(size) => {
+                self.tile_size = Some(size);
+                self.last_tile_size = Some(size);
+                return self.schedule_config_save();
+            }
+            Message::ToggleSplitGpuCpu(enabled) => {
+                self.split_gpu_cpu = enabled;
+            }
+            Message::ExecutionProviderSelected(ep) => {
+                self.ort_config.ep = ep;
+                self.config.preferred_ep = ep;
+                if let Err(e) = self.config.save() {
+                    log_error(&format!("Failed to save config: {}", e));
+                }
+                #[cfg(feature = "coreml")]
+                if ep == ExecutionProvider::CoreML {
+                    return Command::perform(check_coreml_available(), Message::CoreMLChecked);
+                }
+            }
+            #[cfg(feature = "coreml")]
+            Message::CoreMLChecked(result) => {
+                if let Err(e) = result {
+                    log_error(&format!("CoreML unavailable: {}", e));
+                    // Only for this session, the saved preference stays CoreML
+                    self.ort_config.ep = ExecutionProvider::Cpu;
+                    self.status_message = format!("⚠ CoreML unavailable ({}), using CPU", e);
+                }
+            }
+            Message::ExecutionProviderProbed(ep) => {
+                self.active_ep = Some(ep);
+            }
+            Message::VideoCodecsProbed(codecs) => {
+                self.available_video_codecs = codecs;
+            }
+            Message::VideoCodecSelected(codec) => {
+                self.video_options.video_codec = Some(codec);
+            }
+            #[cfg(feature = "openvino")]
+            Message::OpenVinoDeviceSelected(device) => {
+                self.ort_config.openvino_device = device.clone();
+                self.config.openvino_device = device;
+                if let Err(e) = self.config.save() {
+                    log_error(&format!("Failed to save config: {}", e));
+                }
+            }
+            Message::BatchSizeChanged(size) => {
+                self.process_options.batch_size = size as usize;
+            }
+            Message::ExifModeSelected(mode) => {
+                self.process_options.exif_mode = mode;
+            }
+            Message::ColorProfileModeSelected(mode) => {
+                self.process_options.color_profile_mode = mode;
+            }
+            Message::ToggleOptimizePng(enabled) => {
+                self.optimize_png = enabled;
+            }
+            Message::ToggleAutoOpenOutput(enabled) => {
+                self.auto_open_output = enabled;
+            }
+            Message::ExportComparison => {
+                let (Some(before), Some(after)) = (self.before_image.clone(), self.after_image.clone()) else {
+                    return Command::none();
+                };
+                let [r, g, b, _] = self.colors().text.into_rgba8();
+                let file_name = match &self.selected_model {
+                    Some(model) => format!("comparison_{}_x{}.png",
+                        model.name.replace(|c: char| !c.is_ascii_alphanumeric(), "_"), model.scale),
+                    None => "comparison.png".to_string(),
+                };
+                
+                return Command::perform(
+                    async move {
+                        let path = rfd::AsyncFileDialog::new()
+                            .add_filter("PNG", &["png"])
+                            .set_file_name(file_name)
+                            .save_file()
+                            .await?
+                            .path()
+                            .to_path_buf();
+                        
+                        let result = tokio::task::spawn_blocking(move || {
+                            build_comparison_image(&before, &after, image::Rgba([r, g, b, 255]))
+                                .save_with_format(&path, image::ImageFormat::Png)
+                                .map(|_| path)
+                                .map_err(|e| e.to_string())
+                        })
+                        .await
+                        .map_err(|e| e.to_string())
+                        .and_then(|r| r);
+                        Some(result)
+                    },
+                    Message::ComparisonSaved,
+                );
+            }
+            Message::ComparisonSaved(result) => {
+                match result {
+                    Some(Ok(path)) => self.status_message = format!("Saved comparison to {}", path.display()),
+                    Some(Err(e)) => {
+                        log_error(&format!("Failed to save comparison: {}", e));
+                        self.status_message = format!("Failed to save comparison: {}", e);
+                    }
+                    None => {}
+                }
+            }
+            Message::ToggleDiffView => {
+                self.show_diff_view = !self.show_diff_view && self.after_image.is_some();
+                return self.refresh_ssim_map();
+            }
+            Message::SsimComputed(pair, result) => {
+                if let (true, Some((score, map))) = (self.is_current_pair(&pair), result) {
+                    self.ssim_score = Some(score);
+                    self.ssim_map = Some(map);
+                }
+            }
+            Message::PsnrComputed(pair, psnr) => {
+                if self.is_current_pair(&pair) {
+                    self.last_psnr = psnr;
+                }
+            }
+            Message::ChangeHeatmapComputed(pair, heatmap) => {
+                if self.is_current_pair(&pair) {
+                    self.change_heatmap = heatmap;
+                }
+            }
+            Message::PaletteExtracted(after, palette) => {
+                if self.after_image.as_ref().is_some_and(|current| Arc::ptr_eq(current, &after)) {
+                    self.after_palette = palette;
+                }
+            }
+            Message::ToggleComparisonMode => {
+                self.comparison_mode = match self.comparison_mode {
+                    ComparisonMode::SideBySide => ComparisonMode::SplitLine,
+                    ComparisonMode::SplitLine => ComparisonMode::SideBySide,
+                };
+                self.split_dragging = false;
+                self.refresh_split_before();
+            }
+            Message::SplitCursorMoved(x) => {
+                self.split_cursor_x = x;
+                if self.split_dragging {
+                    self.set_split_from_cursor();
+                }
+            }
+            Message::SplitDragStarted => {
+                // Pressing anywhere jumps the divider there, then it follows the cursor
+                self.split_dragging = true;
+                self.set_split_from_cursor();
+            }
+            Message::SplitDragEnded => {
+                self.split_dragging = false;
+            }
+            Message::ToggleChangeHeatmap(enabled) => {
+                self.show_change_heatmap = enabled;
+                return self.refresh_change_heatmap();
+            }
+            Message::ResetSettings => {
+                self.reset_settings();
+                self.status_message = "Settings reset to defaults".to_string();
+            }
+            Message::ToggleFailFast(enabled) => {
+                self.fail_fast = enabled;
+            }
+            Message::ToggleCropToContent(enabled) => {
+                self.crop_to_content = enabled;
+            }
+            Message::CloudUrlChanged(value) => {
+                self.cloud_url_input = value;
+            }
+            Message::LoadCloudUrls => {
+                let urls: Vec<String> = self.cloud_url_input
+                    .split(|c: char| c.is_whitespace() || c == ',')
+                    .filter(|s| !s.is_empty())
+                    .map(|s| s.to_string())
+                    .collect();
+                
+                if let Some(bad) = urls.iter().find(|u| !is_cloud_url(u)) {
+                    self.status_message = format!("Not a supported cloud URL: {}", bad);
+                    return Command::none();
+                }
+                if urls.is_empty() {
+                    return Command::none();
+                }
+                
+                self.status_message = format!("Downloading {} cloud input(s)...", urls.len());
+                return Command::perform(
+                    download_cloud_inputs(urls, cloud_temp_dir(), self.config.cloud.clone()),
+                    Message::CloudDownloaded,
+                );
+            }
+            Message::CloudDownloaded(result) => {
+                match result {
+                    Ok(downloads) if downloads.is_empty() => {
+                        self.status_message = "No images found at the given URL(s)".to_string();
+                    }
+                    Ok(downloads) => {
+                        let files: Vec<PathBuf> = downloads.iter().map(|(path, _)| path.clone()).collect();
+                        
+                        self.cloud_sources = downloads.into_iter().collect();
+                        self.discard_zip_extraction();
+                        self.input_path = Some(cloud_temp_dir());
+                        self.input_type = InputType::CloudUrl;
+                        self.selected_preview_file = files.first()
+                            .and_then(|p| p.file_name())
+                            .and_then(|n| n.to_str())
+                            .map(|s| s.to_string());
+                        self.image_files = files.clone();
+                        self.image_hashes.clear();
+                        self.thumbnail_cache.clear();
+                        self.thumbnails_requested.clear();
+                        self.duplicate_pairs.clear();
+                        self.set_after_image(None);
+                        self.process_results.clear();
+                        self.status_message = format!("Downloaded {} image(s)", files.len());
+                        self.output_convention = detect_output_convention(&self.output_dir());
+                        self.zoom_level = 1.0;
+                        
+                        let hash_command = self.compute_hashes(files.clone());
+                        let thumbnail_command = self.request_thumbnails(THUMBNAIL_BATCH);
+                        
+                        if let Some(first) = files.first() {
+                            let path = first.clone();
+                            return Command::batch([
+                                hash_command,
+                                thumbnail_command,
+                                Command::perform(
+                                    async move {
+                                        fast_open(&path)
+                                            .map(|img| (img, path.clone()))
+                                            .map_err(|e| e.to_string())
+                                    },
+                                    Message::PreviewLoaded,
+                                ),
+                            ]);
+                        }
+                    }
+                    Err(e) => {
+                        self.status_message = format!("Download failed: {}", e);
+                    }
+                }
+            }
+            Message::ToggleUploadToCloud(enabled) => {
+                self.upload_output_to_cloud = enabled;
+            }
+            Message::CopyPalette => {
+                let hex = self.after_palette.iter()
+                    .map(|[r, g, b]| format!("#{:02X}{:02X}{:02X}", r, g, b))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                
+                self.status_message = format!("Copied {} palette colours to clipboard", self.after_palette.len());
+                return iced::clipboard::write(hex);
+            }
+            Message::CloudUploadComplete(result) => {
+                self.status_message = match result {
+                    Ok(count) => format!("Uploaded {} output(s) to cloud storage", count),
+                    Err(e) => format!("Upload failed: {}", e),
+                };
+            }
+            Message::CopyDataUri => {
+                // Reuse the batch's encoding when the previewed result was produced as a data URI
+                let previewed_uri = self.selected_preview_file.as_ref().and_then(|filename| {
+                    self.process_results.iter()
+                        .find(|r| r.input_path.file_name().and_then(|n| n.to_str()) == Some(filename.as_str()))
+                        .and_then(|r| r.data_uri.clone())
+                });
+                
+                let uri = match (previewed_uri, &self.after_image) {
+                    (Some(uri), _) => Ok(uri),
+                    (None, Some(after_img)) => encode_to_data_uri(after_img, "image/png"),
+                    (None, None) => return Command::none(),
+                };
+                
+                match uri {
+                    Ok(uri) => {
+                        self.status_message = format!("Copied data URI ({} KB) to clipboard", uri.len() / 1024);
+                        return iced::clipboard::write(uri);
+                    }
+                    Err(e) => {
+                        self.status_message = format!("Error: {}", e);
+                    }
+                }
+            }
+        }
+        
+        Command::none()
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        let colors = self.colors();
+        
+        // An explicitly chosen provider is shown as is, Auto as what the startup probe found
+        let ep_badge = match (self.ort_config.ep, self.active_ep) {
+            (ExecutionProvider::Auto, None) => "Detecting accelerator...".to_string(),
+            (ExecutionProvider::Auto, Some(ExecutionProvider::Cpu)) | (ExecutionProvider::Cpu, _) => "CPU".to_string(),
+            (ExecutionProvider::Auto, Some(ep)) | (ep, _) => format!("GPU: {}", ep),
+        };
+        
+        let header = container(
+            row![
+                column![
+                    text("Super-Resolution Upscaler")
+                        .size(16)
+                        .font(HEADING_FONT)
+                        .style(Color::WHITE),
+                    text("AI-powered upscaling, denoising & restoration")
+                        .size(11)
+                        .font(BODY_FONT)
+                        .style(Color::from_rgba(1.0, 1.0, 1.0, 0.8)),
+                ].spacing(4),
+                Space::with_width(Length::Fill),
+            ]
+            .push_maybe(self.config.offline_mode.then(|| {
+                container(text("Offline Mode Active").size(12).style(Color::WHITE))
+                    .padding([2, 8])
+                    .style(theme::Container::Custom(Box::new(BadgeContainer(LogLevel::Warn.color(colors)))))
+            }))
+            .push(container(text(ep_badge).size(12).style(Color::WHITE))
+                .padding([2, 8])
+                .style(theme::Container::Custom(Box::new(BadgeContainer(Color::from_rgba(1.0, 1.0, 1.0, 0.2))))))
+            .push(button(text(if self.dark_mode { "☀" } else { "☾" }).size(16).style(Color::WHITE))
+                .on_press(Message::ToggleDarkMode)
+                .padding([2, 8])
+                .style(theme::Button::Text))
+            .spacing(10)
+            .align_items(Alignment::Center)
+        )
+        .width(Length::Fill)
+        .padding([18, 26])
+        .style(theme::Container::Custom(Box::new(GradientContainer)));
+
+        let file_btn = button("Browse File").on_press(Message::BrowseFile).padding(10);
         let folder_btn = button("Browse Folder").on_press(Message::BrowseFolder).padding(10);
+        let zip_btn = button("Browse ZIP").on_press(Message::BrowseZip).padding(10);
         
         let video_btn = button("Browse Video")
             .on_press(Message::BrowseVideo)
             .padding(10);
     
+        // Moved or deleted paths stay in the config but aren't offered
+        let recent: Vec<RecentEntry> = self.recent.iter()
+            .filter(|entry| entry.path.exists())
+            .cloned()
+            .collect();
+        
         let input_card = card_container(
             column![
-                section_title("Input"),
+                section_title("Input", colors),
                 Space::with_height(8),
                 row![
                     file_btn,
                     folder_btn,
+                    zip_btn,
                     video_btn,
+                    checkbox("Recursive", self.recursive_scan)
+                        .on_toggle(Message::ToggleRecursiveScan)
+                        .size(16)
+                        .text_size(14),
+                ]
+                .push_maybe((self.input_type == InputType::Folder).then(|| {
+                    let watching = self.watched_folder().is_some();
+                    button(if watching { "Stop Watching" } else { "Watch Folder" })
+                        .on_press(if watching { Message::StopWatching } else { Message::StartWatching })
+                        .padding(10)
+                        .style(if watching { theme::Button::Primary } else { theme::Button::Secondary })
+                }))
+                .push(
                     text(self.input_path.as_ref()
                         .and_then(|p| p.to_str())
                         .unwrap_or("No file selected"))
                         .size(14)
-                        .style(TEXT_SECONDARY)
-                ]
+                        .style(colors.text_secondary)
+                )
                 .spacing(10)
                 .align_items(Alignment::Center),
-            ].spacing(0)
+            ]
+            .push_maybe((!recent.is_empty()).then(|| {
+                column![
+                    Space::with_height(8),
+                    pick_list(recent, None::<RecentEntry>, Message::RecentSelected)
+                        .placeholder("Recent")
+                        .text_size(14),
+                ]
+            }))
+            .push(Space::with_height(8))
+            .push(
+                row![
+                    text_input("s3://bucket/prefix/, gs://bucket/image.png, https://...blob.core.windows.net/...", &self.cloud_url_input)
+                        .on_input(Message::CloudUrlChanged)
+                        .on_submit(Message::LoadCloudUrls)
+                        .size(14)
+                        .padding(8),
+                    button("Load URLs").on_press(Message::LoadCloudUrls).padding(10),
+                    checkbox("Upload outputs", self.upload_output_to_cloud)
+                        .on_toggle(Message::ToggleUploadToCloud)
+                        .size(16)
+                        .text_size(14),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center)
+            )
+            .spacing(0)
         );
 
         // Category picker
@@ -964,6 +3265,40 @@ impl Application for App {
         } else {
             self.available_models.clone()
         };
+        // Offline, models that would need a download can't be picked and are listed greyed out instead
+        let (filtered_models, not_downloaded): (Vec<ModelInfo>, Vec<ModelInfo>) = filtered_models.into_iter()
+            .partition(|m| !self.config.offline_mode || self.model_cache.is_cached(m));
+
+        let pipeline_steps = self.pipeline.iter().enumerate().fold(column![].spacing(6), |col, (i, step)| {
+            col.push(row![
+                Space::with_width(Length::Fixed(80.0)),
+                text(format!("Step {}", i + 1)).size(12).style(colors.text_secondary),
+                pick_list(
+                    self.available_models.clone(),
+                    Some(step.clone()),
+                    move |model| Message::PipelineStepSelected(i, model),
+                )
+                .text_size(12),
+                button(text("Remove").size(12))
+                    .on_press_maybe((!self.processing).then_some(Message::PipelineStepRemoved(i)))
+                    .padding([2, 8])
+                    .style(theme::Button::Text),
+            ].spacing(10).align_items(Alignment::Center))
+        });
+        let pipeline_section = column![
+            row![
+                text("Pipeline:").size(14).style(colors.text_secondary).width(Length::Fixed(80.0)),
+                button(text("Add Step").size(12))
+                    .on_press_maybe((!self.processing && !self.available_models.is_empty()).then_some(Message::PipelineStepAdded))
+                    .padding([2, 8])
+                    .style(theme::Button::Secondary),
+                text(match self.pipeline.len() {
+                    0 | 1 => "Add two or more steps to chain models".to_string(),
+                    n => format!("{} models run in order", n),
+                }).size(12).style(colors.text_secondary),
+            ].spacing(10).align_items(Alignment::Center),
+            pipeline_steps,
+        ].spacing(6);
 
         let model_picker = pick_list(
             filtered_models,
@@ -994,77 +3329,680 @@ impl Application for App {
                 .padding([8, 10])
                 .style(theme::Button::Primary)
         };
+        // Only image batches carry a cancel token and watch the pause flag
+        let batch_running = self.processing && self.cancel_token.is_some();
+        let paused = self.pause_flag.load(Ordering::Relaxed);
+        let process_btn = row![process_btn]
+            .push_maybe(batch_running.then(|| {
+                let (label, message) = if paused {
+                    ("Resume", Message::ResumeProcess)
+                } else {
+                    ("Pause", Message::PauseProcess)
+                };
+                button(text(label).font(HEADING_FONT).size(14))
+                    .on_press(message)
+                    .padding([8, 10])
+                    .style(theme::Button::Secondary)
+            }))
+            .push_maybe(batch_running.then(|| {
+                button(text("Cancel").font(HEADING_FONT).size(14))
+                    .on_press(Message::CancelProcess)
+                    .padding([8, 10])
+                    .style(theme::Button::Destructive)
+            }))
+            .spacing(8);
 
         let mut settings_card_content = column![
-            section_title("Settings"),
+            section_title("Settings", colors),
+            Space::with_height(8),
+            row![
+                text("Preset:").size(14).style(colors.text_secondary).width(Length::Fixed(80.0)),
+                pick_list(
+                    self.presets.iter().map(|p| p.name.clone()).collect::<Vec<_>>(),
+                    self.selected_preset.clone(),
+                    Message::PresetSelected,
+                )
+                .placeholder("Saved presets"),
+                button(text("Delete Preset").size(14))
+                    .on_press_maybe(self.selected_preset.as_ref().map(|_| Message::DeletePreset))
+                    .padding([4, 12])
+                    .style(theme::Button::Text),
+                text_input("Preset name", &self.preset_name_input)
+                    .on_input(Message::PresetNameChanged)
+                    .on_submit(Message::SavePreset)
+                    .width(Length::Fixed(180.0)),
+                button(text("Save Preset").size(14))
+                    .on_press_maybe((!self.preset_name_input.trim().is_empty() && self.selected_model.is_some())
+                        .then_some(Message::SavePreset))
+                    .padding([4, 12])
+                    .style(theme::Button::Secondary),
+            ].spacing(10).align_items(Alignment::Center),
             Space::with_height(8),
             row![
-                text("Category:").size(14).style(TEXT_SECONDARY).width(Length::Fixed(80.0)),
+                text("Category:").size(14).style(colors.text_secondary).width(Length::Fixed(80.0)),
                 category_picker
             ].spacing(10).align_items(Alignment::Center),
             Space::with_height(8),
             row![
-                text("Model:").size(14).style(TEXT_SECONDARY).width(Length::Fixed(80.0)),
-                model_picker
+                text("Model:").size(14).style(colors.text_secondary).width(Length::Fixed(80.0)),
+                model_picker,
+                button(text(if self.quantizing { "Quantizing..." } else { "Quantize Model" }).size(14))
+                    .on_press_maybe(self.selected_model.as_ref()
+                        .filter(|m| !m.quantized && !self.quantizing && !self.processing)
+                        .map(|_| Message::QuantizeModel))
+                    .padding([4, 12])
+                    .style(theme::Button::Secondary),
+                button(text("Add Custom Model").size(14))
+                    .on_press_maybe((!self.processing).then_some(Message::ImportCustomModel))
+                    .padding([4, 12])
+                    .style(theme::Button::Secondary),
             ].spacing(10).align_items(Alignment::Center),
-            Space::with_height(12),
-            process_btn,
+            column![].push_maybe((!not_downloaded.is_empty()).then(|| row![
+                Space::with_width(Length::Fixed(80.0)),
+                text(not_downloaded.iter()
+                    .map(|m| format!("{} (not downloaded)", m))
+                    .collect::<Vec<_>>()
+                    .join(", "))
+                    .size(12)
+                    .style(colors.text_secondary),
+            ].spacing(10).padding([6, 0, 0, 0]))),
+            Space::with_height(8),
+            row![
+                Space::with_width(Length::Fixed(80.0)),
+                text(match self.selected_model.as_ref().and_then(|m| self.model_cache.cached_size(m)) {
+                    Some(bytes) => format!("Cached ({})", format_file_size(bytes)),
+                    None => "Not downloaded yet".to_string(),
+                }).size(12).style(colors.text_secondary),
+                button(text("Download").size(12))
+                    .on_press_maybe(self.selected_model.as_ref()
+                        .filter(|m| self.download_progress.is_none() && m.url != "local" && !self.model_cache.is_cached(m))
+                        .map(|_| Message::DownloadModel))
+                    .padding([2, 8])
+                    .style(theme::Button::Text),
+                button(text("Inspect").size(12))
+                    .on_press_maybe(self.selected_model.clone()
+                        .filter(|_| !self.processing)
+                        .map(Message::InspectModel))
+                    .padding([2, 8])
+                    .style(theme::Button::Text),
+                button(text("Delete cached file").size(12))
+                    .on_press_maybe(self.selected_model.as_ref()
+                        .filter(|m| !self.processing && !self.quantizing && m.url != "local" && self.model_cache.is_cached(m))
+                        .map(|_| Message::DeleteCachedModel))
+                    .padding([2, 8])
+                    .style(theme::Button::Text),
+            ].spacing(10).align_items(Alignment::Center),
+            column![].push_maybe(self.download_progress.as_ref().map(|(name, received, total)| {
+                row![
+                    Space::with_width(Length::Fixed(80.0)),
+                    progress_bar(0.0..=total.unwrap_or(0).max(1) as f32, *received as f32)
+                        .width(Length::Fixed(200.0))
+                        .height(8),
+                    text(match total {
+                        Some(total) => format!("Downloading {}… {} / {}", name, format_file_size(*received), format_file_size(*total)),
+                        None => format!("Downloading {}… {}", name, format_file_size(*received)),
+                    }).size(12).style(colors.text_secondary),
+                ].spacing(10).align_items(Alignment::Center)
+            })),
             Space::with_height(8),
-            text(&self.status_message).size(12).style(TEXT_SECONDARY),
+            row![
+                Space::with_width(Length::Fixed(80.0)),
+                text("Runs").size(12).style(colors.text_secondary),
+                slider(1..=20, self.benchmark_runs, Message::BenchmarkRunsChanged).width(Length::Fixed(120.0)),
+                text(self.benchmark_runs.to_string()).size(12).style(colors.text_secondary),
+                button(text(if self.benchmarking { "Benchmarking..." } else { "Benchmark" }).size(12))
+                    .on_press_maybe((self.selected_model.is_some() && self.before_image.is_some() && !self.benchmarking && !self.processing)
+                        .then_some(Message::Benchmark))
+                    .padding([2, 8])
+                    .style(theme::Button::Secondary),
+            ].spacing(10).align_items(Alignment::Center),
+            Space::with_height(8),
+            pipeline_section,
+            Space::with_height(8),
+            row![
+                text("Output:").size(14).style(colors.text_secondary).width(Length::Fixed(80.0)),
+                pick_list(
+                    OutputFormat::all(self.output_format.quality().unwrap_or(DEFAULT_OUTPUT_QUALITY)),
+                    Some(self.output_format.clone()),
+                    Message::OutputFormatSelected,
+                ),
+            ]
+            .push_maybe(self.output_format.quality().map(|quality| {
+                slider(1..=100, quality, Message::OutputQualityChanged).width(Length::Fixed(120.0))
+            }))
+            .push_maybe(self.output_format.quality().map(|quality| {
+                text(format!("Quality {}", quality)).size(12).style(colors.text_secondary)
+            }))
+            .spacing(10).align_items(Alignment::Center),
+            row![
+                text("Save to:").size(14).style(colors.text_secondary).width(Length::Fixed(80.0)),
+                radio("Subfolder", 0, Some(self.output_location.index()), |_| {
+                    Message::OutputLocationSelected(OutputLocation::SiblingFolder(DEFAULT_OUTPUT_SUBFOLDER.to_string()))
+                }).size(16).text_size(14),
+                radio("Next to inputs", 1, Some(self.output_location.index()), |_| {
+                    Message::OutputLocationSelected(OutputLocation::SameDirectory)
+                }).size(16).text_size(14),
+                // Without a picked folder yet, choosing Custom opens the picker
+                radio("Custom", 2, Some(self.output_location.index()), |_| match self.output_dir.clone() {
+                    Some(path) => Message::OutputLocationSelected(OutputLocation::CustomPath(path)),
+                    None => Message::BrowseOutputDir,
+                }).size(16).text_size(14),
+                button(text("Browse Output Dir").size(14))
+                    .on_press(Message::BrowseOutputDir)
+                    .padding([4, 12])
+                    .style(theme::Button::Secondary),
+                text(truncate_path_label(&self.output_dir(), OUTPUT_PATH_LABEL_CHARS)).size(12).style(colors.text_secondary),
+            ].spacing(10).align_items(Alignment::Center),
+            row![
+                text("Filename:").size(14).style(colors.text_secondary).width(Length::Fixed(80.0)),
+                text_input("{stem}_{scale}x", &self.process_options.filename_template)
+                    .on_input(Message::FilenameTemplateChanged)
+                    .size(14)
+                    .width(Length::Fixed(220.0)),
+            ]
+            .push_maybe(self.filename_template_preview().map(|preview| match preview {
+                Ok(name) => text(format!("→ {}", name)).size(12).style(colors.text_secondary),
+                Err(e) => text(format!("Invalid template: {}", e)).size(12).style(LogLevel::Error.color(colors)),
+            }))
+            .push(text("{stem} {model} {scale} {width} {height} {date}").size(12).style(colors.text_secondary))
+            .spacing(10).align_items(Alignment::Center),
+            row![
+                text("If exists:").size(14).style(colors.text_secondary).width(Length::Fixed(80.0)),
+                pick_list(ConflictMode::all(), Some(self.process_options.conflict_mode), Message::ConflictModeSelected),
+            ].spacing(10).align_items(Alignment::Center),
+            row![
+                text("Normalize:").size(14).style(colors.text_secondary).width(Length::Fixed(80.0)),
+                radio("Model default", None, Some(self.norm_override), Message::NormOverrideSelected).size(16).text_size(14),
+                radio("[0,1]", Some(NormalizationRange::ZeroOne), Some(self.norm_override), Message::NormOverrideSelected).size(16).text_size(14),
+                radio("[-1,1]", Some(NormalizationRange::MinusOneOne), Some(self.norm_override), Message::NormOverrideSelected).size(16).text_size(14),
+            ].spacing(10).align_items(Alignment::Center),
+            Space::with_height(8),
+            text(match &self.output_convention {
+                Some(template) => format!("Detected convention: {}", template),
+                None => String::new(),
+            }).size(12).style(colors.text_secondary),
         ]
         .spacing(0);
-
-        if self.input_type == InputType::Folder && !self.image_files.is_empty() {
-            let filenames: Vec<String> = self.image_files.iter()
-                .filter_map(|p| p.file_name())
-                .filter_map(|n| n.to_str())
-                .map(|s| s.to_string())
+        
+        // The classifier has no download URL, so the toggle only shows once one has been put in the cache
+        let classifier_cached = self.cached_classifier().is_some();
+        if classifier_cached {
+            settings_card_content = settings_card_content
+                .push(Space::with_height(8))
+                .push(
+                    checkbox("Auto-select model per image", self.auto_select_model)
+                        .on_toggle(Message::ToggleAutoSelectModel)
+                        .size(16)
+                        .text_size(14),
+                );
+        }
+        
+        if self.auto_select_model && classifier_cached {
+            let routable_models: Vec<ModelInfo> = self.available_models.iter()
+                .filter(|m| matches!(m.model_type, ModelType::Upscaling | ModelType::Enhancement))
+                .cloned()
                 .collect();
             
-            if !filenames.is_empty() {
-                let file_picker = pick_list(
-                    filenames,
-                    self.selected_preview_file.as_ref(),
-                    Message::PreviewFileSelected,
-                )
-                .placeholder("Select file");
+            for class in ImageClass::ALL {
+                let selected = routable_models.iter()
+                    .find(|m| m.name == self.classifier_routing.model_for(class))
+                    .cloned();
                 
-                settings_card_content = settings_card_content.push(Space::with_height(12));
+                settings_card_content = settings_card_content.push(Space::with_height(8));
                 settings_card_content = settings_card_content.push(
                     row![
-                        text("Preview:").size(14).style(TEXT_SECONDARY).width(Length::Fixed(80.0)),
-                        file_picker
+                        text(format!("{}:", class)).size(14).style(colors.text_secondary).width(Length::Fixed(80.0)),
+                        pick_list(
+                            routable_models.clone(),
+                            selected,
+                            move |model| Message::ClassifierRouteChanged(class, model),
+                        )
+                        .placeholder("Select model"),
                     ]
                     .spacing(10)
                     .align_items(Alignment::Center)
                 );
             }
         }
-
-        let settings_card = card_container(settings_card_content);
-
-        let zoom_controls = row![
-            button(text("-").size(18).horizontal_alignment(iced::alignment::Horizontal::Center))
-                .on_press(Message::ZoomOut)
-                .padding([4, 12])
-                .style(theme::Button::Secondary),
-            text(format!("{:.0}%", self.zoom_level * 100.0))
-                .size(14)
-                .style(TEXT_SECONDARY),
-            button(text("+").size(18).horizontal_alignment(iced::alignment::Horizontal::Center))
-                .on_press(Message::ZoomIn)
-                .padding([4, 12])
-                .style(theme::Button::Secondary),
-            button(text("Reset").size(14))
-                .on_press(Message::ResetZoom)
-                .padding([4, 12])
-                .style(theme::Button::Text),
-        ]
-        .spacing(8)
-        .align_items(Alignment::Center)
-        .width(Length::FillPortion(1));
-
-        let preview_card = if let Some(before_img) = &self.before_image {
+        
+        if self.norm_override.is_some() {
+            settings_card_content = settings_card_content.push(column![
+                Space::with_height(8),
+                container(text("Normalization overridden — model default ignored.").size(12).style(Color::from_rgb(0.45, 0.35, 0.0)))
+                    .padding([2, 8])
+                    .style(theme::Container::Custom(Box::new(BadgeContainer(Color::from_rgb(1.0, 0.85, 0.2))))),
+            ]);
+        }
+        
+        if let Some(report) = &self.quantize_report {
+            settings_card_content = settings_card_content.push(column![
+                Space::with_height(8),
+                text(format!("{}: {} → {}, CPU inference {:.0} ms → {:.0} ms",
+                    report.model.name,
+                    format_file_size(report.fp32_bytes),
+                    format_file_size(report.int8_bytes),
+                    report.fp32_secs * 1000.0,
+                    report.int8_secs * 1000.0))
+                    .size(12)
+                    .style(colors.text_secondary),
+            ]);
+        }
+        
+        if let Some(result) = &self.benchmark_result {
+            settings_card_content = settings_card_content.push(column![
+                Space::with_height(8),
+                text(format!("{} on {} at {}x{}: {:.0} ms mean (min {:.0}, max {:.0}, σ {:.0}) over {} runs, {:.2} MP/s",
+                    result.model_name,
+                    result.ep,
+                    result.input_dims.0,
+                    result.input_dims.1,
+                    result.mean_secs * 1000.0,
+                    result.min_secs * 1000.0,
+                    result.max_secs * 1000.0,
+                    result.std_dev_secs * 1000.0,
+                    result.runs,
+                    result.megapixels_per_sec))
+                    .size(12)
+                    .style(colors.text_secondary),
+            ]);
+        }
+        
+        settings_card_content = settings_card_content.push(column![
+            Space::with_height(12),
+            process_btn,
+            Space::with_height(8),
+        ]);
+        settings_card_content = settings_card_content.push_maybe(self.batch_progress.map(|(current, total, _)| {
+            column![
+                progress_bar(0.0..=total.max(1) as f32, current as f32).height(Length::Fixed(6.0)),
+                Space::with_height(6),
+            ]
+        }));
+        settings_card_content = settings_card_content.push(row![
+            text(&self.status_message).size(12).style(colors.text_secondary),
+            Space::with_width(Length::Fill),
+            text(match self.gpu_temp.load(Ordering::Relaxed) {
+                0 => String::new(),
+                temp => format!("GPU: {}°C", temp),
+            }).size(12).style(colors.text_secondary),
+        ]);
+        
+        if self.processing && !self.throughput_history.is_empty() {
+            let now = std::time::Instant::now();
+            let current = self.throughput_history.back().map(|&(_, ipm)| ipm).unwrap_or(0.0);
+            let chart = ThroughputChart {
+                samples: self.throughput_history.iter()
+                    .map(|&(t, ipm)| (now.duration_since(t).as_secs_f32(), ipm))
+                    .collect(),
+            };
+            
+            settings_card_content = settings_card_content.push(Space::with_height(8));
+            settings_card_content = settings_card_content.push(
+                row![
+                    canvas(chart).width(Length::Fill).height(Length::Fixed(80.0)),
+                    text(format!("{:.1} img/min", current)).size(14).style(colors.text_secondary),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center)
+            );
+        }
+        
+        if let Some(free_gb) = self.disk_space_low {
+            settings_card_content = settings_card_content.push(Space::with_height(12));
+            settings_card_content = settings_card_content.push(
+                row![
+                    text(format!("⚠ Low disk space: {:.2} GB free. Free up space, then continue.", free_gb))
+                        .size(14)
+                        .style(Color::from_rgb(0.8, 0.5, 0.1)),
+                    Space::with_width(Length::Fill),
+                    button(text("Continue").size(14))
+                        .on_press(Message::DiskSpaceContinue)
+                        .padding([4, 12]),
+                    button(text("Abort").size(14))
+                        .on_press(Message::DiskSpaceAbort)
+                        .padding([4, 12])
+                        .style(theme::Button::Destructive),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center)
+            );
+        }
+
+        if matches!(self.input_type, InputType::Folder | InputType::CloudUrl | InputType::Zip) && !self.image_files.is_empty() {
+            let thumbnails = self.image_files.iter().fold(row![].spacing(8), |thumbnails, path| {
+                let Some(filename) = path.file_name().and_then(|n| n.to_str()) else {
+                    return thumbnails;
+                };
+                let selected = self.selected_preview_file.as_deref() == Some(filename);
+                // Not loaded yet (or failed), the file name stands in
+                let content: Element<'_, Message> = match self.thumbnail_cache.get(path) {
+                    Some(handle) => iced_image::Image::new(handle.clone())
+                        .width(Length::Fixed(THUMBNAIL_SIZE as f32))
+                        .height(Length::Fixed(THUMBNAIL_SIZE as f32))
+                        .into(),
+                    None => text(filename).size(11).style(colors.text_secondary).into(),
+                };
+                thumbnails.push(
+                    button(
+                        container(content)
+                            .width(Length::Fixed(THUMBNAIL_SIZE as f32))
+                            .height(Length::Fixed(THUMBNAIL_SIZE as f32))
+                            .center_x()
+                            .center_y()
+                            .style(theme::Container::Custom(Box::new(ThumbnailContainer(selected))))
+                    )
+                    .on_press(Message::PreviewFileSelected(filename.to_string()))
+                    .padding(0)
+                    .style(theme::Button::Text)
+                )
+            });
+            
+            settings_card_content = settings_card_content.push(Space::with_height(12));
+            settings_card_content = settings_card_content.push(
+                scrollable(thumbnails)
+                    .id(scrollable::Id::new(THUMBNAIL_SCROLL_ID))
+                    .on_scroll(|viewport| Message::ThumbnailsScrolled(viewport.relative_offset()))
+                    .direction(Direction::Horizontal(Properties::default()))
+                    .width(Length::Fill)
+            );
+            
+            if !self.duplicate_pairs.is_empty() {
+                let file_name = |p: &PathBuf| p.file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("?")
+                    .to_string();
+                
+                let pairs = self.duplicate_pairs.iter().fold(column![].spacing(2), |pairs, (kept, duplicate)| {
+                    pairs.push(text(format!("{} ≈ {}", file_name(duplicate), file_name(kept))).size(12).style(colors.text_secondary))
+                });
+                
+                settings_card_content = settings_card_content.push(Space::with_height(12));
+                settings_card_content = settings_card_content.push(
+                    row![
+                        text(format!("⚠ Duplicates detected: {}", self.duplicate_pairs.len()))
+                            .size(14)
+                            .style(Color::from_rgb(0.8, 0.5, 0.1)),
+                        Space::with_width(Length::Fill),
+                        button(text("Skip duplicates").size(14))
+                            .on_press(Message::SkipDuplicates)
+                            .padding([4, 12])
+                            .style(theme::Button::Secondary),
+                    ]
+                    .align_items(Alignment::Center)
+                );
+                settings_card_content = settings_card_content.push(
+                    scrollable(pairs).height(Length::Fixed(80.0))
+                );
+            }
+        }
+
+        let settings_card = card_container(settings_card_content);
+
+        let mut gpu_throttle_row = row![
+            checkbox("Pause when GPU temperature exceeds limit", self.gpu_temp_limit.is_some())
+                .on_toggle(Message::ToggleGpuThrottle)
+                .size(16)
+                .text_size(14),
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center);
+        
+        if let Some(limit) = self.gpu_temp_limit {
+            gpu_throttle_row = gpu_throttle_row
+                .push(slider(60..=100, limit, Message::GpuTempLimitChanged).width(Length::Fixed(200.0)))
+                .push(text(format!("{}°C", limit)).size(14).style(colors.text_secondary));
+        }
+
+        let mut tile_row = row![
+            checkbox("Tiled inference (full resolution)", self.tile_size.is_some())
+                .on_toggle(Message::ToggleTiling)
+                .size(16)
+                .text_size(14),
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center);
+        
+        if let Some(size) = self.tile_size {
+            tile_row = tile_row
+                .push(slider(MIN_TILE_SIZE..=2048, size, Message::TileSizeChanged).step(64u32).width(Length::Fixed(200.0)))
+                .push(text(format!("{}×{} px", size, size)).size(14).style(colors.text_secondary));
+        }
+
+        let mut segment_row = row![
+            checkbox("Split into segments", self.video_segment_duration.is_some())
+                .on_toggle(Message::ToggleVideoSegments)
+                .size(16)
+                .text_size(14),
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center);
+        
+        if let Some(secs) = self.video_segment_duration {
+            let minutes = (secs / 60.0).round() as u32;
+            segment_row = segment_row
+                .push(slider(1..=60, minutes, Message::VideoSegmentMinutesChanged).width(Length::Fixed(200.0)))
+                .push(text(format!("{} min per segment", minutes)).size(14).style(colors.text_secondary));
+        }
+        
+        let mut quality_row = row![
+            checkbox("Target bitrate", self.video_options.target_bitrate_kbps.is_some())
+                .on_toggle(Message::ToggleVideoBitrateMode)
+                .size(16)
+                .text_size(14),
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center);
+        
+        let quality_hint = if let Some(kbps) = self.video_options.target_bitrate_kbps {
+            quality_row = quality_row
+                .push(slider(500..=50_000, kbps, Message::VideoBitrateChanged).step(500u32).width(Length::Fixed(200.0)))
+                .push(text(format!("{:.1} Mbps", kbps as f32 / 1000.0)).size(14).style(colors.text_secondary));
+            "Output size ≈ bitrate × duration"
+        } else {
+            quality_row = quality_row
+                .push(slider(0..=MAX_VIDEO_CRF, self.video_options.crf, Message::VideoCrfChanged).width(Length::Fixed(200.0)))
+                .push(text(format!("CRF {}", self.video_options.crf)).size(14).style(colors.text_secondary));
+            "Lower CRF = higher quality + larger file"
+        };
+        
+        let video_card = card_container(
+            column![
+                section_title("Video Settings", colors),
+                Space::with_height(8),
+                segment_row,
+                Space::with_height(8),
+                checkbox("Deinterlace (yadif, double frame rate)", self.deinterlace_video)
+                    .on_toggle(Message::ToggleDeinterlace)
+                    .size(16)
+                    .text_size(14),
+                Space::with_height(8),
+                checkbox("Keep subtitle tracks (saves as .mkv)", self.video_options.preserve_subtitles)
+                    .on_toggle(Message::TogglePreserveSubtitles)
+                    .size(16)
+                    .text_size(14),
+                Space::with_height(8),
+                row![
+                    text("Codec").size(14),
+                    pick_list(
+                        self.available_video_codecs.clone(),
+                        self.video_options.video_codec,
+                        Message::VideoCodecSelected,
+                    )
+                    .placeholder(if self.available_video_codecs.is_empty() { "Auto (detecting...)" } else { "Auto" }),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                Space::with_height(8),
+                quality_row,
+                Space::with_height(4),
+                text(quality_hint).size(12).style(colors.text_secondary),
+                Space::with_height(8),
+                row![
+                    text("Frames").size(14),
+                    text_input("first", &self.video_options.start_frame.map(|f| f.to_string()).unwrap_or_default())
+                        .on_input(Message::VideoStartFrameChanged)
+                        .width(Length::Fixed(80.0)),
+                    text("to").size(14).style(colors.text_secondary),
+                    text_input("last", &self.video_options.end_frame.map(|f| f.to_string()).unwrap_or_default())
+                        .on_input(Message::VideoEndFrameChanged)
+                        .width(Length::Fixed(80.0)),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+            ]
+            .push_maybe(self.video_checkpoint.filter(|_| !self.processing).map(|(done, total)| {
+                column![
+                    Space::with_height(8),
+                    button(text(format!("Resume ({}/{} frames)", done, total)).size(14))
+                        .on_press(Message::ResumeVideo)
+                        .padding([6, 10])
+                        .style(theme::Button::Secondary),
+                ]
+            }))
+            .spacing(0)
+        );
+
+        let mut download_limit_row = row![
+            checkbox("Limit model download speed", self.download_speed_limit_kbps.is_some())
+                .on_toggle(Message::ToggleDownloadSpeedLimit)
+                .size(16)
+                .text_size(14),
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center);
+        
+        if let Some(limit) = self.download_speed_limit_kbps {
+            download_limit_row = download_limit_row
+                .push(slider(128..=10240, limit, Message::DownloadSpeedLimitChanged).step(128u32).width(Length::Fixed(200.0)))
+                .push(text(format!("{} KB/s", limit)).size(14).style(colors.text_secondary));
+        }
+
+        // OpenVINO is left out when the loaded ONNX Runtime lacks it
+        #[cfg(feature = "openvino")]
+        let ep_options: Vec<ExecutionProvider> = ExecutionProvider::all().into_iter()
+            .filter(|&ep| ep != ExecutionProvider::OpenVINO || self.openvino_available)
+            .collect();
+        #[cfg(not(feature = "openvino"))]
+        let ep_options = ExecutionProvider::all();
+        
+        let ep_row = row![
+            text("Execution provider").size(14),
+            pick_list(ep_options, Some(self.ort_config.ep), Message::ExecutionProviderSelected),
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center);
+        
+        // Only builds with OpenVINO support can have a runtime that fails to load it
+        #[cfg(feature = "openvino")]
+        let ep_row = ep_row
+            .push_maybe((self.ort_config.ep == ExecutionProvider::OpenVINO).then(|| pick_list(
+                OPENVINO_DEVICES.map(String::from).to_vec(),
+                Some(self.ort_config.openvino_device.clone()),
+                Message::OpenVinoDeviceSelected,
+            )))
+            .push_maybe((!self.openvino_available).then(|| {
+                text("OpenVINO not available").size(12).style(colors.text_secondary)
+            }));
+
+        let advanced_card = card_container(
+            column![
+                row![
+                    section_title("Advanced Settings", colors),
+                    Space::with_width(Length::Fill),
+                    button(text("Reset to defaults").size(14))
+                        .on_press_maybe((!self.processing).then_some(Message::ResetSettings))
+                        .padding([4, 12])
+                        .style(theme::Button::Secondary),
+                ]
+                .align_items(Alignment::Center),
+                Space::with_height(8),
+                ep_row,
+                Space::with_height(8),
+                gpu_throttle_row,
+                Space::with_height(8),
+                download_limit_row,
+                Space::with_height(8),
+                checkbox("Offline mode (no model downloads or cloud transfers)", self.config.offline_mode)
+                    .on_toggle(Message::ToggleOfflineMode)
+                    .size(16)
+                    .text_size(14),
+                Space::with_height(8),
+                checkbox("Crop to content (trim scanned page margins)", self.crop_to_content)
+                    .on_toggle(Message::ToggleCropToContent)
+                    .size(16)
+                    .text_size(14),
+                Space::with_height(8),
+                tile_row,
+                Space::with_height(8),
+                checkbox("Optimize PNG output (lossless, oxipng)", self.optimize_png)
+                    .on_toggle(Message::ToggleOptimizePng)
+                    .size(16)
+                    .text_size(14),
+                Space::with_height(8),
+                row![
+                    text("EXIF metadata").size(14),
+                    pick_list(ExifMode::all(), Some(self.process_options.exif_mode), Message::ExifModeSelected),
+                    text("Color profile").size(14),
+                    pick_list(ColorProfileMode::all(), Some(self.process_options.color_profile_mode), Message::ColorProfileModeSelected),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                Space::with_height(8),
+                checkbox("Open after processing", self.auto_open_output)
+                    .on_toggle(Message::ToggleAutoOpenOutput)
+                    .size(16)
+                    .text_size(14),
+                Space::with_height(8),
+                checkbox("Process small images on CPU alongside the GPU", self.split_gpu_cpu)
+                    .on_toggle(Message::ToggleSplitGpuCpu)
+                    .size(16)
+                    .text_size(14),
+                Space::with_height(8),
+                // The split GPU/CPU workers run one image per call, so the slider would have no effect
+                if self.split_gpu_cpu {
+                    row![
+                        text("Images per inference call").size(14),
+                        text("1 while small images run on the CPU").size(14).style(colors.text_secondary),
+                    ]
+                } else {
+                    row![
+                        text("Images per inference call").size(14),
+                        slider(1..=16, self.process_options.batch_size as u32, Message::BatchSizeChanged).width(Length::Fixed(200.0)),
+                        text(self.process_options.batch_size.to_string()).size(14).style(colors.text_secondary),
+                    ]
+                }
+                .spacing(10)
+                .align_items(Alignment::Center),
+                Space::with_height(8),
+                checkbox("Stop batch on first error", self.fail_fast)
+                    .on_toggle(Message::ToggleFailFast)
+                    .size(16)
+                    .text_size(14),
+            ].spacing(0)
+        );
+
+        let zoom_controls = row![
+            button(text("-").size(18).horizontal_alignment(iced::alignment::Horizontal::Center))
+                .on_press(Message::ZoomOut)
+                .padding([4, 12])
+                .style(theme::Button::Secondary),
+            text(format!("{:.0}%", self.zoom_level * 100.0))
+                .size(14)
+                .style(colors.text_secondary),
+            button(text("+").size(18).horizontal_alignment(iced::alignment::Horizontal::Center))
+                .on_press(Message::ZoomIn)
+                .padding([4, 12])
+                .style(theme::Button::Secondary),
+            button(text("Reset").size(14))
+                .on_press(Message::ResetZoom)
+                .padding([4, 12])
+                .style(theme::Button::Text),
+        ]
+        .spacing(8)
+        .align_items(Alignment::Center)
+        .width(Length::FillPortion(1));
+
+        let preview_card = if let Some(before_img) = &self.before_image {
             let (w, h) = before_img.dimensions();
             let display_w = (w as f32 * self.zoom_level) as u32;
             let display_h = (h as f32 * self.zoom_level) as u32;
@@ -1076,711 +4014,5640 @@ impl Application for App {
                 before_rgba.into_raw()
             );
 
-            let before_preview = scrollable(
-                container(
-                    iced_image::Image::new(before_handle.clone())
-                        .width(Length::Fixed(display_w as f32))
-                        .height(Length::Fixed(display_h as f32))
-                )
-                .center_x()
-                .center_y()
-            )
-            .direction(Direction::Both {
-                vertical: Properties::default(),
-                horizontal: Properties::default(),
-            })
-            .width(Length::FillPortion(1))
-            .height(Length::Fixed(400.0));
+            let before_preview = scrollable(
+                container(
+                    iced_image::Image::new(before_handle.clone())
+                        .width(Length::Fixed(display_w as f32))
+                        .height(Length::Fixed(display_h as f32))
+                )
+                .center_x()
+                .center_y()
+            )
+            .id(scrollable::Id::new(BEFORE_PREVIEW_SCROLL_ID))
+            .on_scroll(|viewport| Message::ScrollMoved(viewport.relative_offset()))
+            .direction(Direction::Both {
+                vertical: Properties::default(),
+                horizontal: Properties::default(),
+            })
+            .width(Length::FillPortion(1))
+            .height(Length::Fixed(400.0));
+
+            let before_col = column![
+                text("Before").size(16).font(HEADING_FONT).style(colors.text),
+                Space::with_height(8),
+                before_preview,
+                Space::with_height(8),
+                text(format!("{}×{}", w, h)).size(12).style(colors.text_secondary)
+            ]
+            .spacing(0)
+            .align_items(Alignment::Center);
+
+            let flicker_controls = if self.flicker_mode {
+                row![
+                    slider(0.5..=4.0, self.flicker_hz, Message::FlickerRateChanged)
+                        .step(0.5)
+                        .width(Length::Fixed(120.0)),
+                    text(format!("{:.1} Hz", self.flicker_hz)).size(14).style(colors.text_secondary),
+                    button(text("Stop").size(14))
+                        .on_press(Message::StopFlickerMode)
+                        .padding([4, 12])
+                        .style(theme::Button::Secondary),
+                ]
+            } else {
+                row![
+                    checkbox("Change heatmap", self.show_change_heatmap)
+                        .on_toggle(Message::ToggleChangeHeatmap)
+                        .size(16)
+                        .text_size(14),
+                    button(text("Diff").size(14))
+                        .on_press_maybe(self.after_image.as_ref().map(|_| Message::ToggleDiffView))
+                        .padding([4, 12])
+                        .style(if self.show_diff_view { theme::Button::Primary } else { theme::Button::Secondary }),
+                    button(text("Flicker").size(14))
+                        .on_press_maybe(self.after_image.as_ref().map(|_| Message::StartFlickerMode))
+                        .padding([4, 12])
+                        .style(theme::Button::Secondary),
+                    button(text("Split").size(14))
+                        .on_press_maybe(self.after_image.as_ref().map(|_| Message::ToggleComparisonMode))
+                        .padding([4, 12])
+                        .style(if self.comparison_mode == ComparisonMode::SplitLine { theme::Button::Primary } else { theme::Button::Secondary }),
+                    button(text("Save Comparison").size(14))
+                        .on_press_maybe((self.before_image.is_some() && self.after_image.is_some())
+                            .then_some(Message::ExportComparison))
+                        .padding([4, 12])
+                        .style(theme::Button::Secondary),
+                ]
+                .push_maybe(self.ssim_score.filter(|_| self.show_diff_view).map(|score| {
+                    text(format!("SSIM: {:.4}", score)).size(12).style(colors.text_secondary)
+                }))
+            }
+            .spacing(8)
+            .align_items(Alignment::Center);
+
+            let after_col = if let Some(after_img) = &self.after_image {
+                let (w, h) = after_img.dimensions();
+                let display_w = (w as f32 * self.zoom_level) as u32;
+                let display_h = (h as f32 * self.zoom_level) as u32;
+
+                // The SSIM map is at the before image's size, the preview stretches it to the after size
+                let shown = match (&self.ssim_map, &self.change_heatmap) {
+                    (Some(ssim_map), _) if self.show_diff_view => ssim_map,
+                    (_, Some(heatmap)) if self.show_change_heatmap => heatmap,
+                    _ => after_img,
+                };
+                let after_handle = iced_image::Handle::from_pixels(
+                    shown.width(),
+                    shown.height(),
+                    shown.to_rgba8().into_raw()
+                );
+
+                let after_preview = scrollable(
+                    container(
+                        iced_image::Image::new(after_handle)
+                            .width(Length::Fixed(display_w as f32))
+                            .height(Length::Fixed(display_h as f32))
+                    )
+                    .center_x()
+                    .center_y()
+                )
+                .id(scrollable::Id::new(AFTER_PREVIEW_SCROLL_ID))
+                .on_scroll(|viewport| Message::ScrollMoved(viewport.relative_offset()))
+                .direction(Direction::Both {
+                    vertical: Properties::default(),
+                    horizontal: Properties::default(),
+                })
+                .width(Length::FillPortion(1))
+                .height(Length::Fixed(400.0));
+
+                let swatches = self.after_palette.iter().fold(row![].spacing(2), |swatches, &[r, g, b]| {
+                    swatches.push(
+                        container(Space::new(Length::Fixed(16.0), Length::Fixed(16.0)))
+                            .style(theme::Container::Custom(Box::new(SwatchContainer(Color::from_rgb8(r, g, b)))))
+                    )
+                });
+
+                column![
+                    text("After").size(16).font(HEADING_FONT).style(colors.text),
+                    Space::with_height(8),
+                    after_preview,
+                    Space::with_height(8),
+                    text(format!("{}×{}", w, h)).size(12).style(colors.text_secondary),
+                    Space::with_height(8),
+                    row![
+                        swatches,
+                        button(text("Copy hex").size(12))
+                            .on_press_maybe((!self.after_palette.is_empty()).then_some(Message::CopyPalette))
+                            .padding([2, 8])
+                            .style(theme::Button::Text),
+                    ]
+                    .spacing(8)
+                    .align_items(Alignment::Center),
+                ]
+                .spacing(0)
+                .align_items(Alignment::Center)
+            } else {
+                column![
+                    text("After").size(16).font(HEADING_FONT).style(colors.text),
+                    Space::with_height(8),
+                    container(text("Process to see result").style(colors.text_secondary))
+                        .width(Length::Fixed(500.0))
+                        .height(Length::Fixed(400.0))
+                        .center_x()
+                        .center_y()
+                ]
+                .spacing(0)
+                .align_items(Alignment::Center)
+                .width(Length::FillPortion(1))
+            };
+
+            // Flicker mode shows both images at the after size in one place, so differences blink in and out
+            let comparison: Element<'_, Message> = match (&self.after_image, self.flicker_mode, &self.split_image) {
+                (Some(after_img), true, _) => {
+                    let (aw, ah) = after_img.dimensions();
+                    let (label, handle) = if self.flicker_show_after {
+                        ("After", iced_image::Handle::from_pixels(aw, ah, after_img.to_rgba8().into_raw()))
+                    } else {
+                        ("Before", before_handle)
+                    };
+                    
+                    column![
+                        text(label).size(16).font(HEADING_FONT).style(colors.text),
+                        Space::with_height(8),
+                        scrollable(
+                            container(
+                                iced_image::Image::new(handle)
+                                    .width(Length::Fixed(aw as f32 * self.zoom_level))
+                                    .height(Length::Fixed(ah as f32 * self.zoom_level))
+                            )
+                            .center_x()
+                            .center_y()
+                        )
+                        .direction(Direction::Both {
+                            vertical: Properties::default(),
+                            horizontal: Properties::default(),
+                        })
+                        .width(Length::Fill)
+                        .height(Length::Fixed(400.0)),
+                    ]
+                    .spacing(0)
+                    .align_items(Alignment::Center)
+                    .into()
+                }
+                (Some(after_img), false, Some(split_image)) if self.comparison_mode == ComparisonMode::SplitLine => {
+                    let (aw, ah) = after_img.dimensions();
+                    
+                    column![
+                        row![
+                            text("Before").size(16).font(HEADING_FONT).style(colors.text),
+                            Space::with_width(Length::Fill),
+                            text("After").size(16).font(HEADING_FONT).style(colors.text),
+                        ],
+                        Space::with_height(8),
+                        scrollable(
+                            container(
+                                mouse_area(
+                                    iced_image::Image::new(split_image.clone())
+                                        .width(Length::Fixed(aw as f32 * self.zoom_level))
+                                        .height(Length::Fixed(ah as f32 * self.zoom_level))
+                                )
+                                .on_move(|position| Message::SplitCursorMoved(position.x))
+                                .on_press(Message::SplitDragStarted)
+                                .on_release(Message::SplitDragEnded)
+                                .on_exit(Message::SplitDragEnded)
+                                .interaction(iced::mouse::Interaction::ResizingHorizontally)
+                            )
+                            .center_x()
+                            .center_y()
+                        )
+                        .direction(Direction::Both {
+                            vertical: Properties::default(),
+                            horizontal: Properties::default(),
+                        })
+                        .width(Length::Fill)
+                        .height(Length::Fixed(400.0)),
+                    ]
+                    .spacing(0)
+                    .align_items(Alignment::Center)
+                    .into()
+                }
+                _ => row![before_col, Space::with_width(20), after_col]
+                    .align_items(Alignment::Start)
+                    .into(),
+            };
+
+            card_container(
+                column![
+                    row![
+                        section_title("Preview", colors),
+                        Space::with_width(Length::Fill),
+                        flicker_controls,
+                        button(text("Copy Data URI").size(14))
+                            .on_press_maybe(self.after_image.as_ref().map(|_| Message::CopyDataUri))
+                            .padding([4, 12])
+                            .style(theme::Button::Secondary),
+                        zoom_controls,
+                    ]
+                    .spacing(8)
+                    .align_items(Alignment::Center),
+                    Space::with_height(16),
+                    mouse_area(comparison)
+                        .on_enter(Message::PreviewHovered(true))
+                        .on_exit(Message::PreviewHovered(false)),
+                ].spacing(0)
+            )
+        } else {
+            card_container(
+                column![
+                    section_title("Preview", colors),
+                    Space::with_height(16),
+                    text("Select an image to preview").size(14).style(colors.text_secondary)
+                ].spacing(0)
+            )
+        };
+
+        let mut cards = column![
+            input_card,
+            settings_card,
+        ].spacing(16);
+        
+        if let Some(form) = &self.custom_model_form {
+            cards = cards.push(custom_model_card(form, &self.available_models, colors));
+        }
+        
+        if let Some(inspection) = &self.model_inspection {
+            cards = cards.push(inspector_card(inspection, self.inspector_collapsed, colors));
+        }
+        
+        if self.input_type == InputType::Video {
+            cards = cards.push(video_card);
+        }
+        
+        // iced has no stacking widget, so the drop target takes the preview's place while dragging
+        let preview_card = if self.drag_hover {
+            card_container(
+                container(text("Drop image here").size(20).font(HEADING_FONT).style(PRIMARY_COLOR))
+                    .width(Length::Fill)
+                    .height(Length::Fixed(400.0))
+                    .center_x()
+                    .center_y()
+                    .style(theme::Container::Custom(Box::new(DropTargetContainer)))
+            )
+        } else {
+            preview_card
+        };
+        
+        cards = cards.push(advanced_card).push(preview_card);
+        
+        if !self.process_results.is_empty() {
+            let (sort_column, sort_order) = self.results_sort;
+            let mut visible: Vec<&ProcessResult> = self.process_results.iter()
+                .filter(|r| !self.results_rated_only || r.rating.is_some())
+                .collect();
+            
+            match sort_order {
+                SortOrder::Ascending => visible.sort_by(|a, b| a.compare_by(b, sort_column)),
+                SortOrder::Descending => visible.sort_by(|a, b| b.compare_by(a, sort_column)),
+                SortOrder::None => {}
+            }
+            
+            let sorted_by = match sort_order {
+                SortOrder::Ascending => format!("{} asc", sort_column.label()),
+                SortOrder::Descending => format!("{} desc", sort_column.label()),
+                SortOrder::None => "none".to_string(),
+            };
+            
+            let bytes_saved: u64 = self.process_results.iter().map(|r| r.bytes_saved).sum();
+            let saved_note = if bytes_saved > 0 {
+                format!(", PNG optimization saved {}", format_file_size(bytes_saved))
+            } else {
+                String::new()
+            };
+            
+            let summary = text(format!("Showing {} results, filtered: {}, sorted by: {}{}",
+                self.process_results.len(), visible.len(), sorted_by, saved_note))
+                .size(12)
+                .style(colors.text_secondary);
+            
+            let results_list = visible.into_iter()
+                .fold(column![].spacing(6), |list, result| list.push(result_row(result, colors)));
+            
+            cards = cards.push(card_container(
+                column![
+                    row![
+                        section_title("Results", colors),
+                        Space::with_width(Length::Fill),
+                        button(text("Export 4+ stars").size(14))
+                            .on_press(Message::ExportStarred)
+                            .padding([4, 12])
+                            .style(theme::Button::Secondary),
+                        button(text("Delete unrated").size(14))
+                            .on_press(Message::DeleteUnrated)
+                            .padding([4, 12])
+                            .style(theme::Button::Destructive),
+                    ]
+                    .spacing(8)
+                    .align_items(Alignment::Center),
+                    Space::with_height(8),
+                    row![
+                        summary,
+                        Space::with_width(Length::Fill),
+                    ]
+                    .push_maybe(self.last_psnr.map(|psnr| {
+                        text(if psnr.is_finite() { format!("PSNR: {:.1} dB", psnr) } else { "PSNR: ∞ dB".to_string() })
+                            .size(12)
+                            .style(colors.text_secondary)
+                    }))
+                    .push(
+                        checkbox("Rated only", self.results_rated_only)
+                            .on_toggle(Message::ToggleRatedOnly)
+                            .size(14)
+                            .text_size(12),
+                    )
+                    .spacing(12)
+                    .align_items(Alignment::Center),
+                    Space::with_height(8),
+                    results_header(self.results_sort, colors),
+                    Space::with_height(4),
+                    scrollable(results_list).height(Length::Fixed(300.0)),
+                ].spacing(0)
+            ));
+        }
+        
+        cards = cards.push(Space::with_height(20));
+
+        let content = scrollable(
+            column![
+                header,
+                container(cards)
+                .width(Length::Fill)
+                .center_x()
+                .padding([6, 14, 6, 6])
+            ].spacing(0)
+        );
+
+        let errors = self.log_entries.iter().filter(|(level, _)| *level == LogLevel::Error).count();
+        let mut log_panel = column![
+            row![
+                section_title(&format!("Log ({} entries, {} errors)", self.log_entries.len(), errors), colors),
+                Space::with_width(Length::Fill),
+                button(text(if self.log_collapsed { "Show" } else { "Hide" }).size(14))
+                    .on_press(Message::ToggleLogPanel)
+                    .padding([4, 12])
+                    .style(theme::Button::Text),
+            ]
+            .align_items(Alignment::Center),
+        ];
+        if !self.log_collapsed {
+            let entries = self.log_entries.iter().fold(column![].spacing(2), |entries, (level, message)| {
+                entries.push(text(message).size(12).font(iced::Font::MONOSPACE).style(level.color(colors)))
+            });
+            log_panel = log_panel.push(
+                scrollable(entries)
+                    .id(scrollable::Id::new(LOG_SCROLL_ID))
+                    .width(Length::Fill)
+                    .height(Length::Fixed(200.0))
+            );
+        }
+
+        container(
+            column![
+                container(content).height(Length::Fill),
+                container(log_panel.spacing(6))
+                    .width(Length::Fill)
+                    .padding([8, 20])
+                    .style(theme::Container::Custom(Box::new(CardContainer))),
+            ]
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .style(theme::Container::Custom(Box::new(BackgroundContainer)))
+        .into()
+    }
+
+    fn theme(&self) -> Theme {
+        if self.dark_mode { Theme::Dark } else { Theme::Light }
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        let tick = if self.processing {
+            iced::time::every(Duration::from_secs(1)).map(|_| Message::Tick)
+        } else {
+            Subscription::none()
+        };
+        
+        let flicker = if self.flicker_mode {
+            iced::time::every(Duration::from_secs_f32(1.0 / self.flicker_hz)).map(|_| Message::FlickerTick)
+        } else {
+            Subscription::none()
+        };
+        
+        let preview_zoom = if self.preview_hovered && self.before_image.is_some() {
+            iced::event::listen_with(preview_zoom_event)
+        } else {
+            Subscription::none()
+        };
+        
+        // Only the first call finds the receiver; iced keeps that subscription running by its id
+        let log_receiver = self.log_receiver.lock().unwrap().take();
+        let logs = iced::subscription::unfold(LOG_SCROLL_ID, log_receiver, |receiver| async move {
+            let Some(mut receiver) = receiver else {
+                return std::future::pending().await;
+            };
+            match receiver.recv().await {
+                Some((level, message)) => (Message::LogEntry(level, message), Some(receiver)),
+                None => std::future::pending().await,
+            }
+        });
+        
+        // Time::every can't carry the shared state, so this sleeps and reads it itself
+        let progress = if self.processing && self.batch_progress.is_some() {
+            iced::subscription::unfold("batch_progress", self.progress.clone(), |progress| async move {
+                tokio::time::sleep(PROGRESS_POLL_INTERVAL).await;
+                let message = {
+                    let state = progress.lock().unwrap();
+                    Message::ProgressUpdate {
+                        current: state.current,
+                        total: state.total,
+                        processed: state.processed,
+                        avg_duration: state.average(),
+                    }
+                };
+                (message, progress)
+            })
+        } else {
+            Subscription::none()
+        };
+        
+        let watch = match self.watched_folder() {
+            Some(folder) => iced::subscription::unfold(
+                ("watch_folder", folder.clone()),
+                FolderWatch::Starting(folder.clone()),
+                next_watched_files,
+            ),
+            None => Subscription::none(),
+        };
+        
+        Subscription::batch([tick, flicker, preview_zoom, logs, progress, watch, iced::event::listen_with(file_drop_event)])
+    }
+}
+
+// Lives in the watch subscription's state, so the watcher stops when the subscription is dropped
+enum FolderWatch {
+    Starting(PathBuf),
+    Watching(notify::RecommendedWatcher, tokio::sync::mpsc::UnboundedReceiver<(PathBuf, bool)>), // (path, created)
+    Failed,
+}
+
+fn start_folder_watcher(folder: &Path) -> notify::Result<(notify::RecommendedWatcher, tokio::sync::mpsc::UnboundedReceiver<(PathBuf, bool)>)> {
+    use notify::{EventKind, Watcher};
+    
+    let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let Ok(event) = event else {
+            return;
+        };
+        // Writes only extend the quiet period, only creations queue a file
+        let created = matches!(event.kind, EventKind::Create(_));
+        if created || matches!(event.kind, EventKind::Modify(_)) {
+            for path in event.paths.into_iter().filter(|p| has_image_extension(p)) {
+                let _ = sender.send((path, created));
+            }
+        }
+    })?;
+    watcher.watch(folder, notify::RecursiveMode::NonRecursive)?;
+    log_message(&format!("Watching {} for new images", folder.display()));
+    Ok((watcher, receiver))
+}
+
+// Waits for the first new image, then until the folder has been quiet for WATCH_DEBOUNCE
+async fn next_watched_files(state: FolderWatch) -> (Message, FolderWatch) {
+    let (watcher, mut receiver) = match state {
+        FolderWatch::Starting(folder) => match start_folder_watcher(&folder) {
+            Ok(started) => started,
+            Err(e) => return (Message::WatchFailed(e.to_string()), FolderWatch::Failed),
+        },
+        FolderWatch::Watching(watcher, receiver) => (watcher, receiver),
+        FolderWatch::Failed => return std::future::pending().await,
+    };
+    
+    let mut created = BTreeSet::new();
+    loop {
+        let next = if created.is_empty() {
+            receiver.recv().await
+        } else {
+            match tokio::time::timeout(WATCH_DEBOUNCE, receiver.recv()).await {
+                Ok(next) => next,
+                Err(_) => break,
+            }
+        };
+        match next {
+            Some((path, true)) => {
+                created.insert(path);
+            }
+            Some((_, false)) => {}
+            None => return std::future::pending().await,
+        }
+    }
+    
+    (Message::WatchedFilesArrived(created.into_iter().collect()), FolderWatch::Watching(watcher, receiver))
+}
+
+fn file_drop_event(event: iced::Event, _status: iced::event::Status) -> Option<Message> {
+    use iced::window;
+    
+    match event {
+        iced::Event::Window(_, window::Event::FileHovered(_)) => Some(Message::FileHovered),
+        iced::Event::Window(_, window::Event::FileDropped(path)) => Some(Message::FileDropped(path)),
+        iced::Event::Window(_, window::Event::FilesHoveredLeft) => Some(Message::FileDragLeft),
+        _ => None,
+    }
+}
+
+// Wheel zooms even when a scrollable also used it, keys only when no text input took them
+fn preview_zoom_event(event: iced::Event, status: iced::event::Status) -> Option<Message> {
+    use iced::keyboard::{self, Key};
+    use iced::mouse::{self, ScrollDelta};
+    
+    match event {
+        iced::Event::Mouse(mouse::Event::WheelScrolled { delta }) => {
+            let lines = match delta {
+                ScrollDelta::Lines { y, .. } => y,
+                ScrollDelta::Pixels { y, .. } => y / SCROLL_PIXELS_PER_LINE,
+            };
+            Some(Message::ScrollZoom(lines))
+        }
+        iced::Event::Keyboard(keyboard::Event::KeyPressed { key: Key::Character(c), .. })
+            if status == iced::event::Status::Ignored =>
+        {
+            match c.as_str() {
+                "+" | "=" => Some(Message::ZoomIn),
+                "-" => Some(Message::ZoomOut),
+                "0" => Some(Message::ResetZoom),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+impl App {
+    fn start_video(&mut self, resume: bool) -> Command<Message> {
+        if self.processing || self.input_path.is_none() {
+            return Command::none();
+        }
+        
+        let Some(model) = self.selected_model.clone().map(|m| self.with_norm_override(m)) else {
+            self.status_message = "No model selected".to_string();
+            return Command::none();
+        };
+        
+        let Some(video_path) = self.input_path.clone() else {
+            return Command::none();
+        };
+        
+        let options = VideoProcessOptions {
+            segment_duration: self.video_segment_duration,
+            deinterlace: self.deinterlace_video,
+            resume,
+            ..self.video_options.clone()
+        };
+        if let (Some(start), Some(end)) = (options.start_frame, options.end_frame) {
+            if end < start {
+                self.status_message = format!("End frame {} is before start frame {}", end, start);
+                return Command::none();
+            }
+        }
+        
+        self.processing = true;
+        self.status_message = "Processing video...".to_string();
+        
+        Command::perform(
+            process_video(
+                video_path,
+                model,
+                options,
+                self.ort_config.clone(),
+                self.model_cache.clone(),
+                self.download_speed_limit_kbps,
+            ),
+            Message::VideoProcessComplete,
+        )
+    }
+
+    // The image in flight when Pause was pressed still finishes, so the count can move once
+    fn paused_status(&self) -> String {
+        let done = self.batch_progress.map_or(0, |(current, _, _)| current);
+        format!("Paused ({}/{} images)", done, self.image_files.len())
+    }
+    
+    fn colors(&self) -> ColorScheme {
+        if self.dark_mode { DARK_COLORS } else { LIGHT_COLORS }
+    }
+    
+    // Runs `files` with the current model and settings; Process passes every loaded image
+    fn start_processing(&mut self, files: Vec<PathBuf>) -> Command<Message> {
+        if self.processing || self.download_progress.is_some() || files.is_empty() {
+            return Command::none();
+        }
+        
+        let Some(model) = self.selected_model.clone().map(|m| self.with_norm_override(m)) else {
+            self.status_message = "No model selected".to_string();
+            return Command::none();
+        };
+        
+        // Fetch the model first so its progress shows in the settings card
+        if model.url != "local" && !self.model_cache.is_cached(&model) {
+            self.process_after_download = Some(files);
+            return self.start_download(&model);
+        }
+        
+        // Same condition as the toggle in the settings card, which is hidden without a cached classifier
+        let auto_select = if self.auto_select_model && self.cached_classifier().is_some() {
+            match self.resolve_auto_select() {
+                Ok(auto_select) => Some(auto_select),
+                Err(e) => {
+                    self.status_message = format!("Error: {}", e);
+                    return Command::none();
+                }
+            }
+        } else {
+            None
+        };
+        
+        self.processing = true;
+        self.status_message = "Processing...".to_string();
+        self.gpu_temp.store(0, Ordering::Relaxed);
+        self.batch_started = Some(std::time::Instant::now());
+        self.images_processed = 0;
+        self.completion_times.clear();
+        self.throughput_history.clear();
+        self.disk_space = Arc::new(DiskSpaceGate::default());
+        self.disk_space_low = None;
+        let cancel_token = Arc::new(AtomicBool::new(false));
+        self.cancel_token = Some(cancel_token.clone());
+        self.pause_flag = Arc::new(AtomicBool::new(false));
+        self.progress = Arc::new(Mutex::new(ProgressState { total: files.len(), ..ProgressState::default() }));
+        self.batch_progress = Some((0, files.len(), Duration::ZERO));
+        
+        let options = self.current_process_options();
+        
+        Command::perform(
+            process_images(
+                files,
+                model,
+                options,
+                self.ort_config.clone(),
+                self.model_cache.clone(),
+                self.download_speed_limit_kbps,
+                auto_select,
+                self.gpu_temp_limit,
+                self.gpu_temp.clone(),
+                self.disk_space.clone(),
+                cancel_token,
+                self.pause_flag.clone(),
+                self.progress.clone(),
+            ),
+            |result| match result {
+                Err(BatchError { checksum_failed: Some(model_name), .. }) => Message::ChecksumFailed(model_name),
+                result => Message::ProcessComplete(result),
+            },
+        )
+    }
+    
+    // The loaded folder, while watching it
+    fn watched_folder(&self) -> Option<&PathBuf> {
+        self.watching.as_ref()
+            .filter(|folder| self.input_type == InputType::Folder && self.input_path.as_ref() == Some(*folder))
+    }
+    
+    // Starts a batch for files that arrived in the watched folder, once nothing else is running
+    fn process_watch_queue(&mut self) -> Command<Message> {
+        if self.watched_folder().is_none() || self.processing || self.watch_queue.is_empty() {
+            return Command::none();
+        }
+        // The queue is only emptied once its files are running or waiting on the model download
+        let download_pending = self.process_after_download.is_some();
+        let command = self.start_processing(self.watch_queue.clone());
+        if self.processing || (!download_pending && self.process_after_download.is_some()) {
+            self.watch_queue.clear();
+        }
+        command
+    }
+    
+    // Watch runs only cover the new files, so they add to the list instead of replacing it
+    fn merge_process_results(&mut self, results: &[ProcessResult]) {
+        if self.watched_folder().is_some() {
+            self.process_results.retain(|r| !results.iter().any(|new| new.input_path == r.input_path));
+            self.process_results.extend(results.iter().cloned());
+        } else {
+            self.process_results = results.to_vec();
+        }
+    }
+    
+    // The options a Process click would run with right now
+    fn current_process_options(&self) -> ProcessOptions {
+        ProcessOptions {
+            output_dir: self.output_dir(),
+            output_format: self.output_format.clone(),
+            tile_size: self.tile_size,
+            crop_to_content: self.crop_to_content,
+            optimize_png: self.optimize_png,
+            fail_fast: self.fail_fast,
+            split_gpu_cpu: self.split_gpu_cpu,
+            input_root: match (&self.input_type, &self.input_path) {
+                (InputType::Folder, Some(path)) => Some(path.clone()),
+                (InputType::Zip, Some(path)) => Some(zip_temp_dir(path)),
+                _ => None,
+            },
+            pipeline: (self.pipeline.len() > 1).then(|| ProcessPipeline(self.pipeline.clone())),
+            ..self.process_options.clone()
+        }
+    }
+    
+    // Inverse of current_process_options, the output directory stays with the current input
+    fn apply_preset(&mut self, preset: &Preset) {
+        self.selected_category = Some(preset.category.clone());
+        self.selected_model = self.available_models.iter()
+            .find(|m| m.name == preset.model_name)
+            .cloned();
+        self.norm_override = None;
+        
+        let options = &preset.process_options;
+        self.output_format = options.output_format.clone();
+        self.tile_size = options.tile_size;
+        if options.tile_size.is_some() {
+            self.last_tile_size = options.tile_size;
+        }
+        self.crop_to_content = options.crop_to_content;
+        self.optimize_png = options.optimize_png;
+        self.fail_fast = options.fail_fast;
+        self.split_gpu_cpu = options.split_gpu_cpu;
+        self.pipeline = options.pipeline.as_ref().map(|p| p.0.clone()).unwrap_or_default();
+        self.process_options = ProcessOptions {
+            output_dir: self.process_options.output_dir.clone(),
+            ..options.clone()
+        };
+    }
+    
+    fn save_presets(&mut self) {
+        self.config.presets = self.presets.clone();
+        if let Err(e) = self.config.save() {
+            log_error(&format!("Failed to save config: {}", e));
+        }
+    }
+    
+    // Moves the path to the front of the history and saves it
+    fn push_recent(&mut self, path: PathBuf, input_kind: InputType) {
+        self.recent.retain(|entry| entry.path != path);
+        self.recent.push_front(RecentEntry { path, input_kind, timestamp: Local::now() });
+        self.recent.truncate(MAX_RECENT_ENTRIES);
+        
+        self.config.recent = self.recent.clone();
+        if let Err(e) = self.config.save() {
+            log_error(&format!("Failed to save config: {}", e));
+        }
+    }
+    
+    // Hashes for a new file list; results for any earlier list are dropped on arrival
+    fn compute_hashes(&mut self, files: Vec<PathBuf>) -> Command<Message> {
+        self.hash_generation += 1;
+        let generation = self.hash_generation;
+        Command::perform(compute_image_hashes(files), move |hashes| Message::HashesComputed(generation, hashes))
+    }
+    
+    // Queues thumbnails for the first `up_to` files that haven't been requested yet
+    fn request_thumbnails(&mut self, up_to: usize) -> Command<Message> {
+        let pending: Vec<PathBuf> = self.image_files.iter()
+            .take(up_to)
+            .filter(|path| !self.thumbnails_requested.contains(*path))
+            .cloned()
+            .collect();
+        self.thumbnails_requested.extend(pending.iter().cloned());
+        Command::batch(pending.into_iter().map(|path| Command::perform(load_thumbnail(path), Message::ThumbnailLoaded)))
+    }
+    
+    // Picks up frames left by an interrupted run of the loaded video with the current settings
+    fn refresh_video_checkpoint(&mut self) {
+        self.video_checkpoint = None;
+        let (Some(video_path), Some(model)) = (&self.input_path, &self.selected_model) else {
+            return;
+        };
+        if self.input_type != InputType::Video {
+            return;
+        }
+        let options = VideoProcessOptions {
+            deinterlace: self.deinterlace_video,
+            ..self.video_options.clone()
+        };
+        self.video_checkpoint = VideoCheckpoint::load(Path::new(VIDEO_TEMP_UPSCALED_DIR))
+            .filter(|checkpoint| checkpoint.matches(video_path, model, &options))
+            .map(|checkpoint| (checkpoint.completed.len(), checkpoint.total_frames));
+    }
+    
+    fn start_download(&mut self, model: &ModelInfo) -> Command<Message> {
+        let (progress_tx, progress_rx) = iced::futures::channel::mpsc::channel(DOWNLOAD_PROGRESS_BUFFER);
+        self.download_progress = Some((model.name.clone(), 0, None));
+        self.status_message = format!("Downloading {}...", model.name);
+        
+        let url = model.url.clone();
+        let path = self.model_cache.model_path(model);
+        let checksum = model.checksum.clone();
+        let speed_limit_kbps = self.download_speed_limit_kbps;
+        let progress_name = model.name.clone();
+        let name = model.name.clone();
+        
+        Command::batch([
+            Command::run(progress_rx, move |progress: DownloadProgress| {
+                Message::DownloadProgress(progress_name.clone(), progress.bytes_received, progress.total_bytes)
+            }),
+            Command::perform(
+                async move {
+                    download_model_async(&url, &path, checksum.as_deref(), speed_limit_kbps, progress_tx).await
+                        .map_err(|e| e.to_string())
+                },
+                move |result| Message::DownloadComplete(name, result),
+            ),
+        ])
+    }
+    
+    fn cached_classifier(&self) -> Option<&ModelInfo> {
+        self.available_models.iter()
+            .find(|m| m.model_type == ModelType::Classification && self.model_cache.is_cached(m))
+    }
+    
+    fn resolve_auto_select(&self) -> Result<AutoSelect, String> {
+        let classifier = self.cached_classifier()
+            .cloned()
+            .ok_or("No classifier model in the model cache for auto-select")?;
+        
+        // The normalization override was picked for the selected model, other routes keep their own
+        let is_selected = |model: &ModelInfo| self.selected_model.as_ref().is_some_and(|selected| selected.name == model.name);
+        let routes = ImageClass::ALL.iter()
+            .map(|&class| {
+                let name = self.classifier_routing.model_for(class);
+                self.available_models.iter()
+                    .find(|m| m.name == name)
+                    .map(|m| (class, if is_selected(m) { self.with_norm_override(m.clone()) } else { m.clone() }))
+                    .ok_or(format!("Model '{}' routed for {} images not found", name, class))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        
+        Ok(AutoSelect { classifier, routes })
+    }
+}
+
+fn section_title(title: &str, colors: ColorScheme) -> Element<'static, Message> {
+    text(title)
+        .size(14)
+        .font(HEADING_FONT)
+        .style(colors.text)
+        .into()
+}
+
+const RESULT_COLUMNS: [(SortColumn, &str, u16); 7] = [
+    (SortColumn::Filename, "Filename", 3),
+    (SortColumn::InputSize, "Input", 2),
+    (SortColumn::OutputSize, "Output", 2),
+    (SortColumn::Scale, "Scale", 1),
+    (SortColumn::Duration, "Time", 1),
+    (SortColumn::FileSize, "Size in/out", 2),
+    (SortColumn::Rating, "Rating", 2),
+];
+
+fn results_header(current: (SortColumn, SortOrder), colors: ColorScheme) -> Element<'static, Message> {
+    RESULT_COLUMNS.iter().fold(row![].spacing(8), |header, &(column, label, portion)| {
+        let (order, arrow) = if current.0 == column {
+            let arrow = match current.1 {
+                SortOrder::Ascending => " ▲",
+                SortOrder::Descending => " ▼",
+                SortOrder::None => "",
+            };
+            (current.1.next(), arrow)
+        } else {
+            (SortOrder::Ascending, "")
+        };
+        
+        header.push(
+            button(text(format!("{}{}", label, arrow)).size(12).font(HEADING_FONT).style(colors.text))
+                .on_press(Message::SortResults(column, order))
+                .padding(0)
+                .style(theme::Button::Text)
+                .width(Length::FillPortion(portion))
+        )
+    })
+    .into()
+}
+
+// Numbers sort before text, matching where ASCII digits fall relative to letters
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum NaturalSortToken {
+    Number(u64),
+    Text(String),
+}
+
+// Splits "frame_10.png" into ["frame_", 10, ".png"] so embedded numbers compare by value
+fn natural_sort_key(s: &str) -> Vec<NaturalSortToken> {
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().peekable();
+    
+    while let Some(&c) = chars.peek() {
+        if c.is_ascii_digit() {
+            let mut digits = String::new();
+            while let Some(&d) = chars.peek().filter(|d| d.is_ascii_digit()) {
+                digits.push(d);
+                chars.next();
+            }
+            // Absurdly long digit runs don't fit in u64, compare those as text
+            tokens.push(match digits.parse() {
+                Ok(n) => NaturalSortToken::Number(n),
+                Err(_) => NaturalSortToken::Text(digits),
+            });
+        } else {
+            let mut text = String::new();
+            while let Some(&t) = chars.peek().filter(|t| !t.is_ascii_digit()) {
+                text.extend(t.to_lowercase());
+                chars.next();
+            }
+            tokens.push(NaturalSortToken::Text(text));
+        }
+    }
+    tokens
+}
+
+fn sort_naturally(paths: &mut [PathBuf]) {
+    paths.sort_by_cached_key(|p| natural_sort_key(&p.to_string_lossy()));
+}
+
+fn has_image_extension(path: &Path) -> bool {
+    let extensions = ["jpg", "jpeg", "png", "bmp", "webp"];
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| extensions.contains(&ext.to_lowercase().as_str()))
+}
+
+// Images in `folder` and all of its subfolders except `skip`, the output folder of earlier runs
+fn list_folder_images_recursive(folder: &Path, skip: Option<&Path>) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = walkdir::WalkDir::new(folder)
+        .into_iter()
+        .filter_entry(|entry| Some(entry.path()) != skip)
+        .flatten()
+        .filter(|entry| entry.file_type().is_file() && has_image_extension(entry.path()))
+        .map(|entry| entry.into_path())
+        .collect();
+    
+    sort_naturally(&mut files);
+    files
+}
+
+// Images directly inside `folder`, in natural order
+fn list_folder_images(folder: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    
+    if let Ok(entries) = std::fs::read_dir(folder) {
+        for entry in entries.flatten() {
+            let entry_path = entry.path();
+            if entry_path.is_file() && has_image_extension(&entry_path) {
+                files.push(entry_path);
+            }
+        }
+    }
+    
+    sort_naturally(&mut files);
+    files
+}
+
+// Keeps the end of the path, which is the part that tells output folders apart
+fn truncate_path_label(path: &Path, max_chars: usize) -> String {
+    let label = path.display().to_string();
+    let len = label.chars().count();
+    if len <= max_chars {
+        return label;
+    }
+    let tail: String = label.chars().skip(len - max_chars.saturating_sub(1)).collect();
+    format!("…{}", tail)
+}
+
+fn format_file_size(bytes: u64) -> String {
+    if bytes >= 1024 * 1024 {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    } else {
+        format!("{} KB", bytes.div_ceil(1024))
+    }
+}
+
+fn result_row(result: &ProcessResult, colors: ColorScheme) -> Element<'_, Message> {
+    let filename = result.output_path.file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("output");
+    let cell = |value: String, portion: u16| {
+        text(value).size(12).style(colors.text_secondary).width(Length::FillPortion(portion))
+    };
+    
+    let stars = (1..=5u8).fold(row![].spacing(2), |stars, n| {
+        let filled = result.rating.is_some_and(|r| n <= r);
+        stars.push(
+            button(text(if filled { "★" } else { "☆" })
+                .size(14)
+                .style(if filled { PRIMARY_COLOR } else { colors.text_secondary }))
+                .on_press(Message::RateResult { output_path: result.output_path.clone(), rating: n })
+                .padding([0, 2])
+                .style(theme::Button::Text)
+        )
+    });
+    
+    row![
+        text(filename).size(12).style(colors.text).width(Length::FillPortion(3)),
+        cell(format!("{}×{}", result.input_dims.0, result.input_dims.1), 2),
+        cell(format!("{}×{}", result.output_dims.0, result.output_dims.1), 2),
+        cell(format!("{:.1}x", result.scale()), 1),
+        cell(match result.status {
+            ProcessResultStatus::Processed => format!("{:.2}s", result.duration),
+            ProcessResultStatus::Skipped => "Skipped".to_string(),
+        }, 1),
+        cell(format!("{} / {}", format_file_size(result.input_bytes), format_file_size(result.output_bytes)), 2),
+        container(stars).width(Length::FillPortion(2)),
+    ]
+    .spacing(8)
+    .align_items(Alignment::Center)
+    .into()
+}
+
+fn custom_model_card<'a>(form: &CustomModelForm, existing: &[ModelInfo], colors: ColorScheme) -> Element<'a, Message> {
+    let label = |s: &str| text(s.to_string()).size(14).style(colors.text_secondary).width(Length::Fixed(80.0));
+    let problems = form.problems(existing);
+    
+    let name_form = form.clone();
+    let scale_form = form.clone();
+    let window_form = form.clone();
+    let type_form = form.clone();
+    let format_form = form.clone();
+    let channels_form = form.clone();
+    
+    let mut content = column![
+        section_title("Add Custom Model", colors),
+        Space::with_height(4),
+        text(form.source_path.display().to_string()).size(12).style(colors.text_secondary),
+        Space::with_height(8),
+        row![
+            label("Name:"),
+            text_input("Model name", &form.name)
+                .on_input(move |name| Message::CustomModelFormChanged(CustomModelForm { name, ..name_form.clone() })),
+        ].spacing(10).align_items(Alignment::Center),
+        Space::with_height(8),
+        row![
+            label("Category:"),
+            pick_list(
+                vec![ModelType::Upscaling, ModelType::Denoising, ModelType::Deblur, ModelType::Enhancement],
+                Some(form.model_type.clone()),
+                move |model_type| Message::CustomModelFormChanged(CustomModelForm { model_type, ..type_form.clone() }),
+            ),
+        ].spacing(10).align_items(Alignment::Center),
+        Space::with_height(8),
+        row![
+            label("Scale:"),
+            text_input("4", &form.scale)
+                .on_input(move |scale| Message::CustomModelFormChanged(CustomModelForm { scale, ..scale_form.clone() }))
+                .width(Length::Fixed(60.0)),
+            text("Window size:").size(14).style(colors.text_secondary),
+            text_input("1", &form.window_size)
+                .on_input(move |window_size| Message::CustomModelFormChanged(CustomModelForm { window_size, ..window_form.clone() }))
+                .width(Length::Fixed(60.0)),
+        ].spacing(10).align_items(Alignment::Center),
+        Space::with_height(8),
+        row![
+            label("Layout:"),
+            pick_list(
+                vec![TensorFormat::NCHW, TensorFormat::NHWC],
+                Some(form.tensor_format.clone()),
+                move |tensor_format| Message::CustomModelFormChanged(CustomModelForm { tensor_format, ..format_form.clone() }),
+            ),
+        ].spacing(10).align_items(Alignment::Center),
+        Space::with_height(8),
+        row![
+            label("Normalize:"),
+            radio("[0,1]", NormalizationRange::ZeroOne, Some(form.norm), |norm| Message::CustomModelFormChanged(CustomModelForm { norm, ..form.clone() })).size(16).text_size(14),
+            radio("[-1,1]", NormalizationRange::MinusOneOne, Some(form.norm), |norm| Message::CustomModelFormChanged(CustomModelForm { norm, ..form.clone() })).size(16).text_size(14),
+        ].spacing(10).align_items(Alignment::Center),
+        Space::with_height(8),
+        checkbox("Grayscale model, run on R, G and B separately", form.channel_independent)
+            .on_toggle(move |channel_independent| Message::CustomModelFormChanged(CustomModelForm { channel_independent, ..channels_form.clone() }))
+            .size(16)
+            .text_size(14),
+    ]
+    .spacing(0);
+    
+    for problem in &problems {
+        content = content.push(Space::with_height(4));
+        content = content.push(text(format!("⚠ {}", problem)).size(12).style(Color::from_rgb(0.8, 0.5, 0.1)));
+    }
+    
+    content = content.push(Space::with_height(12));
+    content = content.push(
+        row![
+            button(text("Save").size(14))
+                .on_press_maybe(problems.is_empty().then_some(Message::SaveCustomModel))
+                .padding([4, 12]),
+            button(text("Cancel").size(14))
+                .on_press(Message::CancelCustomModel)
+                .padding([4, 12])
+                .style(theme::Button::Secondary),
+        ]
+        .spacing(10)
+    );
+    
+    card_container(content)
+}
+
+fn inspector_card(inspection: &ModelInspection, collapsed: bool, colors: ColorScheme) -> Element<'_, Message> {
+    let mut content = column![
+        row![
+            section_title(&format!("Model Inspector: {}", inspection.model.name), colors),
+            Space::with_width(Length::Fill),
+            button(text(if collapsed { "Expand" } else { "Collapse" }).size(14))
+                .on_press(Message::ToggleInspectorCollapsed)
+                .padding([4, 12])
+                .style(theme::Button::Secondary),
+            button(text("Close").size(14))
+                .on_press(Message::CloseInspector)
+                .padding([4, 12])
+                .style(theme::Button::Secondary),
+        ]
+        .spacing(8)
+        .align_items(Alignment::Center),
+    ]
+    .spacing(0);
+    
+    if collapsed {
+        return card_container(content);
+    }
+    
+    for (heading, specs) in [("Inputs", &inspection.inputs), ("Outputs", &inspection.outputs)] {
+        content = content.push(Space::with_height(8));
+        content = content.push(text(heading).size(14).font(HEADING_FONT).style(colors.text));
+        for spec in specs {
+            content = content.push(
+                row![
+                    text(&spec.name).size(12).width(Length::FillPortion(2)),
+                    text(&spec.element_type).size(12).style(colors.text_secondary).width(Length::FillPortion(1)),
+                    text(spec.shape_label()).size(12).style(colors.text_secondary).width(Length::FillPortion(2)),
+                ]
+                .spacing(10)
+            );
+        }
+    }
+    
+    content = content.push(Space::with_height(8));
+    content = content.push(text("? = dynamic dimension").size(12).style(colors.text_secondary));
+    for warning in &inspection.warnings {
+        content = content.push(Space::with_height(4));
+        content = content.push(text(format!("⚠ {}", warning)).size(12).style(Color::from_rgb(0.8, 0.5, 0.1)));
+    }
+    
+    card_container(content)
+}
+
+fn card_container<'a>(content: impl Into<Element<'a, Message>>) -> Element<'a, Message> {
+    container(content)
+        .width(Length::Fill)
+        .padding(14)
+        .style(theme::Container::Custom(Box::new(CardContainer)))
+        .into()
+}
+
+struct BackgroundContainer;
+impl container::StyleSheet for BackgroundContainer {
+    type Style = Theme;
+    
+    fn appearance(&self, style: &Self::Style) -> container::Appearance {
+        container::Appearance {
+            background: Some(Background::Color(ColorScheme::of(style).background)),
+            ..Default::default()
+        }
+    }
+}
+
+struct CardContainer;
+impl container::StyleSheet for CardContainer {
+    type Style = Theme;
+    
+    fn appearance(&self, style: &Self::Style) -> container::Appearance {
+        container::Appearance {
+            background: Some(Background::Color(ColorScheme::of(style).card)),
+            border: iced::Border {
+                color: Color::from_rgba(0.0, 0.0, 0.0, 0.08),
+                width: 1.0,
+                radius: 12.0.into(),
+            },
+            ..Default::default()
+        }
+    }
+}
+
+// Images/minute over the last THROUGHPUT_WINDOW, newest sample on the right
+struct ThroughputChart {
+    samples: Vec<(f32, f32)>, // (seconds ago, images/minute)
+}
+
+impl canvas::Program<Message> for ThroughputChart {
+    type State = ();
+    
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &iced::Renderer,
+        theme: &Theme,
+        bounds: iced::Rectangle,
+        _cursor: iced::mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        let (w, h) = (bounds.width, bounds.height);
+        
+        frame.fill_rectangle(iced::Point::ORIGIN, bounds.size(), ColorScheme::of(theme).background);
+        
+        // Headroom above the peak so the line never touches the top edge
+        let peak = self.samples.iter().map(|&(_, ipm)| ipm).fold(1.0f32, f32::max) * 1.2;
+        let window = THROUGHPUT_WINDOW.as_secs_f32();
+        let point = |(ago, ipm): (f32, f32)| iced::Point::new(w * (1.0 - ago / window), h * (1.0 - ipm / peak));
+        
+        let line = canvas::Path::new(|builder| {
+            let mut samples = self.samples.iter().copied();
+            if let Some(first) = samples.next() {
+                builder.move_to(point(first));
+                for sample in samples {
+                    builder.line_to(point(sample));
+                }
+            }
+        });
+        frame.stroke(&line, canvas::Stroke::default().with_color(PRIMARY_COLOR).with_width(2.0));
+        
+        vec![frame.into_geometry()]
+    }
+}
+
+struct BadgeContainer(Color);
+impl container::StyleSheet for BadgeContainer {
+    type Style = Theme;
+    
+    fn appearance(&self, _style: &Self::Style) -> container::Appearance {
+        container::Appearance {
+            background: Some(Background::Color(self.0)),
+            border: iced::Border {
+                radius: 4.0.into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+}
+
+struct SwatchContainer(Color);
+impl container::StyleSheet for SwatchContainer {
+    type Style = Theme;
+    
+    fn appearance(&self, _style: &Self::Style) -> container::Appearance {
+        container::Appearance {
+            background: Some(Background::Color(self.0)),
+            border: iced::Border {
+                color: Color::from_rgba(0.0, 0.0, 0.0, 0.15),
+                width: 1.0,
+                radius: 2.0.into(),
+            },
+            ..Default::default()
+        }
+    }
+}
+
+// Thumbnail tile, outlined in the primary color when it's the previewed file
+struct DropTargetContainer;
+impl container::StyleSheet for DropTargetContainer {
+    type Style = Theme;
+    
+    fn appearance(&self, _style: &Self::Style) -> container::Appearance {
+        container::Appearance {
+            background: Some(Background::Color(Color { a: 0.15, ..PRIMARY_COLOR })),
+            border: iced::Border {
+                color: PRIMARY_COLOR,
+                width: 2.0,
+                radius: 8.0.into(),
+            },
+            ..Default::default()
+        }
+    }
+}
+
+struct ThumbnailContainer(bool);
+impl container::StyleSheet for ThumbnailContainer {
+    type Style = Theme;
+    
+    fn appearance(&self, style: &Self::Style) -> container::Appearance {
+        let (color, width) = if self.0 {
+            (PRIMARY_COLOR, 3.0)
+        } else {
+            (Color::from_rgba(0.0, 0.0, 0.0, 0.15), 1.0)
+        };
+        container::Appearance {
+            background: Some(Background::Color(ColorScheme::of(style).background)),
+            border: iced::Border {
+                color,
+                width,
+                radius: 4.0.into(),
+            },
+            ..Default::default()
+        }
+    }
+}
+
+struct GradientContainer;
+impl container::StyleSheet for GradientContainer {
+    type Style = Theme;
+    
+    fn appearance(&self, _style: &Self::Style) -> container::Appearance {
+        container::Appearance {
+            background: Some(Background::Color(PRIMARY_COLOR)),
+            ..Default::default()
+        }
+    }
+}
+
+// Add this logging function at the top level
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+enum LogLevel {
+    #[default]
+    Info,
+    Warn, // log_message lines flagged with ⚠
+    Error,
+}
+
+impl LogLevel {
+    fn color(&self, colors: ColorScheme) -> Color {
+        match self {
+            LogLevel::Info => colors.text_secondary,
+            LogLevel::Warn => Color::from_rgb(0.85, 0.55, 0.05),
+            LogLevel::Error => Color::from_rgb(0.85, 0.2, 0.2),
+        }
+    }
+}
+
+fn send_to_log_panel(level: LogLevel, entry: &str) {
+    if let Some(sender) = LOG_SENDER.get() {
+        let _ = sender.send((level, entry.trim().to_string()));
+    }
+}
+
+fn log_message(message: &str) {
+    let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S");
+    let log_entry = format!("[{}] {}\n", timestamp, message);
+    
+    // Print to console
+    println!("{}", log_entry.trim());
+    let level = if message.trim_start().starts_with('⚠') { LogLevel::Warn } else { LogLevel::Info };
+    send_to_log_panel(level, &log_entry);
+    
+    // Write to log file
+    if let Ok(mut file) = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open("image_processor.log")
+    {
+        let _ = file.write_all(log_entry.as_bytes());
+    }
+}
+
+fn log_error(message: &str) {
+    let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S");
+    let log_entry = format!("[{}] ERROR: {}\n", timestamp, message);
+    
+    eprintln!("{}", log_entry.trim());
+    send_to_log_panel(LogLevel::Error, &log_entry);
+    
+    if let Ok(mut file) = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open("image_processor.log")
+    {
+        let _ = file.write_all(log_entry.as_bytes());
+    }
+}
+
+// Name of the graph output at output_index, falling back to the first output when the
+// index is out of range so a misconfigured model still produces something to look at
+fn output_name(session: &Session, output_index: usize) -> String {
+    match session.outputs.get(output_index) {
+        Some(output) => output.name.to_string(),
+        None => {
+            let names: Vec<&str> = session.outputs.iter().map(|o| o.name.as_str()).collect();
+            log_message(&format!("⚠ Output index {} is out of range, using output 0. Available outputs: {:?}", output_index, names));
+            session.outputs[0].name.to_string()
+        }
+    }
+}
+
+fn run_inference(session: &mut Session, input_tensor: Array4<f32>, model: &ModelInfo) -> Result<Array4<f32>> {
+    log_message("Creating ONNX input value...");
+    let input_value = match model.tensor_dtype {
+        TensorDtype::F32 => Value::from_array(input_tensor).map(|value| value.into_dyn()),
+        TensorDtype::F16 => Value::from_array(input_tensor.mapv(half::f16::from_f32)).map(|value| value.into_dyn()),
+    }.map_err(|e| {
+        log_error(&format!("Failed to create input value: {}", e));
+        e
+    })?;
+    
+    let input_name = session.inputs[0].name.to_string();
+    let output_name = output_name(session, model.output_index);
+    log_message(&format!("Model input: '{}', output: '{}'", input_name, output_name));
+
+    log_message("Running inference...");
+    let outputs = session.run(ort::inputs![input_name.as_str() => input_value]).map_err(|e| {
+        log_error(&format!("Inference failed: {}", e));
+        e
+    })?;
+
+    log_message("Extracting output tensor...");
+    let output = &outputs[output_name.as_str()];
+    let (shape_vec, output_data) = match model.tensor_dtype {
+        TensorDtype::F32 => output.try_extract_tensor::<f32>()
+            .map(|(shape, data)| (shape.as_ref().to_vec(), data.to_vec())),
+        TensorDtype::F16 => output.try_extract_tensor::<half::f16>()
+            .map(|(shape, data)| (shape.as_ref().to_vec(), data.iter().map(|v| v.to_f32()).collect())),
+    }.map_err(|e| {
+        log_error(&format!("Failed to extract tensor: {}", e));
+        e
+    })?;
+    
+    log_message(&format!("Output tensor shape: {:?}", shape_vec));
+    
+    let output_array = Array4::from_shape_vec(
+        (shape_vec[0] as usize, shape_vec[1] as usize, 
+         shape_vec[2] as usize, shape_vec[3] as usize),
+        output_data
+    ).map_err(|e| {
+        log_error(&format!("Failed to create output array: {}", e));
+        e
+    })?;
+
+    Ok(output_array)
+}
+
+// Splits the input along the channel axis, runs each channel through a 1-channel model
+// and stacks the outputs back into a 3-channel tensor
+fn run_channel_independent(session: &mut Session, input_tensor: Array4<f32>, model: &ModelInfo) -> Result<Array4<f32>> {
+    let channel_axis = match model.tensor_format {
+        TensorFormat::NCHW => Axis(1),
+        TensorFormat::NHWC => Axis(3),
+    };
+    
+    let mut channel_outputs = Vec::new();
+    for (channel, name) in ["R", "G", "B"].iter().enumerate() {
+        let channel_start = std::time::Instant::now();
+        let channel_input = input_tensor
+            .slice_axis(channel_axis, Slice::from(channel..channel + 1))
+            .to_owned();
+        
+        channel_outputs.push(run_inference(session, channel_input, model)?);
+        log_message(&format!("Channel {} processed in {:.2}s", name, channel_start.elapsed().as_secs_f32()));
+    }
+    
+    let views: Vec<_> = channel_outputs.iter().map(|output| output.view()).collect();
+    Ok(ndarray::concatenate(channel_axis, &views)?)
+}
+
+// SRCNN-style luma-only inference: the model sees the Y channel (BT.601), Cb and Cr are
+// upscaled bicubically to the model's output size and recombined
+fn run_ycbcr(session: &mut Session, input_tensor: Array4<f32>, model: &ModelInfo) -> Result<Array4<f32>> {
+    let to_unit = |norm: &NormalizationRange, v: f32| match norm {
+        NormalizationRange::ZeroOne => v,
+        NormalizationRange::MinusOneOne => (v + 1.0) / 2.0,
+    };
+    let from_unit = |norm: &NormalizationRange, v: f32| match norm {
+        NormalizationRange::ZeroOne => v,
+        NormalizationRange::MinusOneOne => v * 2.0 - 1.0,
+    };
+    let (to_nchw, from_nchw) = match model.tensor_format {
+        TensorFormat::NCHW => ([0, 1, 2, 3], [0, 1, 2, 3]),
+        TensorFormat::NHWC => ([0, 3, 1, 2], [0, 2, 3, 1]),
+    };
+    
+    let input = input_tensor.permuted_axes(to_nchw);
+    let (h, w) = (input.shape()[2], input.shape()[3]);
+    
+    let mut luma = Array4::<f32>::zeros((1, 1, h, w));
+    let mut chroma = image::Rgb32FImage::new(w as u32, h as u32);
+    for y in 0..h {
+        for x in 0..w {
+            let [r, g, b] = [0, 1, 2].map(|c| to_unit(&model.input_norm, input[[0, c, y, x]]));
+            let luma_value = 0.299 * r + 0.587 * g + 0.114 * b;
+            luma[[0, 0, y, x]] = from_unit(&model.input_norm, luma_value);
+            chroma.put_pixel(x as u32, y as u32, Rgb([(b - luma_value) * 0.564 + 0.5, (r - luma_value) * 0.713 + 0.5, 0.0]));
+        }
+    }
+    
+    let luma_input = luma.permuted_axes(from_nchw).as_standard_layout().to_owned();
+    let luma_output = run_inference(session, luma_input, model)?.permuted_axes(to_nchw);
+    let (out_h, out_w) = (luma_output.shape()[2], luma_output.shape()[3]);
+    
+    let chroma = DynamicImage::ImageRgb32F(chroma)
+        .resize_exact(out_w as u32, out_h as u32, image::imageops::FilterType::CatmullRom)
+        .to_rgb32f();
+    
+    let mut output = Array4::<f32>::zeros((1, 3, out_h, out_w));
+    for y in 0..out_h {
+        for x in 0..out_w {
+            let luma_value = to_unit(&model.output_norm, luma_output[[0, 0, y, x]]);
+            let Rgb([cb, cr, _]) = *chroma.get_pixel(x as u32, y as u32);
+            let (cb, cr) = (cb - 0.5, cr - 0.5);
+            let rgb = [
+                luma_value + 1.403 * cr,
+                luma_value - 0.344 * cb - 0.714 * cr,
+                luma_value + 1.773 * cb,
+            ];
+            for (c, value) in rgb.into_iter().enumerate() {
+                output[[0, c, y, x]] = from_unit(&model.output_norm, value);
+            }
+        }
+    }
+    
+    Ok(output.permuted_axes(from_nchw).as_standard_layout().to_owned())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExecutionDevice {
+    Gpu,
+    Cpu,
+}
+
+impl std::fmt::Display for ExecutionDevice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExecutionDevice::Gpu => write!(f, "GPU"),
+            ExecutionDevice::Cpu => write!(f, "CPU"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum ExecutionProvider {
+    Auto, // Every accelerator this build and platform can have, ORT keeps the first that loads
+    Cpu,
+    DirectML,
+    // Always declared so a config saved by a build with more features still loads, see compiled_or_auto
+    TensorRT,
+    Cuda,
+    CoreML,
+    OpenVINO, // Intel GPUs and NPUs, device picked by OrtConfig::openvino_device
+}
+
+impl Default for ExecutionProvider {
+    // On macOS the Neural Engine is 10-20x faster than the CPU fallback
+    #[cfg(all(target_os = "macos", feature = "coreml"))]
+    fn default() -> Self {
+        ExecutionProvider::CoreML
+    }
+    
+    #[cfg(not(all(target_os = "macos", feature = "coreml")))]
+    fn default() -> Self {
+        ExecutionProvider::Auto
+    }
+}
+
+impl ExecutionProvider {
+    fn all() -> Vec<Self> {
+        Vec::from([
+            ExecutionProvider::Auto,
+            ExecutionProvider::Cpu,
+            ExecutionProvider::DirectML,
+            #[cfg(feature = "tensorrt")]
+            ExecutionProvider::TensorRT,
+            #[cfg(feature = "cuda")]
+            ExecutionProvider::Cuda,
+            #[cfg(feature = "coreml")]
+            ExecutionProvider::CoreML,
+            #[cfg(feature = "openvino")]
+            ExecutionProvider::OpenVINO,
+        ])
+    }
+    
+    fn is_compiled(self) -> bool {
+        match self {
+            ExecutionProvider::Auto | ExecutionProvider::Cpu | ExecutionProvider::DirectML => true,
+            ExecutionProvider::TensorRT => cfg!(feature = "tensorrt"),
+            ExecutionProvider::Cuda => cfg!(feature = "cuda"),
+            ExecutionProvider::CoreML => cfg!(feature = "coreml"),
+            ExecutionProvider::OpenVINO => cfg!(feature = "openvino"),
+        }
+    }
+    
+    // A saved preference this build can't honour runs on Auto; the config keeps it for builds that can
+    fn compiled_or_auto(self) -> Self {
+        if self.is_compiled() {
+            self
+        } else {
+            log_error(&format!("{} is not supported by this build, using Auto", self));
+            ExecutionProvider::Auto
+        }
+    }
+    
+    // What Auto tries, fastest first; the CPU is always the last resort
+    fn auto_priority() -> Vec<Self> {
+        Vec::from([
+            #[cfg(feature = "tensorrt")]
+            ExecutionProvider::TensorRT,
+            #[cfg(feature = "cuda")]
+            ExecutionProvider::Cuda,
+            #[cfg(all(target_os = "macos", feature = "coreml"))]
+            ExecutionProvider::CoreML,
+            #[cfg(windows)]
+            ExecutionProvider::DirectML,
+            #[cfg(feature = "openvino")]
+            ExecutionProvider::OpenVINO,
+        ])
+    }
+}
+
+impl std::fmt::Display for ExecutionProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExecutionProvider::Auto => write!(f, "Auto"),
+            ExecutionProvider::Cpu => write!(f, "CPU"),
+            ExecutionProvider::DirectML => write!(f, "DirectML"),
+            ExecutionProvider::TensorRT => write!(f, "TensorRT"),
+            ExecutionProvider::Cuda => write!(f, "CUDA"),
+            ExecutionProvider::CoreML => write!(f, "CoreML"),
+            ExecutionProvider::OpenVINO => write!(f, "OpenVINO"),
+        }
+    }
+}
+
+// How sessions are built for ExecutionDevice::Gpu
+#[derive(Debug, Clone)]
+struct OrtConfig {
+    ep: ExecutionProvider,
+    device_id: i32,
+    opt_level: u8, // 0 = disabled ... 3 = all optimizations
+    #[cfg(feature = "openvino")]
+    openvino_device: String, // One of OPENVINO_DEVICES
+}
+
+impl Default for OrtConfig {
+    fn default() -> Self {
+        Self {
+            ep: ExecutionProvider::default(),
+            device_id: 0,
+            opt_level: 3,
+            #[cfg(feature = "openvino")]
+            openvino_device: OPENVINO_DEVICES[0].to_string(),
+        }
+    }
+}
+
+// OpenVINO device_type values, the first is the default
+const OPENVINO_DEVICES: [&str; 3] = ["GPU", "CPU", "NPU"];
+
+// One Identity node, float [1, 1, 1, 1] "x" -> "y"; just enough graph to prove a provider runs
+const PROBE_MODEL: &[u8] = include_bytes!("../assets/probe_identity.onnx");
+
+// First provider of the Auto priority list that can load and run a graph, CPU if none can
+fn probe_execution_providers() -> ExecutionProvider {
+    let ort_config = OrtConfig::default();
+    for ep in ExecutionProvider::auto_priority() {
+        match probe_execution_provider(&ort_config, ep) {
+            Ok(()) => {
+                log_message(&format!("Execution provider available: {}", ep));
+                return ep;
+            }
+            Err(e) => log_message(&format!("Execution provider {} unavailable: {}", ep, e)),
+        }
+    }
+    ExecutionProvider::Cpu
+}
+
+fn probe_execution_provider(ort_config: &OrtConfig, ep: ExecutionProvider) -> Result<()> {
+    let mut session = Session::builder()?
+        .with_execution_providers([ort_config.dispatch(ep).error_on_failure()])?
+        .commit_from_memory(PROBE_MODEL)?;
+    let input = Value::from_array(Array4::<f32>::zeros((1, 1, 1, 1)))?;
+    session.run(ort::inputs!["x" => input])?;
+    Ok(())
+}
+
+async fn detect_execution_provider() -> ExecutionProvider {
+    tokio::task::spawn_blocking(probe_execution_providers)
+        .await
+        .unwrap_or(ExecutionProvider::Cpu)
+}
+
+// Whether the loaded ONNX Runtime was built with OpenVINO, checked once at startup
+#[cfg(feature = "openvino")]
+fn check_openvino_available() -> bool {
+    use ort::execution_providers::ExecutionProvider as _;
+    
+    match ort::execution_providers::OpenVINOExecutionProvider::default().is_available() {
+        Ok(available) => available,
+        Err(e) => {
+            log_error(&format!("Could not probe for OpenVINO: {}", e));
+            false
+        }
+    }
+}
+
+impl OrtConfig {
+    // In priority order; ORT skips providers that fail to register and ends up on the CPU
+    fn execution_providers(&self, device: ExecutionDevice) -> Vec<ort::execution_providers::ExecutionProviderDispatch> {
+        let cpu = self.dispatch(ExecutionProvider::Cpu);
+        match (device, self.ep) {
+            (ExecutionDevice::Cpu, _) | (_, ExecutionProvider::Cpu) => vec![cpu],
+            (_, ExecutionProvider::Auto) => ExecutionProvider::auto_priority().into_iter()
+                .map(|ep| self.dispatch(ep))
+                .chain([cpu])
+                .collect(),
+            // Fails loudly so create_session can tell the user it fell back to the CPU
+            #[cfg(feature = "coreml")]
+            (_, ExecutionProvider::CoreML) => vec![self.dispatch(ExecutionProvider::CoreML).error_on_failure(), cpu],
+            (_, ep) => vec![self.dispatch(ep), cpu],
+        }
+    }
+    
+    fn dispatch(&self, ep: ExecutionProvider) -> ort::execution_providers::ExecutionProviderDispatch {
+        // Not a glob import, ort's ExecutionProvider trait would shadow the enum
+        use ort::execution_providers::{CPUExecutionProvider, DirectMLExecutionProvider};
+        #[cfg(feature = "tensorrt")]
+        use ort::execution_providers::TensorRTExecutionProvider;
+        #[cfg(feature = "cuda")]
+        use ort::execution_providers::CUDAExecutionProvider;
+        #[cfg(feature = "coreml")]
+        use ort::execution_providers::CoreMLExecutionProvider;
+        #[cfg(feature = "openvino")]
+        use ort::execution_providers::OpenVINOExecutionProvider;
+        
+        // Providers missing from this build fall through to the CPU; compiled_or_auto keeps them out of OrtConfig
+        #[allow(unreachable_patterns)]
+        match ep {
+            ExecutionProvider::Auto | ExecutionProvider::Cpu => CPUExecutionProvider::default().build(),
+            ExecutionProvider::DirectML => DirectMLExecutionProvider::default().with_device_id(self.device_id).build(),
+            #[cfg(feature = "tensorrt")]
+            ExecutionProvider::TensorRT => TensorRTExecutionProvider::default().with_device_id(self.device_id).build(),
+            #[cfg(feature = "cuda")]
+            ExecutionProvider::Cuda => CUDAExecutionProvider::default().with_device_id(self.device_id).build(),
+            #[cfg(feature = "coreml")]
+            ExecutionProvider::CoreML => CoreMLExecutionProvider::default().build(),
+            #[cfg(feature = "openvino")]
+            ExecutionProvider::OpenVINO => OpenVINOExecutionProvider::default().with_device_type(&self.openvino_device).build(),
+            _ => CPUExecutionProvider::default().build(),
+        }
+    }
+    
+    fn optimization_level(&self) -> ort::session::builder::GraphOptimizationLevel {
+        use ort::session::builder::GraphOptimizationLevel;
+        
+        match self.opt_level {
+            0 => GraphOptimizationLevel::Disable,
+            1 => GraphOptimizationLevel::Level1,
+            2 => GraphOptimizationLevel::Level2,
+            _ => GraphOptimizationLevel::Level3,
+        }
+    }
+}
+
+// Per-run settings for process_images and process_single_image, also stored in presets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+struct ProcessOptions {
+    #[serde(skip)]
+    output_dir: PathBuf, // Follows the input, never taken from a preset
+    conflict_mode: ConflictMode,
+    output_format: OutputFormat,
+    tile_size: Option<u32>, // Overrides the model's max_tile_size, None = whole image when possible
+    tile_overlap: u32, // See TILE_OVERLAP
+    retry_count: usize, // Extra attempts for an image that failed, 0 = give up on first error
+    write_sidecar: bool, // <output>.json with the run's metadata next to each output file
+    max_input_dim: u32, // Untiled inputs are downscaled to fit, see prepare_input
+    crop_to_content: bool,
+    optimize_png: bool,
+    fail_fast: bool,
+    split_gpu_cpu: bool,
+    batch_size: usize, // Images stacked into one session.run call, 1 = one call per image
+    filename_template: String, // See render_output_filename for the variables, empty = default naming
+    exif_mode: ExifMode,
+    color_profile_mode: ColorProfileMode,
+    pipeline: Option<ProcessPipeline>, // Two or more models run in sequence instead of the single model
+    #[serde(skip)]
+    input_root: Option<PathBuf>, // Folder input; subfolders below it are mirrored below output_dir
+    #[serde(skip)]
+    claimed_outputs: ClaimedOutputs, // Reset by process_images for every batch
+}
+
+// Output paths already handed out in the current batch, whose files may not exist yet because
+// they're still queued on the OutputWriter or being written by the other split worker.
+// Keyed by output with the input that claimed it, so a retried image gets its own path back.
+type ClaimedOutputs = Arc<Mutex<HashMap<PathBuf, PathBuf>>>;
+
+impl Default for ProcessOptions {
+    fn default() -> Self {
+        Self {
+            output_dir: PathBuf::from("./processed"),
+            conflict_mode: ConflictMode::Skip,
+            output_format: OutputFormat::Png,
+            tile_size: None,
+            tile_overlap: TILE_OVERLAP,
+            retry_count: 0,
+            write_sidecar: false,
+            max_input_dim: DEFAULT_MAX_INPUT_DIM,
+            crop_to_content: false,
+            optimize_png: false,
+            fail_fast: false,
+            split_gpu_cpu: false,
+            batch_size: 1,
+            filename_template: String::new(),
+            exif_mode: ExifMode::Strip,
+            color_profile_mode: ColorProfileMode::Strip,
+            pipeline: None,
+            input_root: None,
+            claimed_outputs: ClaimedOutputs::default(),
+        }
+    }
+}
+
+impl ProcessOptions {
+    // album/2019/a.jpg with input_root album/ writes to <output_dir>/2019/
+    fn output_dir_for(&self, input_path: &Path) -> PathBuf {
+        self.input_root.as_ref()
+            .and_then(|root| input_path.parent()?.strip_prefix(root).ok())
+            .map_or_else(|| self.output_dir.clone(), |relative| self.output_dir.join(relative))
+    }
+}
+
+// Models applied one after another, e.g. a denoiser ahead of an upscaler
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ProcessPipeline(Vec<ModelInfo>);
+
+impl ProcessPipeline {
+    fn step_names(&self) -> Vec<String> {
+        self.0.iter().map(|model| model.name.clone()).collect()
+    }
+}
+
+// What to do when an image's output file already exists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum ConflictMode {
+    Overwrite,
+    Skip, // The default, so re-running a folder never destroys outputs that were edited by hand
+    AutoRename, // photo_4x_1.png, photo_4x_2.png, ... see unique_path
+}
+
+impl ConflictMode {
+    fn all() -> Vec<ConflictMode> {
+        vec![ConflictMode::Skip, ConflictMode::AutoRename, ConflictMode::Overwrite]
+    }
+}
+
+impl std::fmt::Display for ConflictMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConflictMode::Overwrite => write!(f, "Overwrite existing"),
+            ConflictMode::Skip => write!(f, "Skip existing"),
+            ConflictMode::AutoRename => write!(f, "Rename new output"),
+        }
+    }
+}
+
+// How the input's EXIF block is carried over to the output PNG
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum ExifMode {
+    Strip,
+    Preserve,
+    PreserveFiltered, // Only descriptive tags, see DESCRIPTIVE_EXIF_TAGS
+}
+
+impl ExifMode {
+    fn all() -> Vec<ExifMode> {
+        vec![ExifMode::Strip, ExifMode::Preserve, ExifMode::PreserveFiltered]
+    }
+}
+
+impl std::fmt::Display for ExifMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExifMode::Strip => write!(f, "Strip"),
+            ExifMode::Preserve => write!(f, "Preserve all"),
+            ExifMode::PreserveFiltered => write!(f, "Preserve descriptive"),
+        }
+    }
+}
+
+// What happens to an embedded ICC profile (Adobe RGB, ProPhoto RGB, ...)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum ColorProfileMode {
+    Strip,
+    Preserve, // Model sees the untouched values, the output carries the input's profile
+    ConvertToSRGB, // Pixels are converted before inference, the output is plain sRGB
+}
+
+impl ColorProfileMode {
+    fn all() -> Vec<ColorProfileMode> {
+        vec![ColorProfileMode::Strip, ColorProfileMode::Preserve, ColorProfileMode::ConvertToSRGB]
+    }
+}
+
+impl std::fmt::Display for ColorProfileMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ColorProfileMode::Strip => write!(f, "Strip"),
+            ColorProfileMode::Preserve => write!(f, "Preserve"),
+            ColorProfileMode::ConvertToSRGB => write!(f, "Convert to sRGB"),
+        }
+    }
+}
+
+// Runs process again up to retry_count times after a failed first attempt
+fn retry_failed<T>(retry_count: usize, input_path: &Path, first_error: anyhow::Error, mut process: impl FnMut() -> Result<T>) -> Result<T> {
+    let mut error = first_error;
+    for attempt in 1..=retry_count {
+        log_message(&format!("Retrying {} ({}/{}) after: {}", input_path.display(), attempt, retry_count, error));
+        match process() {
+            Ok(result) => return Ok(result),
+            Err(e) => error = e,
+        }
+    }
+    Err(error)
+}
+
+// ORT_EP, ORT_DEVICE_ID and ORT_OPT_LEVEL let container deployments pick the runtime setup
+// The EP settings come from the config file and are used unless ORT_EP overrides them.
+// ep_override takes the same names as ORT_EP and wins over it, e.g. the CLI's --ep.
+fn ort_config_from_env(settings: &Config, ep_override: Option<&str>) -> OrtConfig {
+    let mut config = OrtConfig {
+        ep: settings.preferred_ep.compiled_or_auto(),
+        #[cfg(feature = "openvino")]
+        openvino_device: settings.openvino_device.clone(),
+        ..OrtConfig::default()
+    };
+    let mut from_env = false;
+    
+    let ep_setting = match ep_override {
+        Some(ep) => Some(("--ep", ep.to_string())),
+        None => std::env::var("ORT_EP").ok().map(|ep| ("ORT_EP", ep)),
+    };
+    if let Some((source, ep)) = ep_setting {
+        match ep.to_lowercase().as_str() {
+            "auto" => config.ep = ExecutionProvider::Auto,
+            "directml" => config.ep = ExecutionProvider::DirectML,
+            #[cfg(feature = "tensorrt")]
+            "tensorrt" => config.ep = ExecutionProvider::TensorRT,
+            #[cfg(not(feature = "tensorrt"))]
+            "tensorrt" => log_error(&format!("Ignoring {}=tensorrt, this build has no TensorRT support (tensorrt feature)", source)),
+            #[cfg(feature = "cuda")]
+            "cuda" => config.ep = ExecutionProvider::Cuda,
+            #[cfg(not(feature = "cuda"))]
+            "cuda" => log_error(&format!("Ignoring {}=cuda, this build has no CUDA support (cuda feature)", source)),
+            #[cfg(feature = "coreml")]
+            "coreml" => config.ep = ExecutionProvider::CoreML,
+            #[cfg(not(feature = "coreml"))]
+            "coreml" => log_error(&format!("Ignoring {}=coreml, this build has no CoreML support (coreml feature)", source)),
+            #[cfg(feature = "openvino")]
+            "openvino" => config.ep = ExecutionProvider::OpenVINO,
+            #[cfg(not(feature = "openvino"))]
+            "openvino" => log_error(&format!("Ignoring {}=openvino, this build has no OpenVINO support (openvino feature)", source)),
+            "cpu" => config.ep = ExecutionProvider::Cpu,
+            other => log_error(&format!("Ignoring unknown {} '{}'", source, other)),
+        }
+        from_env = true;
+    }
+    
+    if let Ok(device_id) = std::env::var("ORT_DEVICE_ID") {
+        match device_id.parse() {
+            Ok(id) => config.device_id = id,
+            Err(_) => log_error(&format!("Ignoring invalid ORT_DEVICE_ID '{}'", device_id)),
+        }
+        from_env = true;
+    }
+    
+    if let Ok(level) = std::env::var("ORT_OPT_LEVEL") {
+        match level.parse::<u8>() {
+            Ok(level) if level <= 3 => config.opt_level = level,
+            _ => log_error(&format!("Ignoring invalid ORT_OPT_LEVEL '{}', expected 0-3", level)),
+        }
+        from_env = true;
+    }
+    
+    if from_env {
+        log_message(&format!("ORT configuration overridden: EP={}, device={}, opt_level={}",
+            config.ep, config.device_id, config.opt_level));
+    }
+    
+    config
+}
+
+#[derive(Debug, Clone)]
+struct QuantizeReport {
+    model: ModelInfo, // The new INT8 entry
+    fp32_bytes: u64,
+    int8_bytes: u64,
+    fp32_secs: f32, // Mean CPU inference time on QUANTIZE_BENCHMARK_SIZE input
+    int8_secs: f32,
+}
+
+const QUANTIZE_BENCHMARK_SIZE: u32 = 64;
+const QUANTIZE_BENCHMARK_RUNS: u32 = 3;
+
+// ort has no quantization API, so this shells out to onnxruntime's Python tooling
+// (pip install onnxruntime) the same way video processing relies on ffmpeg
+const QUANTIZE_SCRIPT: &str = "import sys\n\
+from onnxruntime.quantization import quantize_dynamic, QuantType\n\
+quantize_dynamic(sys.argv[1], sys.argv[2], weight_type=QuantType.QInt8)\n";
+
+async fn quantize_model(model: ModelInfo, model_cache: ModelCache) -> Result<QuantizeReport, String> {
+    tokio::task::spawn_blocking(move || {
+        ensure_model_file(&model, &model_cache, None).map_err(|e| e.to_string())?;
+        
+        let fp32_path = model_cache.model_path(&model);
+        let int8_model = ModelInfo {
+            name: format!("{}_int8", model.name),
+            url: "local".to_string(),
+            description: format!("{} (INT8)", model.description),
+            quantized: true,
+            checksum: None,
+            ..model.clone()
+        };
+        let int8_path = model_cache.model_path(&int8_model);
+        
+        log_message(&format!("Quantizing {} -> {}", fp32_path.display(), int8_path.display()));
+        let output = ProcessCommand::new("python")
+            .args(["-c", QUANTIZE_SCRIPT])
+            .args([&fp32_path, &int8_path])
+            .output()
+            .map_err(|e| format!("Failed to run python: {}. Make sure Python and onnxruntime are installed.", e))?;
+        
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            log_error(&format!("Quantization failed: {}", stderr));
+            return Err(stderr.lines().last().unwrap_or("python exited with an error").to_string());
+        }
+        
+        let fp32_bytes = fs::metadata(&fp32_path).map(|m| m.len()).unwrap_or(0);
+        let int8_bytes = fs::metadata(&int8_path).map(|m| m.len()).unwrap_or(0);
+        let fp32_secs = benchmark_model(&model, &model_cache).map_err(|e| e.to_string())?;
+        let int8_secs = benchmark_model(&int8_model, &model_cache).map_err(|e| e.to_string())?;
+        
+        log_message(&format!("Quantized {}: {} → {}, {:.0} ms → {:.0} ms",
+            model.name, format_file_size(fp32_bytes), format_file_size(int8_bytes),
+            fp32_secs * 1000.0, int8_secs * 1000.0));
+        
+        Ok(QuantizeReport { model: int8_model, fp32_bytes, int8_bytes, fp32_secs, int8_secs })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+// One graph input or output as reported by ORT
+#[derive(Debug, Clone)]
+struct TensorSpec {
+    name: String,
+    element_type: String,
+    dtype: Option<TensorDtype>, // None = an element type the pipeline can't feed
+    shape: Vec<Option<i64>>, // None = dynamic, e.g. the batch or spatial axes
+}
+
+impl TensorSpec {
+    fn new(name: &str, value_type: &ort::value::ValueType) -> Self {
+        match value_type {
+            ort::value::ValueType::Tensor { ty, shape, .. } => Self {
+                name: name.to_string(),
+                element_type: format!("{:?}", ty),
+                dtype: TensorDtype::from_element_type(ty),
+                shape: shape.iter().map(|&dim| (dim >= 0).then_some(dim)).collect(),
+            },
+            other => Self {
+                name: name.to_string(),
+                element_type: format!("{:?}", other),
+                dtype: None,
+                shape: Vec::new(),
+            },
+        }
+    }
+    
+    fn shape_label(&self) -> String {
+        let dims: Vec<String> = self.shape.iter()
+            .map(|dim| dim.map_or("?".to_string(), |d| d.to_string()))
+            .collect();
+        format!("[{}]", dims.join(", "))
+    }
+    
+    fn channels(&self, format: &TensorFormat) -> Option<i64> {
+        let axis = match format {
+            TensorFormat::NCHW => 1,
+            TensorFormat::NHWC => 3,
+        };
+        self.shape.get(axis).copied().flatten()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ModelInspection {
+    model: ModelInfo,
+    inputs: Vec<TensorSpec>,
+    outputs: Vec<TensorSpec>,
+    warnings: Vec<String>, // Where the graph contradicts the stored ModelInfo
+}
+
+// Loads the model on the CPU just to read its graph signature
+async fn inspect_model(model: ModelInfo, model_cache: ModelCache) -> Result<ModelInspection, String> {
+    tokio::task::spawn_blocking(move || {
+        let session = create_session(&model, ExecutionDevice::Cpu, &OrtConfig::default(), &model_cache)
+            .map_err(|e| e.to_string())?;
+        
+        let inputs: Vec<TensorSpec> = session.inputs.iter()
+            .map(|input| TensorSpec::new(&input.name, &input.input_type))
+            .collect();
+        let outputs: Vec<TensorSpec> = session.outputs.iter()
+            .map(|output| TensorSpec::new(&output.name, &output.output_type))
+            .collect();
+        let warnings = inspection_warnings(&model, &inputs, &outputs);
+        
+        Ok(ModelInspection { model, inputs, outputs, warnings })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+// Only the first input and output are used by run_inference
+fn inspection_warnings(model: &ModelInfo, inputs: &[TensorSpec], outputs: &[TensorSpec]) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let other_format = match model.tensor_format {
+        TensorFormat::NCHW => TensorFormat::NHWC,
+        TensorFormat::NHWC => TensorFormat::NCHW,
+    };
+    // YCbCr and channel-independent models feed the graph one channel at a time
+    let single_channel = model.ycbcr_mode || model.channel_independent;
+    let expected = |channels: u8| if single_channel || channels == 1 { 1 } else { 3 };
+    
+    for (kind, spec, expected_channels) in [
+        ("Input", inputs.first(), expected(model.input_channels)),
+        ("Output", outputs.first(), expected(model.output_channels)),
+    ] {
+        let Some(spec) = spec else {
+            warnings.push(format!("Model has no {}", kind.to_lowercase()));
+            continue;
+        };
+        
+        match spec.dtype {
+            None => warnings.push(format!("{} '{}' is {}, only Float32 and Float16 are supported", kind, spec.name, spec.element_type)),
+            Some(dtype) if dtype != model.tensor_dtype => warnings.push(format!("{} '{}' is {}, ModelInfo says {}",
+                kind, spec.name, dtype, model.tensor_dtype)),
+            Some(_) => {}
+        }
+        if spec.shape.len() != 4 {
+            warnings.push(format!("{} '{}' has {} dimensions, expected 4", kind, spec.name, spec.shape.len()));
+            continue;
+        }
+        
+        match spec.channels(&model.tensor_format) {
+            Some(channels) if channels != expected_channels => {
+                let hint = if spec.channels(&other_format) == Some(expected_channels) {
+                    format!(", the shape looks like {}", other_format)
+                } else {
+                    String::new()
+                };
+                warnings.push(format!("{} '{}' has {} channel(s) on the {} channel axis, ModelInfo expects {}{}",
+                    kind, spec.name, channels, model.tensor_format, expected_channels, hint));
+            }
+            _ => {}
+        }
+    }
+    
+    warnings
+}
+
+// Mean CPU inference time on a flat grey input, after one warm-up run
+fn benchmark_model(model: &ModelInfo, model_cache: &ModelCache) -> Result<f32> {
+    let size = QUANTIZE_BENCHMARK_SIZE.max(model.min_dimension.unwrap_or(0));
+    let img = DynamicImage::ImageRgb8(ImageBuffer::from_pixel(size, size, Rgb([128, 128, 128])));
+    let (padded, _, _) = pad_to_multiple(&img, model.window_size.max(1), model.pad_mode)?;
+    let tensor = preprocess_image_for_model(&padded, model)?;
+    
+    let mut session = create_session(model, ExecutionDevice::Cpu, &OrtConfig::default(), model_cache)?;
+    infer_tensor(&mut session, tensor.clone(), model)?;
+    
+    let start = std::time::Instant::now();
+    for _ in 0..QUANTIZE_BENCHMARK_RUNS {
+        infer_tensor(&mut session, tensor.clone(), model)?;
+    }
+    Ok(start.elapsed().as_secs_f32() / QUANTIZE_BENCHMARK_RUNS as f32)
+}
+
+const DEFAULT_BENCHMARK_RUNS: u32 = 5;
+
+// Inference latency of the selected model on the loaded image, through the configured EP
+#[derive(Debug, Clone)]
+struct BenchmarkResult {
+    model_name: String,
+    ep: String,
+    input_dims: (u32, u32), // After the usual resize and padding
+    runs: u32,
+    mean_secs: f32,
+    min_secs: f32,
+    max_secs: f32,
+    std_dev_secs: f32,
+    megapixels_per_sec: f32, // Input pixels
+}
+
+// Times `runs` forward passes on a warmed-up session; the image is resized and padded the
+// same way an untiled batch image would be
+async fn benchmark_inference(
+    model: ModelInfo,
+    image: Arc<DynamicImage>,
+    runs: u32,
+    ort_config: OrtConfig,
+    model_cache: ModelCache,
+) -> Result<BenchmarkResult, String> {
+    tokio::task::spawn_blocking(move || -> Result<BenchmarkResult> {
+        let max_dim = DEFAULT_MAX_INPUT_DIM.max(model.min_dimension.unwrap_or(0));
+        let image = if image.width() > max_dim || image.height() > max_dim {
+            image.resize(max_dim, max_dim, image::imageops::FilterType::Lanczos3)
+        } else {
+            (*image).clone()
+        };
+        let (padded, input_dims, _) = pad_to_multiple(&image, model.window_size.max(1), model.pad_mode)?;
+        let tensor = preprocess_image_for_model(&padded, &model)?;
+        
+        let mut session = create_session(&model, ExecutionDevice::Gpu, &ort_config, &model_cache)?;
+        warmup_session(&mut session, &model)?;
+        
+        let mut latencies = Vec::with_capacity(runs as usize);
+        for run in 1..=runs {
+            let start = std::time::Instant::now();
+            infer_tensor(&mut session, tensor.clone(), &model)?;
+            let secs = start.elapsed().as_secs_f32();
+            log_message(&format!("Benchmark run {}/{}: {:.0} ms", run, runs, secs * 1000.0));
+            latencies.push(secs);
+        }
+        
+        let mean_secs = latencies.iter().sum::<f32>() / latencies.len() as f32;
+        let variance = latencies.iter().map(|secs| (secs - mean_secs).powi(2)).sum::<f32>() / latencies.len() as f32;
+        let megapixels = input_dims.0 as f32 * input_dims.1 as f32 / 1_000_000.0;
+        
+        Ok(BenchmarkResult {
+            model_name: model.name.clone(),
+            ep: ort_config.ep.to_string(),
+            input_dims,
+            runs,
+            mean_secs,
+            min_secs: latencies.iter().copied().fold(f32::INFINITY, f32::min),
+            max_secs: latencies.iter().copied().fold(0.0, f32::max),
+            std_dev_secs: variance.sqrt(),
+            megapixels_per_sec: megapixels / mean_secs.max(f32::EPSILON),
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
+
+// Registers the CoreML provider once, so an unsupported system is reported before a batch
+#[cfg(feature = "coreml")]
+async fn check_coreml_available() -> Result<(), String> {
+    tokio::task::spawn_blocking(|| {
+        use ort::execution_providers::ExecutionProvider as _;
+        
+        match ort::execution_providers::CoreMLExecutionProvider::default().is_available() {
+            Ok(true) => Ok(()),
+            Ok(false) => Err("not supported by this system or ONNX Runtime build".to_string()),
+            Err(e) => Err(e.to_string()),
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+// The stripe reader is 8-bit only, so 16-bit models always load the whole image
+fn needs_streaming(path: &Path, model: &ModelInfo) -> bool {
+    if model.bit_depth == BitDepth::Bit16 {
+        return false;
+    }
+    
+    let is_png = path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("png"));
+    
+    is_png && image::image_dimensions(path)
+        .is_ok_and(|(w, h)| w as u64 * h as u64 > STREAMING_PIXEL_THRESHOLD)
+}
+
+fn rows_to_rgb_image(rows: &[Vec<u8>], width: u32) -> DynamicImage {
+    let pixels: Vec<u8> = rows.iter().flatten().copied().collect();
+    DynamicImage::ImageRgb8(ImageBuffer::from_raw(width, rows.len() as u32, pixels)
+        .expect("stripe rows are width * 3 bytes"))
+}
+
+// Streams a huge PNG through the model in horizontal stripes of 2 * step rows, advancing by
+// step rows each time. Only the middle of each stripe is written so every output row has
+// step / 2 rows of context above and below, and peak memory is bounded by the stripe size.
+fn process_streaming_png(
+    input_path: &Path,
+    model: &ModelInfo,
+    opts: &ProcessOptions,
+    output_convention: Option<&str>,
+    ort_config: &OrtConfig,
+    model_cache: &ModelCache,
+) -> Result<ProcessResult> {
+    log_message(&format!("=== Streaming: {} (always written as PNG) ===", input_path.display()));
+    let start = std::time::Instant::now();
+    
+    let mut decoder = png::Decoder::new_with_limits(
+        io::BufReader::new(fs::File::open(input_path)?),
+        png::Limits { bytes: usize::MAX },
+    );
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
+    
+    if reader.info().interlaced {
+        return Err(anyhow::anyhow!("Interlaced PNGs cannot be streamed row by row"));
+    }
+    
+    let (width, height) = (reader.info().width, reader.info().height);
+    let (color_type, _) = reader.output_color_type();
+    let scale = model.scale;
+    
+    // Stripe heights must stay multiples of the model's window size
+    let step = model.window_size.max(1) * STREAMING_MIN_STEP.div_ceil(model.window_size.max(1));
+    let context = step / 2;
+    log_message(&format!("Streaming {}x{} in stripes of {} rows (step {})", width, height, step * 2, step));
+    
+    let output_dir = opts.output_dir_for(input_path);
+    let output_path = output_path_for(input_path, model, &output_dir, &opts.filename_template, output_convention,
+        model.bit_depth.output_extension(), (width * scale, height * scale))?;
+    let Some(output_path) = resolve_output_conflict(input_path, &output_path, opts.conflict_mode, &opts.claimed_outputs) else {
+        return Ok(skipped_result(input_path, output_path));
+    };
+    fs::create_dir_all(&output_dir)?;
+    let mut session = create_session(model, ExecutionDevice::Gpu, ort_config, model_cache)?;
+    let mut encoder = png::Encoder::new(
+        io::BufWriter::new(fs::File::create(&output_path)?),
+        width * scale,
+        height * scale,
+    );
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    let mut stream = writer.stream_writer()?;
+    
+    let mut buffer: std::collections::VecDeque<Vec<u8>> = std::collections::VecDeque::new();
+    let mut stripe_start = 0u32; // Input row of buffer[0]
+    let mut committed = 0u32;    // Input rows already written to the output
+    
+    while committed < height {
+        while buffer.len() < (step * 2) as usize {
+            let Some(row) = reader.next_row()? else { break };
+            let rgb: Vec<u8> = match color_type {
+                png::ColorType::Rgb => row.data().to_vec(),
+                png::ColorType::Rgba => row.data().chunks_exact(4).flat_map(|p| [p[0], p[1], p[2]]).collect(),
+                png::ColorType::Grayscale => row.data().iter().flat_map(|&v| [v, v, v]).collect(),
+                png::ColorType::GrayscaleAlpha => row.data().chunks_exact(2).flat_map(|p| [p[0], p[0], p[0]]).collect(),
+                png::ColorType::Indexed => return Err(anyhow::anyhow!("Indexed PNG was not expanded")),
+            };
+            buffer.push_back(rgb);
+        }
+        
+        if buffer.is_empty() {
+            return Err(anyhow::anyhow!("PNG ended after {} of {} rows", stripe_start, height));
+        }
+        
+        let stripe_rows = buffer.len() as u32;
+        let is_last = stripe_start + stripe_rows >= height;
+        let commit_to = if is_last { height } else { stripe_start + step + context };
+        
+        let stripe = rows_to_rgb_image(buffer.make_contiguous(), width);
+        let (padded, _, _) = pad_to_multiple(&stripe, model.window_size.max(1), model.pad_mode)?;
+        let output = infer_tensor(&mut session, preprocess_image_for_model(&padded, model)?, model)?;
+        verify_output_tensor(&output, model)?;
+        let upscaled = postprocess_tensor_for_model(output, model)?.to_rgb8();
+        
+        let row_bytes = (width * scale * 3) as usize;
+        let full_row_bytes = (upscaled.width() * 3) as usize;
+        let raw = upscaled.as_raw();
+        for out_row in (committed - stripe_start) * scale..(commit_to - stripe_start) * scale {
+            let offset = out_row as usize * full_row_bytes;
+            stream.write_all(&raw[offset..offset + row_bytes])?;
+        }
+        
+        log_message(&format!("Streamed rows {}-{} of {}", committed, commit_to, height));
+        committed = commit_to;
+        
+        let advance = step.min(stripe_rows) as usize;
+        buffer.drain(..advance);
+        stripe_start += advance as u32;
+    }
+    
+    stream.finish()?;
+    
+    let duration = start.elapsed().as_secs_f32();
+    log_message(&format!("✓ Streamed in {:.2}s", duration));
+    
+    Ok(ProcessResult {
+        input_path: input_path.to_path_buf(),
+        output_bytes: fs::metadata(&output_path).map(|m| m.len()).unwrap_or(0),
+        input_bytes: fs::metadata(input_path).map(|m| m.len()).unwrap_or(0),
+        output_path,
+        input_dims: (width, height),
+        output_dims: (width * scale, height * scale),
+        duration,
+        rating: None,
+        data_uri: None,
+        bytes_saved: 0,
+        pipeline_steps: Vec::new(),
+        status: ProcessResultStatus::Processed,
+    })
+}
+
+// A filename template typed in the settings wins. Detected conventions always carry a scale token,
+// so they only apply to upscaling models; anything else keeps the default name rather than
+// colliding with earlier outputs.
+fn output_path_for(
+    input_path: &Path,
+    model: &ModelInfo,
+    output_dir: &Path,
+    filename_template: &str,
+    convention: Option<&str>,
+    extension: &str,
+    output_dims: (u32, u32),
+) -> Result<PathBuf> {
+    let template = match convention {
+        _ if !filename_template.trim().is_empty() => with_extension_token(filename_template.trim()),
+        Some(template) if model.scale > 1 => template.to_string(),
+        _ => default_output_template(model),
+    };
+    
+    Ok(output_dir.join(render_output_filename(&template, input_path, model, extension, output_dims)?))
+}
+
+// The path to write, None = Skip. Skip only gets here when the output appeared after the batch's
+// up-front check, or the predicted name differed (crop_to_content with {width}/{height}); the
+// file is left alone and the image still counts as skipped rather than failed.
+// A returned path is claimed for the batch, so no other image picks it while its file is
+// still waiting to be written.
+fn resolve_output_conflict(input_path: &Path, output_path: &Path, mode: ConflictMode, claimed: &ClaimedOutputs) -> Option<PathBuf> {
+    let mut claimed = claimed.lock().unwrap();
+    let taken = |path: &Path, claimed: &HashMap<PathBuf, PathBuf>| {
+        path.exists() || claimed.get(path).is_some_and(|owner| owner != input_path)
+    };
+    if !taken(output_path, &claimed) {
+        claimed.insert(output_path.to_path_buf(), input_path.to_path_buf());
+        return Some(output_path.to_path_buf());
+    }
+    
+    let resolved = match mode {
+        ConflictMode::Overwrite => {
+            log_message(&format!("Overwriting existing output: {}", output_path.display()));
+            output_path.to_path_buf()
+        }
+        ConflictMode::Skip => {
+            log_message(&format!("Skipping, {} already exists", output_path.display()));
+            return None;
+        }
+        ConflictMode::AutoRename => {
+            let renamed = unique_path(output_path, |candidate| taken(candidate, &claimed));
+            log_message(&format!("{} exists, writing {}", output_path.display(), renamed.display()));
+            renamed
+        }
+    };
+    claimed.insert(resolved.clone(), input_path.to_path_buf());
+    Some(resolved)
+}
+
+// photo_4x.png -> photo_4x_1.png, photo_4x_2.png, ... whichever isn't taken first
+fn unique_path(path: &Path, taken: impl Fn(&Path) -> bool) -> PathBuf {
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("output");
+    let extension = path.extension().and_then(|e| e.to_str());
+    
+    (1..)
+        .map(|n| {
+            let name = match extension {
+                Some(ext) => format!("{}_{}.{}", stem, n, ext),
+                None => format!("{}_{}", stem, n),
+            };
+            path.with_file_name(name)
+        })
+        .find(|candidate| !taken(candidate))
+        .unwrap()
+}
+
+// The output a file would get before it is processed, with the model's nominal scale standing
+// in for the final size. Used by ConflictMode::Skip to avoid running the model at all.
+fn existing_output(input_path: &Path, model: &ModelInfo, opts: &ProcessOptions, output_convention: Option<&str>) -> Option<PathBuf> {
+    let (w, h) = image::image_dimensions(input_path).ok()?;
+    let output_path = output_path_for(input_path, model, &opts.output_dir_for(input_path), &opts.filename_template,
+        output_convention, opts.output_format.extension(model.bit_depth), (w * model.scale, h * model.scale)).ok()?;
+    output_path.exists().then_some(output_path)
+}
+
+fn skipped_result(input_path: &Path, output_path: PathBuf) -> ProcessResult {
+    ProcessResult {
+        input_path: input_path.to_path_buf(),
+        input_dims: image::image_dimensions(input_path).unwrap_or((0, 0)),
+        output_dims: image::image_dimensions(&output_path).unwrap_or((0, 0)),
+        duration: 0.0,
+        input_bytes: fs::metadata(input_path).map(|m| m.len()).unwrap_or(0),
+        output_bytes: fs::metadata(&output_path).map(|m| m.len()).unwrap_or(0),
+        output_path,
+        rating: None,
+        data_uri: None,
+        bytes_saved: 0,
+        pipeline_steps: Vec::new(),
+        status: ProcessResultStatus::Skipped,
+    }
+}
+
+// Typed templates may leave out the extension, it always follows the output format
+fn with_extension_token(template: &str) -> String {
+    if template.contains("{ext}") {
+        template.to_string()
+    } else {
+        format!("{}.{{ext}}", template)
+    }
+}
+
+fn default_output_template(model: &ModelInfo) -> String {
+    match model.model_type {
+        ModelType::Upscaling | ModelType::Enhancement if model.scale > 1 => "{stem}_{scale}x.{ext}".to_string(),
+        ModelType::Denoising => "{stem}_denoised.{ext}".to_string(),
+        _ => "{stem}_enhanced.{ext}".to_string(),
+    }
+}
+
+// {ext} follows the output format, whatever extension earlier files used
+fn render_output_filename(template: &str, input_path: &Path, model: &ModelInfo, extension: &str, output_dims: (u32, u32)) -> Result<String> {
+    let stem = input_path.file_stem()
+        .and_then(|n| n.to_str())
+        .unwrap_or("output");
+    
+    let vars = HashMap::from([
+        ("stem", stem.to_string()),
+        ("model", model.name.clone()),
+        ("scale", model.scale.to_string()),
+        ("width", output_dims.0.to_string()),
+        ("height", output_dims.1.to_string()),
+        ("date", Local::now().format("%Y-%m-%d").to_string()),
+        ("ext", extension.to_string()),
+    ]);
+    render_template(template, &vars)
+}
+
+// Replaces each {key}; an unknown key or an unclosed brace is an error, a lone } is kept
+fn render_template(template: &str, vars: &HashMap<&str, String>) -> Result<String> {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    
+    while let Some(open) = rest.find('{') {
+        rendered.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let close = after.find('}')
+            .ok_or_else(|| anyhow::anyhow!("Unclosed {{ in template"))?;
+        let key = &after[..close];
+        let value = vars.get(key)
+            .ok_or_else(|| anyhow::anyhow!("Unknown variable {{{}}}", key))?;
+        rendered.push_str(value);
+        rest = &after[close + 1..];
+    }
+    rendered.push_str(rest);
+    
+    Ok(rendered)
+}
+
+const OUTPUT_SUFFIX_WORDS: [&str; 9] = ["upscaled", "upscale", "sr", "hd", "enhanced", "denoised", "deblurred", "processed", "restored"];
+
+// Finds the most common `{stem}_..._{scale}x...` pattern among existing outputs. Trailing
+// underscore-separated tokens count as suffix while they are scale tokens (4x, x4) or
+// OUTPUT_SUFFIX_WORDS; at least one scale token is required.
+fn detect_output_convention(output_dir: &Path) -> Option<String> {
+    let extensions = ["jpg", "jpeg", "png", "bmp", "webp"];
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    
+    for entry in fs::read_dir(output_dir).ok()?.flatten() {
+        let path = entry.path();
+        let is_image = path.extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| extensions.contains(&e.to_lowercase().as_str()));
+        let Some(stem) = path.file_stem().and_then(|s| s.to_str()).filter(|_| is_image) else {
+            continue;
+        };
+        
+        let tokens: Vec<&str> = stem.split('_').collect();
+        let mut suffix = Vec::new();
+        let mut has_scale = false;
+        for token in tokens.iter().skip(1).rev() {
+            let lower = token.to_lowercase();
+            let is_digits = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+            
+            if lower.strip_suffix('x').is_some_and(is_digits) {
+                suffix.push("{scale}x".to_string());
+                has_scale = true;
+            } else if lower.strip_prefix('x').is_some_and(is_digits) {
+                suffix.push("x{scale}".to_string());
+                has_scale = true;
+            } else if OUTPUT_SUFFIX_WORDS.contains(&lower.as_str()) {
+                suffix.push(token.to_string());
+            } else {
+                break;
+            }
+        }
+        
+        if has_scale {
+            suffix.reverse();
+            *counts.entry(format!("{{stem}}_{}.{{ext}}", suffix.join("_"))).or_insert(0) += 1;
+        }
+    }
+    
+    counts.into_iter()
+        .max_by(|(a_template, a), (b_template, b)| a.cmp(b).then_with(|| b_template.cmp(a_template)))
+        .map(|(template, _)| template)
+}
+
+fn infer_tensor(session: &mut Session, input_tensor: Array4<f32>, model: &ModelInfo) -> Result<Array4<f32>> {
+    if model.ycbcr_mode {
+        run_ycbcr(session, input_tensor, model)
+    } else if model.channel_independent {
+        run_channel_independent(session, input_tensor, model)
+    } else {
+        run_inference(session, input_tensor, model)
+    }
+}
+
+fn run_whole_image(session: &mut Session, input_tensor: Array4<f32>, model: &ModelInfo) -> Result<DynamicImage> {
+    let output_array = infer_tensor(session, input_tensor, model)?;
+    verify_output_tensor(&output_array, model)?;
+    
+    log_message("Postprocessing tensor...");
+    postprocess_tensor_for_model(output_array, model).map_err(|e| {
+        log_error(&format!("Postprocessing failed: {}", e));
+        e
+    })
+}
+
+// Upscales the padded image tile by tile through the same session, so peak memory is bounded
+// by the tile size rather than the image size
+fn run_tiled(session: &mut Session, img: &DynamicImage, model: &ModelInfo, tile_size: u32, tile_overlap: u32) -> Result<DynamicImage> {
+    // Tiles must stay multiples of the window size; the image is already padded to one
+    let window = model.window_size.max(1);
+    let tile = (tile_size / window).max(1) * window;
+    let (width, height) = img.dimensions();
+    
+    if width <= tile && height <= tile {
+        return run_whole_image(session, preprocess_image_for_model(img, model)?, model);
+    }
+    
+    // The overlap should cover at least one attention window for the blend to hide the seam,
+    // but never half a tile or the tile count explodes
+    let overlap = tile_overlap.min(tile / 4).max(window).min(tile / 2);
+    let tiles = tile_image(img, tile, overlap, model.scale);
+    log_message(&format!("Tiled inference: {}x{} in {}x{} tiles ({} tiles)", width, height, tile, tile, tiles.len()));
+    
+    let upscaled = tiles.into_iter()
+        .map(|(x, y, tile_img)| {
+            let output = run_whole_image(session, preprocess_image_for_model(&tile_img, model)?, model)?;
+            Ok((x, y, output))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    
+    let stitched = stitch_tiles(upscaled, width * model.scale, height * model.scale, model.bit_depth);
+    // Tiles are blended as RGB, grayscale models get their single channel back
+    Ok(if model.output_channels == 1 { stitched.grayscale() } else { stitched })
+}
+
+// Cuts img into tile_size squares stepping by tile_size - overlap; the last row and column are
+// shifted back to end at the image edge so every tile is full size. Positions are returned
+// multiplied by scale, i.e. where each upscaled tile belongs in the output.
+fn tile_image(img: &DynamicImage, tile_size: u32, overlap: u32, scale: u32) -> Vec<(u32, u32, DynamicImage)> {
+    let (width, height) = img.dimensions();
+    let step = tile_size.saturating_sub(overlap).max(1);
+    
+    let origins = |len: u32| -> Vec<u32> {
+        if len <= tile_size {
+            return vec![0];
+        }
+        let mut origins: Vec<u32> = (0..len - tile_size).step_by(step as usize).collect();
+        origins.push(len - tile_size);
+        origins
+    };
+    
+    let (xs, ys) = (origins(width), origins(height));
+    ys.iter()
+        .flat_map(|&y| xs.iter().map(move |&x| (x, y)))
+        .map(|(x, y)| (
+            x * scale,
+            y * scale,
+            img.crop_imm(x, y, tile_size.min(width), tile_size.min(height)),
+        ))
+        .collect()
+}
+
+fn stitch_tiles(tiles: Vec<(u32, u32, DynamicImage)>, out_w: u32, out_h: u32, bit_depth: BitDepth) -> DynamicImage {
+    let blended = blend_tile_borders(&tiles, out_w, out_h);
+    let max_value = bit_depth.max_value();
+    let sample = |x: u32, y: u32, c: usize| (blended[[0, c, y as usize, x as usize]] * max_value).round().clamp(0.0, max_value);
+    
+    match bit_depth {
+        BitDepth::Bit8 => DynamicImage::ImageRgb8(ImageBuffer::from_fn(out_w, out_h, |x, y| {
+            Rgb([0, 1, 2].map(|c| sample(x, y, c) as u8))
+        })),
+        BitDepth::Bit16 => DynamicImage::ImageRgb16(ImageBuffer::from_fn(out_w, out_h, |x, y| {
+            Rgb([0, 1, 2].map(|c| sample(x, y, c) as u16))
+        })),
+    }
+}
+
+// Weighted average of all tiles covering each pixel, weighted by a Hann window so every tile
+// fades out towards its borders and overlaps cross-fade instead of showing a seam.
+// Returns NCHW RGB in 0..=1, whatever the tiles' bit depth.
+fn blend_tile_borders(tiles: &[(u32, u32, DynamicImage)], out_w: u32, out_h: u32) -> Array4<f32> {
+    let (out_w, out_h) = (out_w as usize, out_h as usize);
+    let mut accumulator = Array4::<f32>::zeros((1, 3, out_h, out_w));
+    let mut weight_accumulator = Array4::<f32>::zeros((1, 1, out_h, out_w));
+    
+    for (tile_x, tile_y, tile) in tiles {
+        let tile = tile.to_rgb32f();
+        let (tw, th) = (tile.width() as usize, tile.height() as usize);
+        let (tile_x, tile_y) = (*tile_x as usize, *tile_y as usize);
+        let weights = cosine_weight_2d(th, tw);
+        
+        for (x, y, pixel) in tile.enumerate_pixels() {
+            let (x, y) = (x as usize, y as usize);
+            let (ox, oy) = (tile_x + x, tile_y + y);
+            if ox >= out_w || oy >= out_h {
+                continue;
+            }
+            
+            let weight = weights[[y, x]];
+            weight_accumulator[[0, 0, oy, ox]] += weight;
+            for c in 0..3 {
+                accumulator[[0, c, oy, ox]] += pixel[c] * weight;
+            }
+        }
+    }
+    
+    // Every output pixel is covered by some tile, so w is only 0 if the tiles don't span the output
+    accumulator * weight_accumulator.mapv(|w| if w > 0.0 { 1.0 / w } else { 0.0 })
+}
+
+// Raised-cosine (Hann) window, sampled at pixel centres. Each axis is floored at 1e-3 so the
+// corner weight of a large tile stays well clear of f32 underflow and pixels covered by a single
+// tile still normalise back to their own value.
+fn cosine_weight_2d(h: usize, w: usize) -> Array2<f32> {
+    let hann = |i: usize, n: usize| (0.5 - 0.5 * (2.0 * std::f32::consts::PI * (i as f32 + 0.5) / n as f32).cos()).max(1e-3);
+    Array2::from_shape_fn((h, w), |(y, x)| hann(y, h) * hann(x, w))
+}
+
+// Largest tile whose input and output f32 RGB tensors fit in VRAM, falling back to
+// DEFAULT_TILE_SIZE when no NVIDIA GPU is found
+fn auto_tile_size() -> u32 {
+    let Some(vram_bytes) = detect_vram_bytes() else {
+        log_message(&format!("VRAM unknown, using {}×{} tile size", DEFAULT_TILE_SIZE, DEFAULT_TILE_SIZE));
+        return DEFAULT_TILE_SIZE;
+    };
+    
+    let max_safe = ((vram_bytes / (4 * 3 * 2)) as f64).sqrt().floor() as u32;
+    let tile = (max_safe.min(MAX_AUTO_TILE_SIZE) / 64 * 64).max(MIN_TILE_SIZE);
+    
+    log_message(&format!("Detected {}GB VRAM, using {}×{} tile size",
+        vram_bytes / (1024 * 1024 * 1024), tile, tile));
+    tile
+}
+
+fn detect_vram_bytes() -> Option<u64> {
+    let nvml = nvml_wrapper::Nvml::init().ok()?;
+    let memory = nvml.device_by_index(0).ok()?.memory_info().ok()?;
+    Some(memory.total)
+}
+
+fn cached_session<'a>(
+    sessions: &'a mut HashMap<String, Session>,
+    model: &ModelInfo,
+    device: ExecutionDevice,
+    ort_config: &OrtConfig,
+    model_cache: &ModelCache,
+) -> Result<&'a mut Session> {
+    match sessions.entry(model.name.clone()) {
+        std::collections::hash_map::Entry::Occupied(entry) => Ok(entry.into_mut()),
+        std::collections::hash_map::Entry::Vacant(entry) => {
+            let mut session = create_session(model, device, ort_config, model_cache)?;
+            if let Err(e) = warmup_session(&mut session, model) {
+                log_error(&format!("Session warm-up failed, continuing without it: {}", e));
+            }
+            Ok(entry.insert(session))
+        }
+    }
+}
+
+// The first run through a new session JIT-compiles its operators; doing it on a small
+// all-zero input keeps that cost out of the first real image
+fn warmup_session(session: &mut Session, model: &ModelInfo) -> Result<()> {
+    let window = model.window_size.max(1);
+    let side = WARMUP_SIZE.max(model.min_dimension.unwrap_or(0)).div_ceil(window) * window;
+    let side = side as usize;
+    let channels = if model.input_channels == 1 { 1 } else { 3 };
+    let input = match model.tensor_format {
+        TensorFormat::NCHW => Array4::<f32>::zeros((1, channels, side, side)),
+        TensorFormat::NHWC => Array4::<f32>::zeros((1, side, side, channels)),
+    };
+    
+    let start = std::time::Instant::now();
+    infer_tensor(session, input, model)?;
+    log_message(&format!("Session warm-up complete ({}x{} in {:.2}s)", side, side, start.elapsed().as_secs_f32()));
+    Ok(())
+}
+
+// Image loaded, resized and converted to the model's input tensor, ready for inference
+struct PreparedInput {
+    orig_dims: (u32, u32),
+    resized_dims: (u32, u32),
+    padding: (u32, u32), // (pad_right, pad_bottom)
+    pixels: PreparedPixels,
+    alpha: Option<ImageBuffer<Luma<u8>, Vec<u8>>>, // At resized_dims, models only see RGB
+    prepare_secs: f32,
+}
+
+enum PreparedPixels {
+    Tensor(Array4<f32>), // Whole image, one inference
+    Tiles { image: DynamicImage, tile_size: u32, overlap: u32 }, // Padded image, converted tile by tile
+    Inferred(DynamicImage), // Already upscaled as part of a stacked batch, see run_stacked_batch
+}
+
+// Disk read and preprocessing only, so the batch loop can run it on a background thread
+// while the previous image is still in inference
+fn prepare_input(input_path: &Path, model: &ModelInfo, opts: &ProcessOptions) -> Result<PreparedInput> {
+    let start = std::time::Instant::now();
+    
+    log_message("Loading input image...");
+    // WIC always decodes to 8-bit RGBA, so 16-bit models go straight to the image crate
+    let img = match model.bit_depth {
+        BitDepth::Bit8 => fast_open(input_path),
+        BitDepth::Bit16 => image::open(input_path).map_err(anyhow::Error::from),
+    };
+    let img = img.map_err(|e| {
+        log_error(&format!("Failed to open image: {}", e));
+        e
+    })?;
+    
+    // Models are trained on sRGB, so wide-gamut inputs are converted before the tensor is built
+    // Only Convert needs the profile here, the other modes would re-open every file for nothing
+    let icc_profile = if opts.color_profile_mode == ColorProfileMode::ConvertToSRGB {
+        read_icc_profile(input_path)
+    } else {
+        None
+    };
+    let img = match icc_profile {
+        Some(icc) => {
+            log_message(&format!("Converting {} byte ICC profile to sRGB", icc.len()));
+            let fallback = img.clone();
+            convert_to_srgb(img, &icc).unwrap_or_else(|e| {
+                log_error(&format!("ICC conversion failed, using the unconverted pixels: {}", e));
+                fallback
+            })
+        }
+        _ => img,
+    };
+    
+    let mut prepared = prepare_image(img, model, opts)?;
+    prepared.prepare_secs = start.elapsed().as_secs_f32();
+    Ok(prepared)
+}
+
+// Resizing, padding and tensor conversion for an image already in memory
+fn prepare_image(img: DynamicImage, model: &ModelInfo, opts: &ProcessOptions) -> Result<PreparedInput> {
+    let start = std::time::Instant::now();
+    let tile_size = opts.tile_size.or(model.max_tile_size);
+    
+    let (orig_w, orig_h) = img.dimensions();
+    log_message(&format!("Original image size: {}x{}", orig_w, orig_h));
+    
+    // Apply model-specific minimum dimension requirement
+    let min_dim = model.min_dimension.unwrap_or(0);
+    // Tiled inference handles any size, otherwise use at least the minimum, or max_input_dim
+    let max_dim = if tile_size.is_some() { u32::MAX } else { opts.max_input_dim.max(min_dim) };
+    
+    let img = if orig_w > max_dim || orig_h > max_dim || orig_w < min_dim || orig_h < min_dim {
+        // Need to resize - either too large or too small
+        let target_dim = if orig_w < min_dim || orig_h < min_dim {
+            // Too small - upscale to minimum
+            let scale = (min_dim as f32 / orig_w.min(orig_h) as f32).max(1.0);
+            let new_w = (orig_w as f32 * scale) as u32;
+            let new_h = (orig_h as f32 * scale) as u32;
+            log_message(&format!("Image too small, upscaling to {}x{} (scale: {:.2})", new_w, new_h, scale));
+            (new_w, new_h)
+        } else {
+            // Too large - downscale to max_dim
+            let scale = (max_dim as f32 / orig_w.max(orig_h) as f32).min(1.0);
+            let new_w = (orig_w as f32 * scale) as u32;
+            let new_h = (orig_h as f32 * scale) as u32;
+            log_message(&format!("Resizing to {}x{} (scale: {:.2})", new_w, new_h, scale));
+            (new_w, new_h)
+        };
+        
+        img.resize_exact(target_dim.0, target_dim.1, image::imageops::FilterType::Lanczos3)
+    } else {
+        img
+    };
+
+    let (padded_img, padded_dims, (pad_r, pad_b)) = if model.window_size > 1 {
+        log_message(&format!("Padding to multiple of {}", model.window_size));
+        pad_to_multiple(&img, model.window_size, model.pad_mode)?
+    } else {
+        (img.clone(), img.dimensions(), (0, 0))
+    };
+
+    log_message(&format!("Padded dimensions: {}x{} (pad_r: {}, pad_b: {})", 
+        padded_dims.0, padded_dims.1, pad_r, pad_b));
+    
+    // Verify dimensions are valid
+    if padded_dims.0 == 0 || padded_dims.1 == 0 {
+        return Err(anyhow::anyhow!("Invalid padded dimensions: {}x{}", padded_dims.0, padded_dims.1));
+    }
+
+    log_message(&format!("Preprocessing image {}x{} for model: {}", 
+        padded_img.dimensions().0, padded_img.dimensions().1, model.name));
+
+    let resized_dims = img.dimensions();
+    let alpha = extract_alpha(&img);
+    if alpha.is_some() {
+        log_message("Input has an alpha channel, it will be rescaled and restored after inference");
+    }
+    
+    let pixels = match tile_size {
+        // Tiles are converted to tensors one at a time, never the whole image
+        Some(tile_size) => PreparedPixels::Tiles { image: padded_img, tile_size, overlap: opts.tile_overlap },
+        None => {
+            log_message("Preprocessing image...");
+            PreparedPixels::Tensor(preprocess_image_for_model(&padded_img, model).map_err(|e| {
+                log_error(&format!("Preprocessing failed: {}", e));
+                e
+            })?)
+        }
+    };
+
+    Ok(PreparedInput {
+        orig_dims: (orig_w, orig_h),
+        resized_dims,
+        padding: (pad_r, pad_b),
+        pixels,
+        alpha,
+        prepare_secs: start.elapsed().as_secs_f32(),
+    })
+}
+
+// Alpha plane of images that have one; to_rgb8 drops it before preprocessing
+fn extract_alpha(img: &DynamicImage) -> Option<ImageBuffer<Luma<u8>, Vec<u8>>> {
+    if !img.color().has_alpha() {
+        return None;
+    }
+    
+    // WIC decodes every JPEG to RGBA, a fully opaque plane isn't worth restoring
+    let rgba = img.to_rgba8();
+    if rgba.pixels().all(|p| p[3] == u8::MAX) {
+        return None;
+    }
+    Some(ImageBuffer::from_fn(rgba.width(), rgba.height(), |x, y| Luma([rgba.get_pixel(x, y)[3]])))
+}
+
+// Bilinearly rescales the original alpha to the model output and recombines it
+fn restore_alpha(img: DynamicImage, alpha: &ImageBuffer<Luma<u8>, Vec<u8>>) -> DynamicImage {
+    let (w, h) = img.dimensions();
+    let alpha = image::imageops::resize(alpha, w, h, image::imageops::FilterType::Triangle);
+    
+    if matches!(img, DynamicImage::ImageRgb16(_) | DynamicImage::ImageLuma16(_)) {
+        let mut rgba = img.to_rgba16();
+        for (pixel, a) in rgba.pixels_mut().zip(alpha.pixels()) {
+            pixel[3] = a[0] as u16 * 257;
+        }
+        return DynamicImage::ImageRgba16(rgba);
+    }
+    
+    let mut rgba = img.to_rgba8();
+    for (pixel, a) in rgba.pixels_mut().zip(alpha.pixels()) {
+        pixel[3] = a[0];
+    }
+    DynamicImage::ImageRgba8(rgba)
+}
+
+// Inference plus undoing the preparation: padding cropped off, alpha put back
+fn infer_prepared(session: &mut Session, prepared: PreparedInput, model: &ModelInfo) -> Result<DynamicImage> {
+    let PreparedInput { resized_dims, padding: (pad_r, pad_b), pixels, alpha, .. } = prepared;
+    
+    let mut img = match pixels {
+        PreparedPixels::Tensor(input_tensor) => run_whole_image(session, input_tensor, model)?,
+        PreparedPixels::Tiles { image, tile_size, overlap } => run_tiled(session, &image, model, tile_size, overlap)?,
+        PreparedPixels::Inferred(image) => image,
+    };
+
+    if pad_r > 0 || pad_b > 0 {
+        let target_w = resized_dims.0 * model.scale;
+        let target_h = resized_dims.1 * model.scale;
+        log_message(&format!("Cropping padding: target {}x{}", target_w, target_h));
+        img = img.crop_imm(0, 0, target_w, target_h);
+    }
+    
+    if let Some(alpha) = &alpha {
+        img = restore_alpha(img, alpha);
+    }
+    Ok(img)
+}
+
+// Runs every model of the pipeline on the input, each step seeing the previous step's output
+fn process_pipeline(
+    input: &Path,
+    pipeline: &ProcessPipeline,
+    opts: &ProcessOptions,
+    sessions: &mut HashMap<String, Session>,
+    ort_config: &OrtConfig,
+    model_cache: &ModelCache,
+) -> Result<DynamicImage> {
+    let Some((first, rest)) = pipeline.0.split_first() else {
+        return Err(anyhow::anyhow!("Pipeline has no steps"));
+    };
+    
+    log_message(&format!("Pipeline step 1/{}: {}", pipeline.0.len(), first.name));
+    let prepared = prepare_input(input, first, opts)?;
+    let mut img = infer_prepared(cached_session(sessions, first, ExecutionDevice::Gpu, ort_config, model_cache)?, prepared, first)?;
+    
+    for (idx, model) in rest.iter().enumerate() {
+        log_message(&format!("Pipeline step {}/{}: {}", idx + 2, pipeline.0.len(), model.name));
+        let prepared = prepare_image(img, model, opts)?;
+        img = infer_prepared(cached_session(sessions, model, ExecutionDevice::Gpu, ort_config, model_cache)?, prepared, model)?;
+    }
+    Ok(img)
+}
+
+// All but the last step through process_pipeline, the last one through the normal save path
+// so the output is named, tagged and written like a single-model result
+#[allow(clippy::too_many_arguments)]
+fn run_pipeline_image(
+    input_path: &Path,
+    pipeline: &ProcessPipeline,
+    sessions: &mut HashMap<String, Session>,
+    opts: &ProcessOptions,
+    output_convention: Option<&str>,
+    output_writer: &mpsc::Sender<SaveRequest>,
+    ort_config: &OrtConfig,
+    model_cache: &ModelCache,
+) -> Result<ProcessResult> {
+    let Some((last, earlier)) = pipeline.0.split_last() else {
+        return Err(anyhow::anyhow!("Pipeline has no steps"));
+    };
+    let start = std::time::Instant::now();
+    
+    let prepared = if earlier.is_empty() {
+        prepare_input(input_path, last, opts)?
+    } else {
+        let intermediate = process_pipeline(input_path, &ProcessPipeline(earlier.to_vec()), opts, sessions, ort_config, model_cache)?;
+        log_message(&format!("Pipeline step {}/{}: {}", pipeline.0.len(), pipeline.0.len(), last.name));
+        prepare_image(intermediate, last, opts)?
+    };
+    let earlier_secs = start.elapsed().as_secs_f32() - prepared.prepare_secs;
+    
+    let session = cached_session(sessions, last, ExecutionDevice::Gpu, ort_config, model_cache)?;
+    let mut result = run_prepared_image(input_path, prepared, last, session, opts, output_convention, Some(output_writer))?;
+    // The last step only saw the intermediate image
+    result.input_dims = image::image_dimensions(input_path).unwrap_or(result.input_dims);
+    result.duration += earlier_secs.max(0.0);
+    result.pipeline_steps = pipeline.step_names();
+    Ok(result)
+}
+
+fn run_prepared_image(
+    input_path: &Path,
+    prepared: PreparedInput,
+    model: &ModelInfo,
+    session: &mut Session,
+    opts: &ProcessOptions,
+    output_convention: Option<&str>,
+    output_writer: Option<&mpsc::Sender<SaveRequest>>,
+) -> Result<ProcessResult> {
+    log_message(&format!("=== Processing: {} ===", input_path.display()));
+    log_message(&format!("Model: {} ({})", model.name, model.category));
+    
+    let start = std::time::Instant::now();
+
+    let (orig_w, orig_h) = prepared.orig_dims;
+    let prepare_secs = prepared.prepare_secs;
+    let has_alpha = prepared.alpha.is_some();
+    let mut final_img = infer_prepared(session, prepared, model)?;
+    
+    if opts.crop_to_content {
+        final_img = crop_to_content(&final_img, CROP_BACKGROUND_THRESHOLD);
+    }
+    
+    let (out_w, out_h) = final_img.dimensions();
+    log_message(&format!("Final output size: {}x{}", out_w, out_h));
+
+    // The restored alpha would be lost in a JPEG, so transparent inputs are written as PNG
+    let output_format = if has_alpha && !opts.output_format.keeps_alpha() {
+        log_message(&format!("Input has transparency, saving as PNG instead of {}", opts.output_format));
+        OutputFormat::Png
+    } else {
+        opts.output_format.clone()
+    };
+    
+    let output_dir = opts.output_dir_for(input_path);
+    let output_path = output_path_for(input_path, model, &output_dir, &opts.filename_template, output_convention,
+        output_format.extension(model.bit_depth), (out_w, out_h))?;
+    let writes_file = !matches!(output_format, OutputFormat::Base64DataUri { .. });
+    let output_path = if writes_file {
+        fs::create_dir_all(&output_dir)?;
+        match resolve_output_conflict(input_path, &output_path, opts.conflict_mode, &opts.claimed_outputs) {
+            Some(path) => path,
+            None => return Ok(skipped_result(input_path, output_path)),
+        }
+    } else {
+        output_path
+    };
+    
+    let data_uri = match &output_format {
+        OutputFormat::Base64DataUri { mime } => {
+            // Only PNG carries the restored alpha
+            let mime = if has_alpha { "image/png" } else { mime.as_str() };
+            log_message(&format!("Encoding output as {} data URI", mime));
+            Some(encode_to_data_uri(&final_img, mime).map_err(|e| {
+                log_error(&format!("Failed to encode data URI: {}", e));
+                e
+            })?)
+        }
+        output_format => {
+            let metadata = OutputMetadata {
+                exif: read_output_exif(input_path, opts.exif_mode),
+                icc_profile: match opts.color_profile_mode {
+                    ColorProfileMode::Preserve => read_icc_profile(input_path),
+                    ColorProfileMode::Strip | ColorProfileMode::ConvertToSRGB => None,
+                },
+            };
+            match output_writer {
+                Some(output_writer) => {
+                    log_message(&format!("Queued for saving: {}", output_path.display()));
+                    output_writer.send((input_path.to_path_buf(), output_path.clone(), final_img, output_format.clone(), metadata))
+                        .map_err(|_| anyhow::anyhow!("Output writer thread has stopped"))?;
+                }
+                None => {
+                    log_message(&format!("Saving to: {}", output_path.display()));
+                    save_output(&final_img, &output_path, output_format, metadata).map_err(|e| {
+                        log_error(&format!("Failed to save image: {}", e));
+                        e
+                    })?;
+                }
+            }
+            None
+        }
+    };
+
+    let duration = prepare_secs + start.elapsed().as_secs_f32();
+    log_message(&format!("✓ Completed in {:.2}s", duration));
+
+    let input_bytes = fs::metadata(input_path).map(|m| m.len()).unwrap_or(0);
+    // Queued writes are measured once the writer has finished, see OutputWriter::finish
+    let output_bytes = match &data_uri {
+        Some(uri) => uri.len() as u64,
+        None => fs::metadata(&output_path).map(|m| m.len()).unwrap_or(0),
+    };
+
+    let result = ProcessResult {
+        input_path: input_path.to_path_buf(),
+        output_path,
+        input_dims: (orig_w, orig_h),
+        output_dims: (out_w, out_h),
+        duration,
+        input_bytes,
+        output_bytes,
+        rating: None,
+        data_uri,
+        bytes_saved: 0,
+        pipeline_steps: Vec::new(),
+        status: ProcessResultStatus::Processed,
+    };
+    
+    if opts.write_sidecar && result.data_uri.is_none() {
+        if let Err(e) = write_sidecar(&result, model) {
+            log_error(&format!("Failed to write sidecar for {}: {}", result.output_path.display(), e));
+        }
+    }
+    
+    Ok(result)
+}
+
+// photo_upscaled.png -> photo_upscaled.png.json, so tools can trace an output back to its run
+fn write_sidecar(result: &ProcessResult, model: &ModelInfo) -> Result<()> {
+    let sidecar = serde_json::json!({
+        "input": result.input_path,
+        "model": model.name,
+        "scale": model.scale,
+        "input_size": [result.input_dims.0, result.input_dims.1],
+        "output_size": [result.output_dims.0, result.output_dims.1],
+        "duration_secs": result.duration,
+    });
+    
+    let mut sidecar_path = result.output_path.clone().into_os_string();
+    sidecar_path.push(".json");
+    fs::write(PathBuf::from(sidecar_path), serde_json::to_string_pretty(&sidecar)?)?;
+    Ok(())
+}
+
+// Tags that describe the scene or the photo rather than how it was exposed; exposure, shutter
+// speed and lens data would be misleading on an upscaled output
+const DESCRIPTIVE_EXIF_TAGS: &[exif::Tag] = &[
+    exif::Tag::ImageDescription,
+    exif::Tag::Artist,
+    exif::Tag::Copyright,
+    exif::Tag::UserComment,
+    exif::Tag::Make,
+    exif::Tag::Model,
+    exif::Tag::Orientation,
+    exif::Tag::DateTime,
+    exif::Tag::DateTimeOriginal,
+    exif::Tag::DateTimeDigitized,
+    exif::Tag::OffsetTime,
+    exif::Tag::OffsetTimeOriginal,
+    exif::Tag::OffsetTimeDigitized,
+];
+
+// Describe the input's pixel layout, which the upscaled PNG doesn't share
+const STRUCTURAL_EXIF_TAGS: &[exif::Tag] = &[
+    exif::Tag::ImageWidth,
+    exif::Tag::ImageLength,
+    exif::Tag::BitsPerSample,
+    exif::Tag::Compression,
+    exif::Tag::PhotometricInterpretation,
+    exif::Tag::SamplesPerPixel,
+    exif::Tag::PlanarConfiguration,
+    exif::Tag::RowsPerStrip,
+    exif::Tag::PixelXDimension,
+    exif::Tag::PixelYDimension,
+];
+
+// Serialized EXIF (TIFF structure, as the PNG eXIf chunk wants it) for the output, or None
+// when stripping or when the input has nothing to carry over
+fn read_output_exif(input_path: &Path, mode: ExifMode) -> Option<Vec<u8>> {
+    if mode == ExifMode::Strip {
+        return None;
+    }
+    
+    let exif = match fs::File::open(input_path)
+        .map_err(exif::Error::Io)
+        .and_then(|file| exif::Reader::new().read_from_container(&mut io::BufReader::new(file)))
+    {
+        Ok(exif) => exif,
+        Err(exif::Error::NotFound(_)) => return None,
+        Err(e) => {
+            log_error(&format!("Failed to read EXIF from {}: {}", input_path.display(), e));
+            return None;
+        }
+    };
+    
+    // The thumbnail IFD is dropped, it shows the image at its old size
+    let fields: Vec<&exif::Field> = exif.fields()
+        .filter(|field| field.ifd_num == exif::In::PRIMARY)
+        .filter(|field| !matches!(field.value, exif::Value::Unknown(..)))
+        .filter(|field| !STRUCTURAL_EXIF_TAGS.contains(&field.tag))
+        .filter(|field| mode == ExifMode::Preserve
+            || field.tag.context() == exif::Context::Gps
+            || DESCRIPTIVE_EXIF_TAGS.contains(&field.tag))
+        .collect();
+    
+    if fields.is_empty() {
+        return None;
+    }
+    
+    let mut writer = exif::experimental::Writer::new();
+    for field in &fields {
+        writer.push_field(field);
+    }
+    let mut bytes = io::Cursor::new(Vec::new());
+    match writer.write(&mut bytes, exif.little_endian()) {
+        Ok(()) => {
+            log_message(&format!("Copying {} EXIF tag(s) from {}", fields.len(), input_path.display()));
+            Some(bytes.into_inner())
+        }
+        Err(e) => {
+            log_error(&format!("Failed to write EXIF for {}: {}", input_path.display(), e));
+            None
+        }
+    }
+}
+
+// The embedded ICC profile, read from the header without decoding the pixels
+fn read_icc_profile(path: &Path) -> Option<Vec<u8>> {
+    let mut decoder = image::ImageReader::open(path).ok()?
+        .with_guessed_format().ok()?
+        .into_decoder().ok()?;
+    image::ImageDecoder::icc_profile(&mut decoder).ok().flatten()
+}
+
+fn convert_to_srgb(img: DynamicImage, icc: &[u8]) -> Result<DynamicImage> {
+    let profile = lcms2::Profile::new_icc(icc)?;
+    if profile.color_space() != lcms2::ColorSpaceSignature::RgbData {
+        return Err(anyhow::anyhow!("only RGB profiles can be converted, this one is {:?}", profile.color_space()));
+    }
+    let srgb = lcms2::Profile::new_srgb();
+    
+    // Alpha is left as is, the transforms run in place
+    Ok(match img {
+        DynamicImage::ImageRgb8(mut buffer) => {
+            transform_pixels::<u8, 3>(&profile, &srgb, lcms2::PixelFormat::RGB_8, &mut buffer)?;
+            DynamicImage::ImageRgb8(buffer)
+        }
+        DynamicImage::ImageRgba8(mut buffer) => {
+            transform_pixels::<u8, 4>(&profile, &srgb, lcms2::PixelFormat::RGBA_8, &mut buffer)?;
+            DynamicImage::ImageRgba8(buffer)
+        }
+        DynamicImage::ImageRgb16(mut buffer) => {
+            transform_pixels::<u16, 3>(&profile, &srgb, lcms2::PixelFormat::RGB_16, &mut buffer)?;
+            DynamicImage::ImageRgb16(buffer)
+        }
+        DynamicImage::ImageRgba16(mut buffer) => {
+            transform_pixels::<u16, 4>(&profile, &srgb, lcms2::PixelFormat::RGBA_16, &mut buffer)?;
+            DynamicImage::ImageRgba16(buffer)
+        }
+        other => return Err(anyhow::anyhow!("unsupported pixel layout {:?}", other.color())),
+    })
+}
+
+fn transform_pixels<T: Copy + lcms2::Pod, const N: usize>(
+    from: &lcms2::Profile,
+    to: &lcms2::Profile,
+    format: lcms2::PixelFormat,
+    data: &mut [T],
+) -> Result<()> {
+    let transform = lcms2::Transform::<[T; N], [T; N]>::new(from, format, to, format, lcms2::Intent::Perceptual)?;
+    let mut pixels: Vec<[T; N]> = data.chunks_exact(N)
+        .map(|chunk| std::array::from_fn(|i| chunk[i]))
+        .collect();
+    transform.transform_in_place(&mut pixels);
+    for (chunk, pixel) in data.chunks_exact_mut(N).zip(&pixels) {
+        chunk.copy_from_slice(pixel);
+    }
+    Ok(())
+}
+
+// Input metadata carried over to the saved output
+#[derive(Debug)]
+struct OutputMetadata {
+    exif: Option<Vec<u8>>, // See read_output_exif
+    icc_profile: Option<Vec<u8>>, // Written as the iCCP chunk for PNG
+}
+
+// Input path, output path, image, format and metadata for OutputWriter
+type SaveRequest = (PathBuf, PathBuf, DynamicImage, OutputFormat, OutputMetadata);
+
+fn save_output(image: &DynamicImage, path: &Path, format: &OutputFormat, metadata: OutputMetadata) -> Result<()> {
+    // 16-bit models write TIFF under OutputFormat::Png, see BitDepth::output_extension
+    if *format == OutputFormat::Png && image::ImageFormat::from_path(path).ok() == Some(image::ImageFormat::Tiff) {
+        return save_output(image, path, &OutputFormat::Tiff, metadata);
+    }
+    
+    let mut writer = io::BufWriter::new(fs::File::create(path)?);
+    
+    match format {
+        OutputFormat::Jpeg(quality) => {
+            let mut encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(writer, *quality);
+            set_output_metadata(&mut encoder, metadata);
+            // JPEG has no alpha channel and no 16-bit mode
+            DynamicImage::ImageRgb8(image.to_rgb8()).write_with_encoder(encoder)?;
+        }
+        OutputFormat::WebpLossless => {
+            let mut encoder = image::codecs::webp::WebPEncoder::new_lossless(writer);
+            set_output_metadata(&mut encoder, metadata);
+            to_8bit(image).write_with_encoder(encoder)?;
+        }
+        OutputFormat::WebpLossy(quality) => {
+            if metadata.exif.is_some() || metadata.icc_profile.is_some() {
+                log_message("Lossy WebP output doesn't carry EXIF or ICC metadata");
+            }
+            let image = to_8bit(image);
+            let encoder = match &image {
+                DynamicImage::ImageRgba8(rgba) => webp::Encoder::from_rgba(rgba, rgba.width(), rgba.height()),
+                DynamicImage::ImageRgb8(rgb) => webp::Encoder::from_rgb(rgb, rgb.width(), rgb.height()),
+                _ => unreachable!("to_8bit returns RGB or RGBA"),
+            };
+            writer.write_all(&encoder.encode(*quality as f32))?;
+            writer.flush()?;
+        }
+        OutputFormat::Tiff => {
+            let mut encoder = image::codecs::tiff::TiffEncoder::new(writer);
+            set_output_metadata(&mut encoder, metadata);
+            image.write_with_encoder(encoder)?;
+        }
+        // Slow enough to matter, but batch runs already save on the OutputWriter thread
+        #[cfg(feature = "avif")]
+        OutputFormat::Avif(quality) => {
+            if metadata.icc_profile.is_some() {
+                log_message("AVIF output doesn't carry the ICC profile");
+            }
+            let mut encoder = ravif::Encoder::new().with_quality(*quality as f32);
+            if let Some(exif) = metadata.exif {
+                encoder = encoder.with_exif(exif);
+            }
+            let (width, height) = (image.width() as usize, image.height() as usize);
+            let encoded = if image.color().has_alpha() {
+                let pixels: Vec<ravif::RGBA8> = image.to_rgba8().pixels()
+                    .map(|p| ravif::RGBA8::new(p[0], p[1], p[2], p[3]))
+                    .collect();
+                encoder.encode_rgba(ravif::Img::new(&pixels[..], width, height))?
+            } else {
+                let pixels: Vec<ravif::RGB8> = image.to_rgb8().pixels()
+                    .map(|p| ravif::RGB8::new(p[0], p[1], p[2]))
+                    .collect();
+                encoder.encode_rgb(ravif::Img::new(&pixels[..], width, height))?
+            };
+            writer.write_all(&encoded.avif_file)?;
+            writer.flush()?;
+        }
+        #[cfg(feature = "jxl")]
+        OutputFormat::Jxl { quality, lossless } => {
+            if metadata.exif.is_some() || metadata.icc_profile.is_some() {
+                log_message("JPEG XL output doesn't carry EXIF or ICC metadata");
+            }
+            let image = to_8bit(image);
+            let mut encoder = jpegxl_rs::encoder_builder()
+                .has_alpha(image.color().has_alpha())
+                .lossless(*lossless)
+                .uses_original_profile(*lossless)
+                .quality(jxl_distance(*quality))
+                .build()?;
+            let encoded: jpegxl_rs::encode::EncoderResult<u8> =
+                encoder.encode::<u8, u8>(image.as_bytes(), image.width(), image.height())?;
+            writer.write_all(&encoded.data)?;
+            writer.flush()?;
+        }
+        OutputFormat::Png | OutputFormat::Base64DataUri { .. } => {
+            let mut encoder = image::codecs::png::PngEncoder::new(writer);
+            set_output_metadata(&mut encoder, metadata);
+            image.write_with_encoder(encoder)?;
+        }
+    }
+    Ok(())
+}
+
+// Formats that can't store a block log it and are saved without it
+fn set_output_metadata(encoder: &mut impl image::ImageEncoder, metadata: OutputMetadata) {
+    if let Some(exif) = metadata.exif {
+        if let Err(e) = encoder.set_exif_metadata(exif) {
+            log_message(&format!("EXIF not written: {}", e));
+        }
+    }
+    if let Some(icc_profile) = metadata.icc_profile {
+        if let Err(e) = encoder.set_icc_profile(icc_profile) {
+            log_message(&format!("ICC profile not written: {}", e));
+        }
+    }
+}
+
+// libjxl's JxlEncoderDistanceFromQuality: 100 -> 0 (lossless), 90 -> 1.0 (visually lossless)
+#[cfg(feature = "jxl")]
+fn jxl_distance(quality: f32) -> f32 {
+    if quality >= 100.0 {
+        0.0
+    } else if quality >= 30.0 {
+        0.1 + (100.0 - quality) * 0.09
+    } else {
+        53.0 / 3000.0 * quality * quality - 23.0 / 20.0 * quality + 25.0
+    }
+}
+
+// WebP, JPEG XL and AVIF encoders take 8-bit RGB or RGBA only
+fn to_8bit(image: &DynamicImage) -> DynamicImage {
+    if image.color().has_alpha() {
+        DynamicImage::ImageRgba8(image.to_rgba8())
+    } else {
+        DynamicImage::ImageRgb8(image.to_rgb8())
+    }
+}
+
+// Trims uniform margins (e.g. the white border around a scanned page), keeping CROP_PADDING
+// pixels around the detected content
+fn crop_to_content(img: &DynamicImage, background_threshold: u8) -> DynamicImage {
+    let rgba = img.to_rgba8();
+    let (w, h) = rgba.dimensions();
+    let strip = CROP_BORDER_STRIP.min(w / 2).min(h / 2);
+    
+    if strip == 0 {
+        return img.clone();
+    }
+    
+    // Most frequent colour in the border strip is taken as the background
+    let mut counts: HashMap<[u8; 4], u32> = HashMap::new();
+    for (x, y, pixel) in rgba.enumerate_pixels() {
+        if x < strip || y < strip || x >= w - strip || y >= h - strip {
+            *counts.entry(pixel.0).or_insert(0) += 1;
+        }
+    }
+    let Some(background) = counts.into_iter().max_by_key(|&(_, count)| count).map(|(color, _)| color) else {
+        return img.clone();
+    };
+    
+    let mut bounds: Option<(u32, u32, u32, u32)> = None;
+    for (x, y, pixel) in rgba.enumerate_pixels() {
+        let is_content = pixel.0.iter().zip(background.iter())
+            .any(|(&c, &b)| c.abs_diff(b) > background_threshold);
+        
+        if is_content {
+            bounds = Some(match bounds {
+                Some((min_x, min_y, max_x, max_y)) => (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y)),
+                None => (x, y, x, y),
+            });
+        }
+    }
+    
+    let Some((min_x, min_y, max_x, max_y)) = bounds else {
+        log_message("Crop to content: no content found, keeping full image");
+        return img.clone();
+    };
+    
+    let left = min_x.saturating_sub(CROP_PADDING);
+    let top = min_y.saturating_sub(CROP_PADDING);
+    let right = (max_x + 1 + CROP_PADDING).min(w);
+    let bottom = (max_y + 1 + CROP_PADDING).min(h);
+    
+    log_message(&format!("Crop to content: {}x{} -> {}x{}", w, h, right - left, bottom - top));
+    img.crop_imm(left, top, right - left, bottom - top)
+}
+
+// Blue -> green -> yellow -> red for low -> high change, t in 0..=1
+fn heatmap_color(t: f32) -> [u8; 3] {
+    const STOPS: [[f32; 3]; 4] = [
+        [0.0, 0.0, 255.0],
+        [0.0, 255.0, 0.0],
+        [255.0, 255.0, 0.0],
+        [255.0, 0.0, 0.0],
+    ];
+    
+    let scaled = t.clamp(0.0, 1.0) * (STOPS.len() - 1) as f32;
+    let idx = (scaled as usize).min(STOPS.len() - 2);
+    let frac = scaled - idx as f32;
+    let (from, to) = (STOPS[idx], STOPS[idx + 1]);
+    
+    [0, 1, 2].map(|c| (from[c] + (to[c] - from[c]) * frac) as u8)
+}
+
+// Per-pixel RGB distance between the two images, normalized to the largest change in the image
+// so subtle models still show structure, then blended over `after` at 50% opacity
+fn build_change_heatmap(before_upsampled: &DynamicImage, after: &DynamicImage) -> DynamicImage {
+    let before = before_upsampled.to_rgb8();
+    let after = after.to_rgb8();
+    
+    let magnitudes: Vec<f32> = before.pixels().zip(after.pixels())
+        .map(|(b, a)| {
+            let sum: f32 = (0..3).map(|c| (a[c] as f32 - b[c] as f32).powi(2)).sum();
+            sum.sqrt()
+        })
+        .collect();
+    let max = magnitudes.iter().cloned().fold(0.0f32, f32::max).max(1.0);
+    
+    let mut blended = after.clone();
+    for (pixel, magnitude) in blended.pixels_mut().zip(&magnitudes) {
+        let heat = heatmap_color(magnitude / max);
+        for c in 0..3 {
+            pixel[c] = ((pixel[c] as u16 + heat[c] as u16) / 2) as u8;
+        }
+    }
+    
+    DynamicImage::ImageRgb8(blended)
+}
+
+// PSNR in dB against the original, with `processed` brought back to the original resolution
+// so any upscale factor compares like for like
+fn compute_psnr(original: &DynamicImage, processed: &DynamicImage) -> f64 {
+    compute_psnr_ignore_borders(original, processed, 0)
+}
+
+// Same as compute_psnr but skips `border_px` on every edge, the way published SR results
+// exclude the window padding where tiled models are least reliable
+fn compute_psnr_ignore_borders(original: &DynamicImage, processed: &DynamicImage, border_px: u32) -> f64 {
+    let (w, h) = original.dimensions();
+    let processed = if processed.dimensions() == (w, h) {
+        processed.to_rgb8()
+    } else {
+        processed.resize_exact(w, h, image::imageops::FilterType::Lanczos3).to_rgb8()
+    };
+    let original = original.to_rgb8();
+    
+    // Fall back to the whole image when the border would leave nothing to compare
+    let border = if border_px * 2 < w.min(h) { border_px } else { 0 };
+    
+    let mut sum = 0.0f64;
+    let mut count = 0u64;
+    for y in border..h - border {
+        for x in border..w - border {
+            let (a, b) = (original.get_pixel(x, y), processed.get_pixel(x, y));
+            for c in 0..3 {
+                sum += (a[c] as f64 - b[c] as f64).powi(2);
+            }
+            count += 3;
+        }
+    }
+    
+    let mse = sum / count.max(1) as f64;
+    if mse == 0.0 {
+        f64::INFINITY
+    } else {
+        10.0 * (255.0f64 * 255.0 / mse).log10()
+    }
+}
+
+const COMPARISON_DIVIDER_WIDTH: u32 = 4;
+const SPLIT_DIVIDER_WIDTH: u32 = 2;
+const DEFAULT_SPLIT_POSITION: f32 = 0.5;
+
+// How the preview lays out before and after
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum ComparisonMode {
+    #[default]
+    SideBySide,
+    SplitLine, // One image, before left of a draggable divider and after right of it
+}
+
+// Before left of `split` (0.0-1.0 of the width) and after right of it, with a divider line between.
+// iced 0.12's canvas can't draw images, so the split is composited here and shown as a plain image
+fn build_split_image(before: &image::RgbaImage, after: &DynamicImage, split: f32, divider: image::Rgba<u8>) -> image::RgbaImage {
+    let mut split_image = after.to_rgba8();
+    let (w, h) = split_image.dimensions();
+    let split_x = ((split.clamp(0.0, 1.0) * w as f32).round() as u32).min(w);
+    
+    for y in 0..h.min(before.height()) {
+        for x in 0..split_x.min(before.width()) {
+            split_image.put_pixel(x, y, *before.get_pixel(x, y));
+        }
+        let divider_start = split_x.saturating_sub(SPLIT_DIVIDER_WIDTH / 2);
+        for x in divider_start..(divider_start + SPLIT_DIVIDER_WIDTH).min(w) {
+            split_image.put_pixel(x, y, divider);
+        }
+    }
+    split_image
+}
+
+// Before (bicubic-upsampled to the output size) and after side by side with a divider between,
+// the usual layout for sharing SR results
+fn build_comparison_image(before: &DynamicImage, after: &DynamicImage, divider: image::Rgba<u8>) -> image::RgbaImage {
+    let (w, h) = after.dimensions();
+    let before = before.resize_exact(w, h, image::imageops::FilterType::CatmullRom).to_rgba8();
+    
+    let mut canvas = image::RgbaImage::from_pixel(w * 2 + COMPARISON_DIVIDER_WIDTH, h, divider);
+    image::imageops::overlay(&mut canvas, &before, 0, 0);
+    image::imageops::overlay(&mut canvas, &after.to_rgba8(), (w + COMPARISON_DIVIDER_WIDTH) as i64, 0);
+    canvas
+}
+
+const SSIM_WINDOW: usize = 11;
+const SSIM_SIGMA: f64 = 1.5;
+
+// Normalized 1D Gaussian, applied along rows then columns for the 11x11 window
+fn ssim_kernel() -> [f64; SSIM_WINDOW] {
+    let center = (SSIM_WINDOW / 2) as f64;
+    let mut kernel = [0.0; SSIM_WINDOW];
+    for (i, k) in kernel.iter_mut().enumerate() {
+        *k = (-(i as f64 - center).powi(2) / (2.0 * SSIM_SIGMA * SSIM_SIGMA)).exp();
+    }
+    let sum: f64 = kernel.iter().sum();
+    kernel.map(|k| k / sum)
+}
+
+// Separable Gaussian blur with edges clamped, so the map keeps the input size
+fn gaussian_blur(values: &[f64], w: usize, h: usize, kernel: &[f64; SSIM_WINDOW]) -> Vec<f64> {
+    let radius = (SSIM_WINDOW / 2) as isize;
+    let clamp = |v: isize, max: usize| v.clamp(0, max as isize - 1) as usize;
+    
+    let mut horizontal = vec![0.0; values.len()];
+    for y in 0..h {
+        for x in 0..w {
+            horizontal[y * w + x] = kernel.iter().enumerate()
+                .map(|(i, k)| k * values[y * w + clamp(x as isize + i as isize - radius, w)])
+                .sum();
+        }
+    }
+    
+    let mut blurred = vec![0.0; values.len()];
+    for y in 0..h {
+        for x in 0..w {
+            blurred[y * w + x] = kernel.iter().enumerate()
+                .map(|(i, k)| k * horizontal[clamp(y as isize + i as isize - radius, h) * w + x])
+                .sum();
+        }
+    }
+    blurred
+}
+
+// Mean SSIM on luma (Wang et al. 2004) and a heatmap of the local map, blue where the images
+// agree and red where they differ. `b` is resized to `a` first, like compute_psnr.
+fn compute_ssim(a: &DynamicImage, b: &DynamicImage) -> (f64, DynamicImage) {
+    const C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+    const C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+    
+    let (w, h) = a.dimensions();
+    let b = if b.dimensions() == (w, h) {
+        b.to_luma8()
+    } else {
+        b.resize_exact(w, h, image::imageops::FilterType::Lanczos3).to_luma8()
+    };
+    let a = a.to_luma8();
+    let (w, h) = (w as usize, h as usize);
+    
+    let x: Vec<f64> = a.pixels().map(|p| p[0] as f64).collect();
+    let y: Vec<f64> = b.pixels().map(|p| p[0] as f64).collect();
+    let xx: Vec<f64> = x.iter().map(|v| v * v).collect();
+    let yy: Vec<f64> = y.iter().map(|v| v * v).collect();
+    let xy: Vec<f64> = x.iter().zip(&y).map(|(a, b)| a * b).collect();
+    
+    let kernel = ssim_kernel();
+    let [mu_x, mu_y, e_xx, e_yy, e_xy] = [&x, &y, &xx, &yy, &xy].map(|v| gaussian_blur(v, w, h, &kernel));
+    
+    let ssim_map: Vec<f64> = (0..w * h)
+        .map(|i| {
+            let (mx, my) = (mu_x[i], mu_y[i]);
+            let var_x = e_xx[i] - mx * mx;
+            let var_y = e_yy[i] - my * my;
+            let cov = e_xy[i] - mx * my;
+            ((2.0 * mx * my + C1) * (2.0 * cov + C2)) / ((mx * mx + my * my + C1) * (var_x + var_y + C2))
+        })
+        .collect();
+    let mean = ssim_map.iter().sum::<f64>() / ssim_map.len().max(1) as f64;
+    
+    let heatmap = image::RgbImage::from_fn(w as u32, h as u32, |px, py| {
+        let ssim = ssim_map[py as usize * w + px as usize];
+        image::Rgb(heatmap_color((1.0 - ssim) as f32))
+    });
+    
+    (mean, DynamicImage::ImageRgb8(heatmap))
+}
+
+// Median cut: repeatedly split the bucket with the widest channel range at its median,
+// then average each bucket. Works on a thumbnail since exact counts don't matter here.
+fn extract_palette(img: &DynamicImage, k: u8) -> Vec<[u8; 3]> {
+    let mut buckets: Vec<Vec<[u8; 3]>> = vec![
+        img.thumbnail(PALETTE_SAMPLE_SIZE, PALETTE_SAMPLE_SIZE)
+            .to_rgb8()
+            .pixels()
+            .map(|p| p.0)
+            .collect()
+    ];
+    
+    let channel_range = |bucket: &[[u8; 3]], channel: usize| {
+        let (min, max) = bucket.iter().fold((u8::MAX, u8::MIN), |(min, max), p| (min.min(p[channel]), max.max(p[channel])));
+        max.saturating_sub(min)
+    };
+    
+    while buckets.len() < k as usize {
+        let Some((idx, channel, range)) = buckets.iter().enumerate()
+            .filter(|(_, bucket)| bucket.len() > 1)
+            .flat_map(|(idx, bucket)| (0..3).map(move |c| (idx, c, channel_range(bucket, c))))
+            .max_by_key(|&(_, _, range)| range) else {
+            break;
+        };
+        
+        // Every remaining bucket is a single colour
+        if range == 0 {
+            break;
+        }
+        
+        let mut bucket = buckets.swap_remove(idx);
+        bucket.sort_unstable_by_key(|p| p[channel]);
+        let upper = bucket.split_off(bucket.len() / 2);
+        buckets.push(bucket);
+        buckets.push(upper);
+    }
+    
+    let mut palette: Vec<([u8; 3], usize)> = buckets.iter()
+        .filter(|bucket| !bucket.is_empty())
+        .map(|bucket| {
+            let sum = bucket.iter().fold([0u64; 3], |acc, p| [acc[0] + p[0] as u64, acc[1] + p[1] as u64, acc[2] + p[2] as u64]);
+            let n = bucket.len() as u64;
+            ([(sum[0] / n) as u8, (sum[1] / n) as u8, (sum[2] / n) as u8], bucket.len())
+        })
+        .collect();
+    
+    // Most common colours first
+    palette.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+    palette.into_iter().map(|(color, _)| color).collect()
+}
+
+// 64-bit difference hash: each bit says whether a pixel of the 9x8 greyscale thumbnail
+// is brighter than its right-hand neighbour
+fn compute_phash(img: &DynamicImage) -> u64 {
+    let small = img.resize_exact(9, 8, image::imageops::FilterType::Triangle).to_luma8();
+    
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            hash <<= 1;
+            if small.get_pixel(x, y).0[0] > small.get_pixel(x + 1, y).0[0] {
+                hash |= 1;
+            }
+        }
+    }
+    hash
+}
+
+async fn compute_image_hashes(files: Vec<PathBuf>) -> HashMap<PathBuf, u64> {
+    tokio::task::spawn_blocking(move || {
+        files.par_iter()
+            .filter_map(|path| fast_open(path).ok().map(|img| (path.clone(), compute_phash(&img))))
+            .collect()
+    })
+    .await
+    .unwrap_or_default()
+}
+
+// Pairs each image with the first earlier image in queue order that hashes within the threshold
+fn find_near_duplicates(files: &[PathBuf], hashes: &HashMap<PathBuf, u64>) -> Vec<(PathBuf, PathBuf)> {
+    let mut queued: Vec<(&PathBuf, u64)> = Vec::new();
+    let mut pairs = Vec::new();
+    
+    for path in files {
+        let Some(&hash) = hashes.get(path) else { continue };
+        
+        match queued.iter().find(|(_, other)| (hash ^ other).count_ones() < DUPLICATE_HAMMING_THRESHOLD) {
+            Some((original, _)) => pairs.push(((*original).clone(), path.clone())),
+            None => queued.push((path, hash)),
+        }
+    }
+    pairs
+}
+
+// Hands the file to the OS so the result comes back up in the user's editor or viewer
+fn open_in_default_viewer(path: &Path) {
+    match open::that_detached(path) {
+        Ok(()) => log_message(&format!("Opened {} in the default viewer", path.display())),
+        Err(e) => log_error(&format!("Failed to open {}: {}", path.display(), e)),
+    }
+}
+
+fn apply_png_optimization(result: &mut ProcessResult) {
+    let is_png = result.output_path.extension().is_some_and(|e| e.eq_ignore_ascii_case("png"));
+    if result.data_uri.is_some() || !is_png {
+        return;
+    }
+    
+    match optimize_png_file(&result.output_path) {
+        Ok(saved) => {
+            result.bytes_saved = saved;
+            result.output_bytes = result.output_bytes.saturating_sub(saved);
+        }
+        Err(e) => log_error(&format!("PNG optimization failed: {}", e)),
+    }
+}
+
+// Rewrites the PNG in place with oxipng and returns the number of bytes saved
+fn optimize_png_file(path: &Path) -> Result<u64> {
+    let before = fs::metadata(path)?.len();
+    
+    oxipng::optimize(
+        &oxipng::InFile::Path(path.to_path_buf()),
+        &oxipng::OutFile::Path { path: None, preserve_attrs: true },
+        &oxipng::Options::from_preset(2),
+    )?;
+    
+    let after = fs::metadata(path)?.len();
+    let saved = before.saturating_sub(after);
+    log_message(&format!("PNG optimized: {} → {} (−{:.0}%)",
+        format_file_size(before), format_file_size(after), saved as f64 * 100.0 / before.max(1) as f64));
+    
+    Ok(saved)
+}
+
+fn encode_to_data_uri(img: &DynamicImage, mime: &str) -> Result<String> {
+    let format = image::ImageFormat::from_mime_type(mime)
+        .ok_or_else(|| anyhow::anyhow!("Unsupported data URI type: {}", mime))?;
+    
+    let mut bytes = io::Cursor::new(Vec::new());
+    img.write_to(&mut bytes, format)?;
+    
+    Ok(format!("data:{};base64,{}", mime, base64::engine::general_purpose::STANDARD.encode(bytes.into_inner())))
+}
+
+fn decode_data_uri(uri: &str) -> Result<DynamicImage> {
+    let (_, payload) = uri.split_once(";base64,")
+        .ok_or_else(|| anyhow::anyhow!("Not a base64 data URI"))?;
+    let bytes = base64::engine::general_purpose::STANDARD.decode(payload)?;
+    Ok(image::load_from_memory(&bytes)?)
+}
+
+// Decodes and shrinks off the UI thread; THUMBNAIL_SIZE bounds the longer side
+async fn load_thumbnail(path: PathBuf) -> Result<(PathBuf, iced_image::Handle), String> {
+    tokio::task::spawn_blocking(move || {
+        let thumbnail = fast_open(&path)
+            .map_err(|e| format!("{}: {}", path.display(), e))?
+            .thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+            .to_rgba8();
+        let (w, h) = thumbnail.dimensions();
+        Ok((path, iced_image::Handle::from_pixels(w, h, thumbnail.into_raw())))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+// Decodes through WIC on Windows, which is much faster than the pure-Rust JPEG decoder
+// for large photos; anything WIC can't handle falls back to image::open
+fn fast_open(path: &Path) -> Result<DynamicImage> {
+    #[cfg(target_os = "windows")]
+    {
+        let is_wic_format = path.extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| matches!(e.to_lowercase().as_str(), "jpg" | "jpeg" | "png"));
+        
+        if is_wic_format {
+            match wic_open(path) {
+                Ok(img) => return Ok(img),
+                Err(e) => log_message(&format!("WIC decode failed, falling back to image crate: {}", e)),
+            }
+        }
+    }
+    
+    Ok(image::open(path)?)
+}
+
+#[cfg(target_os = "windows")]
+fn wic_open(path: &Path) -> Result<DynamicImage> {
+    use windows::core::HSTRING;
+    use windows::Win32::Foundation::GENERIC_READ;
+    use windows::Win32::Graphics::Imaging::{
+        CLSID_WICImagingFactory, GUID_WICPixelFormat32bppRGBA, IWICImagingFactory,
+        WICBitmapDitherTypeNone, WICBitmapPaletteTypeCustom, WICDecodeMetadataCacheOnDemand,
+    };
+    use windows::Win32::System::Com::{CoCreateInstance, CoInitializeEx, CLSCTX_INPROC_SERVER, COINIT_MULTITHREADED};
+    
+    unsafe {
+        // Already-initialised threads return S_FALSE or RPC_E_CHANGED_MODE, both fine for WIC
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+        
+        let factory: IWICImagingFactory = CoCreateInstance(&CLSID_WICImagingFactory, None, CLSCTX_INPROC_SERVER)?;
+        let decoder = factory.CreateDecoderFromFilename(
+            &HSTRING::from(path),
+            None,
+            GENERIC_READ,
+            WICDecodeMetadataCacheOnDemand,
+        )?;
+        let frame = decoder.GetFrame(0)?;
+        
+        let converter = factory.CreateFormatConverter()?;
+        converter.Initialize(
+            &frame,
+            &GUID_WICPixelFormat32bppRGBA,
+            WICBitmapDitherTypeNone,
+            None,
+            0.0,
+            WICBitmapPaletteTypeCustom,
+        )?;
+        
+        let (mut width, mut height) = (0u32, 0u32);
+        converter.GetSize(&mut width, &mut height)?;
+        
+        let stride = width * 4;
+        let mut pixels = vec![0u8; stride as usize * height as usize];
+        converter.CopyPixels(std::ptr::null(), stride, &mut pixels)?;
+        
+        let rgba = image::RgbaImage::from_raw(width, height, pixels)
+            .ok_or_else(|| anyhow::anyhow!("WIC returned an unexpected buffer size"))?;
+        Ok(DynamicImage::ImageRgba8(rgba))
+    }
+}
+
+fn load_result_image(result: &ProcessResult) -> Option<DynamicImage> {
+    match &result.data_uri {
+        Some(uri) => decode_data_uri(uri).ok(),
+        None => image::open(&result.output_path).ok(),
+    }
+}
+
+// Update process_images to use better error handling
+#[allow(clippy::too_many_arguments)]
+async fn process_images(
+    files: Vec<PathBuf>,
+    model: ModelInfo,
+    options: ProcessOptions,
+    ort_config: OrtConfig,
+    model_cache: ModelCache,
+    download_speed_limit_kbps: Option<u32>,
+    auto_select: Option<AutoSelect>,
+    gpu_temp_limit: Option<u32>,
+    gpu_temp: Arc<AtomicU32>,
+    disk_space: Arc<DiskSpaceGate>,
+    cancel_token: Arc<AtomicBool>,
+    pause_flag: Arc<AtomicBool>,
+    progress: Arc<Mutex<ProgressState>>,
+) -> Result<Vec<ProcessResult>, BatchError> {
+    tokio::task::spawn_blocking(move || {
+        let options = ProcessOptions { claimed_outputs: ClaimedOutputs::default(), ..options };
+        log_message("Initializing ONNX Runtime...");
+        ort::init().commit().map_err(|e| {
+            log_error(&format!("Failed to initialize ONNX Runtime: {}", e));
+            e.to_string()
+        })?;
+        
+        std::fs::create_dir_all(&options.output_dir).map_err(|e| {
+            log_error(&format!("Failed to create output directory: {}", e));
+            e.to_string()
+        })?;
+        
+        // Fetch missing models up front, where the download speed limit applies
+        let batch_models = std::iter::once(&model)
+            .chain(auto_select.iter().flat_map(|a| std::iter::once(&a.classifier).chain(a.routes.iter().map(|(_, m)| m))))
+            .chain(options.pipeline.iter().flat_map(|pipeline| pipeline.0.iter()));
+        for batch_model in batch_models {
+            ensure_model_file(batch_model, &model_cache, download_speed_limit_kbps).map_err(|e| BatchError {
+                checksum_failed: e.is::<ChecksumMismatch>().then(|| batch_model.name.clone()),
+                ..BatchError::from(e.to_string())
+            })?;
+        }
+        
+        let output_convention = detect_output_convention(&options.output_dir);
+        if let Some(template) = &output_convention {
+            log_message(&format!("Matching existing output naming: {}", template));
+        }
+        
+        let mut results = Vec::new();
+        let mut files = files;
+        let writes_files = !matches!(options.output_format, OutputFormat::Base64DataUri { .. });
+        if options.conflict_mode == ConflictMode::Skip && writes_files {
+            // Named after the last pipeline step, like run_pipeline_image; auto-select routes aren't known yet
+            let naming_model = options.pipeline.as_ref().and_then(|p| p.0.last()).unwrap_or(&model);
+            files.retain(|file| match existing_output(file, naming_model, &options, output_convention.as_deref()) {
+                Some(output_path) => {
+                    log_message(&format!("Skipping {}, {} already exists", file.display(), output_path.display()));
+                    results.push(skipped_result(file, output_path));
+                    false
+                }
+                None => true,
+            });
+            progress.lock().unwrap().current += results.len();
+        }
+        let total = files.len();
+        let output_writer = OutputWriter::spawn();
+        
+        // Monitor thread stops when this sender is dropped at the end of the batch
+        let _gpu_monitor = spawn_gpu_temperature_monitor(gpu_temp.clone());
+        
+        // The classifier session is reused for every image in the batch
+        let mut classifier_session = match &auto_select {
+            Some(auto_select) => Some(create_classifier_session(&auto_select.classifier, &ort_config, &model_cache).map_err(|e| {
+                log_error(&format!("Failed to load classifier: {}", e));
+                e.to_string()
+            })?),
+            None => None,
+        };
+        
+        let mut select_model = |file_path: &Path| match (&auto_select, classifier_session.as_mut()) {
+            (Some(auto_select), Some(session)) => match auto_select.select_model(session, file_path) {
+                Ok(selected) => selected.clone(),
+                Err(e) => {
+                    log_error(&format!("Classification failed, using {}: {}", model.name, e));
+                    model.clone()
+                }
+            },
+            _ => model.clone(),
+        };
+        
+        // Chained models run one image at a time, the other modes assume a single model per image
+        if let Some(pipeline) = &options.pipeline {
+            log_message(&format!("Pipeline: {}", pipeline.step_names().join(" → ")));
+            let mut sessions: HashMap<String, Session> = HashMap::new();
+            
+            for (idx, file_path) in files.iter().enumerate() {
+                wait_while_paused(&pause_flag, &cancel_token);
+                if cancel_token.load(Ordering::Relaxed) {
+                    log_message(&format!("Cancelled at {}/{}", idx, total));
+                    break;
+                }
+                if let Some(limit) = gpu_temp_limit {
+                    wait_for_gpu_cooldown(&gpu_temp, limit);
+                }
+                if let Err(error) = disk_space.wait_for_space(&options.output_dir, DEFAULT_MIN_FREE_DISK_GB, &cancel_token) {
+                    let (results, _) = output_writer.finish(results, options.optimize_png);
+                    log_message(&format!("\n=== Batch Stopped: {}/{} completed before abort ===", results.len(), total));
+                    return Err(BatchError { completed: results, failed_on: file_path.clone(), error, checksum_failed: None });
+                }
+                if options.fail_fast && output_writer.failure().is_some() {
+                    return output_writer.finish_batch(results, &options, total);
+                }
+                
+                log_message(&format!("\n>>> Processing {}/{}: {}", idx + 1, total, file_path.display()));
+                
+                let mut process = || run_pipeline_image(file_path, pipeline, &mut sessions, &options, output_convention.as_deref(),
+                    &output_writer.sender, &ort_config, &model_cache);
+                match process().or_else(|e| retry_failed(options.retry_count, file_path, e, &mut process)) {
+                    Ok(result) => {
+                        log_message(&format!("✓ Success: {}", file_path.display()));
+                        progress.lock().unwrap().record(result.duration);
+                        results.push(result);
+                    }
+                    Err(e) => {
+                        log_error(&format!("✗ Failed to process {}: {}", file_path.display(), e));
+                        if options.fail_fast {
+                            let (results, _) = output_writer.finish(results, options.optimize_png);
+                            log_message(&format!("\n=== Batch Stopped: {}/{} completed before failure ===", results.len(), total));
+                            return Err(BatchError { completed: results, failed_on: file_path.clone(), error: e.to_string(), checksum_failed: None });
+                        }
+                    }
+                }
+            }
+            
+            return output_writer.finish_batch(results, &options, total);
+        }
+        
+        if options.split_gpu_cpu {
+            if options.batch_size > 1 {
+                log_message(&format!("Images per inference call ({}) is ignored when small images run on the CPU", options.batch_size));
+            }
+            let (cpu_jobs, gpu_jobs): (Vec<_>, Vec<_>) = files.iter()
+                .map(|path| (path.clone(), select_model(path)))
+                .partition(|(path, _)| image::image_dimensions(path)
+                    .is_ok_and(|(w, h)| w as u64 * h as u64 <= SPLIT_CPU_MAX_PIXELS));
+            
+            log_message(&format!("Split processing: {} image(s) on CPU, {} on GPU", cpu_jobs.len(), gpu_jobs.len()));
+            
+            let stop = std::sync::atomic::AtomicBool::new(false);
+            let run_worker = |jobs: Vec<(PathBuf, ModelInfo)>, device: ExecutionDevice| {
+                let mut sessions: HashMap<String, Session> = HashMap::new();
+                let mut completed = Vec::new();
+                
+                for (file_path, job_model) in jobs {
+                    if stop.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    wait_while_paused(&pause_flag, &cancel_token);
+                    if cancel_token.load(Ordering::Relaxed) {
+                        log_message(&format!("[{}] Cancelled at {}/{}", device, progress.lock().unwrap().current, total));
+                        break;
+                    }
+                    if let (ExecutionDevice::Gpu, Some(limit)) = (device, gpu_temp_limit) {
+                        wait_for_gpu_cooldown(&gpu_temp, limit);
+                    }
+                    if let Err(e) = disk_space.wait_for_space(&options.output_dir, DEFAULT_MIN_FREE_DISK_GB, &cancel_token) {
+                        stop.store(true, Ordering::Relaxed);
+                        return (completed, Some((file_path, e)));
+                    }
+                    // The failed write itself is reported by finish
+                    if options.fail_fast && output_writer.failure().is_some() {
+                        stop.store(true, Ordering::Relaxed);
+                        break;
+                    }
+                    
+                    let mut process = || if needs_streaming(&file_path, &job_model) {
+                        process_streaming_png(&file_path, &job_model, &options, output_convention.as_deref(), &ort_config, &model_cache)
+                    } else {
+                        cached_session(&mut sessions, &job_model, device, &ort_config, &model_cache).and_then(|session| {
+                            let prepared = prepare_input(&file_path, &job_model, &options)?;
+                            run_prepared_image(&file_path, prepared, &job_model, session, &options, output_convention.as_deref(), Some(&output_writer.sender))
+                        })
+                    };
+                    let result = process().or_else(|e| retry_failed(options.retry_count, &file_path, e, &mut process));
+                    
+                    match result {
+                        Ok(result) => {
+                            log_message(&format!("✓ [{}] Success: {}", device, file_path.display()));
+                            progress.lock().unwrap().record(result.duration);
+                            completed.push(result);
+                        }
+                        Err(e) => {
+                            log_error(&format!("✗ [{}] Failed to process {}: {}", device, file_path.display(), e));
+                            if options.fail_fast {
+                                stop.store(true, Ordering::Relaxed);
+                                return (completed, Some((file_path, e.to_string())));
+                            }
+                        }
+                    }
+                }
+                
+                (completed, None)
+            };
+            
+            let ((cpu_results, cpu_failure), (gpu_results, gpu_failure)) = rayon::join(
+                || run_worker(cpu_jobs, ExecutionDevice::Cpu),
+                || run_worker(gpu_jobs, ExecutionDevice::Gpu),
+            );
+            results.extend(cpu_results);
+            results.extend(gpu_results);
+            let (results, write_failure) = output_writer.finish(results, options.optimize_png);
+            
+            if let Some((failed_on, error)) = cpu_failure.or(gpu_failure).or(write_failure.filter(|_| options.fail_fast)) {
+                log_message(&format!("\n=== Batch Stopped: {}/{} completed before failure ===", results.len(), total));
+                return Err(BatchError { completed: results, failed_on, error, checksum_failed: None });
+            }
+            
+            log_message(&format!("\n=== Batch Complete: {}/{} successful ===", results.len(), total));
+            return Ok(results);
+        }
+        
+        if options.batch_size > 1 {
+            let mut sessions: HashMap<String, Session> = HashMap::new();
+            
+            for (chunk_idx, chunk) in files.chunks(options.batch_size).enumerate() {
+                wait_while_paused(&pause_flag, &cancel_token);
+                if cancel_token.load(Ordering::Relaxed) {
+                    log_message(&format!("Cancelled at {}/{}", chunk_idx * options.batch_size, total));
+                    break;
+                }
+                if let Some(limit) = gpu_temp_limit {
+                    wait_for_gpu_cooldown(&gpu_temp, limit);
+                }
+                if let Err(error) = disk_space.wait_for_space(&options.output_dir, DEFAULT_MIN_FREE_DISK_GB, &cancel_token) {
+                    let (results, _) = output_writer.finish(results, options.optimize_png);
+                    log_message(&format!("\n=== Batch Stopped: {}/{} completed before abort ===", results.len(), total));
+                    return Err(BatchError { completed: results, failed_on: chunk[0].clone(), error, checksum_failed: None });
+                }
+                if options.fail_fast && output_writer.failure().is_some() {
+                    return output_writer.finish_batch(results, &options, total);
+                }
+                
+                let first = chunk_idx * options.batch_size;
+                log_message(&format!("\n>>> Processing {}-{}/{}", first + 1, first + chunk.len(), total));
+                
+                let jobs: Vec<(PathBuf, ModelInfo)> = chunk.iter()
+                    .map(|path| (path.clone(), select_model(path)))
+                    .collect();
+                let chunk_results = process_chunk(&jobs, &mut sessions, &options, output_convention.as_deref(), &ort_config, &model_cache, &output_writer.sender);
+                
+                for ((file_path, _), result) in jobs.into_iter().zip(chunk_results) {
+                    match result {
+                        Ok(result) => {
+                            log_message(&format!("✓ Success: {}", file_path.display()));
+                            progress.lock().unwrap().record(result.duration);
+                            results.push(result);
+                        }
+                        Err(e) => {
+                            log_error(&format!("✗ Failed to process {}: {}", file_path.display(), e));
+                            if options.fail_fast {
+                                let (results, _) = output_writer.finish(results, options.optimize_png);
+                                log_message(&format!("\n=== Batch Stopped: {}/{} completed before failure ===", results.len(), total));
+                                return Err(BatchError { completed: results, failed_on: file_path, error: e.to_string(), checksum_failed: None });
+                            }
+                        }
+                    }
+                }
+            }
+            
+            return output_writer.finish_batch(results, &options, total);
+        }
+        
+        // Sessions are built once per model up front and reused for every image in the loop
+        let mut sessions: HashMap<String, Session> = HashMap::new();
+        cached_session(&mut sessions, &model, ExecutionDevice::Gpu, &ort_config, &model_cache).map_err(|e| {
+            log_error(&format!("Failed to create session for {}: {}", model.name, e));
+            e.to_string()
+        })?;
+        
+        // Image N+1 is loaded and preprocessed while image N runs inference
+        let mut prefetch_pipeline: Option<JoinHandle<Result<PreparedInput>>> = None;
+        let mut next_model = files.first().map(|path| select_model(path));
+        
+        for (idx, file_path) in files.iter().enumerate() {
+            wait_while_paused(&pause_flag, &cancel_token);
+            // The prefetch thread for this image is left to finish on its own
+            if cancel_token.load(Ordering::Relaxed) {
+                log_message(&format!("Cancelled at {}/{}", idx, total));
+                break;
+            }
+            if let Some(limit) = gpu_temp_limit {
+                wait_for_gpu_cooldown(&gpu_temp, limit);
+            }
+            if let Err(error) = disk_space.wait_for_space(&options.output_dir, DEFAULT_MIN_FREE_DISK_GB, &cancel_token) {
+                let (results, _) = output_writer.finish(results, options.optimize_png);
+                log_message(&format!("\n=== Batch Stopped: {}/{} completed before abort ===", results.len(), total));
+                return Err(BatchError { completed: results, failed_on: file_path.clone(), error, checksum_failed: None });
+            }
+            if options.fail_fast && output_writer.failure().is_some() {
+                return output_writer.finish_batch(results, &options, total);
+            }
+            
+            log_message(&format!("\n>>> Processing {}/{}: {}", idx + 1, total, file_path.display()));
+            
+            let image_model = next_model.take().unwrap_or_else(|| model.clone());
+            
+            let streaming = needs_streaming(file_path, &image_model);
+            let prepared = match prefetch_pipeline.take() {
+                Some(handle) => Some(handle.join()
+                    .unwrap_or_else(|_| Err(anyhow::anyhow!("Prefetch thread panicked")))),
+                None if streaming => None,
+                None => Some(prepare_input(file_path, &image_model, &options)),
+            };
+            
+            if let Some(next_path) = files.get(idx + 1) {
+                let prefetch_model = select_model(next_path);
+                next_model = Some(prefetch_model.clone());
+                
+                // Streamed images are read row by row at processing time, nothing to prefetch
+                if !needs_streaming(next_path, &prefetch_model) {
+                    let prefetch_path = next_path.clone();
+                    let prefetch_options = options.clone();
+                    prefetch_pipeline = Some(std::thread::spawn(move || {
+                        prepare_input(&prefetch_path, &prefetch_model, &prefetch_options)
+                    }));
+                }
+            }
+            
+            let mut run = |prepared: PreparedInput| {
+                let session = cached_session(&mut sessions, &image_model, ExecutionDevice::Gpu, &ort_config, &model_cache)?;
+                run_prepared_image(file_path, prepared, &image_model, session, &options, output_convention.as_deref(), Some(&output_writer.sender))
+            };
+            let result = match prepared {
+                Some(prepared) => prepared.and_then(&mut run).or_else(|e| retry_failed(options.retry_count, file_path, e, || {
+                    run(prepare_input(file_path, &image_model, &options)?)
+                })),
+                None => process_streaming_png(file_path, &image_model, &options, output_convention.as_deref(), &ort_config, &model_cache)
+                    .or_else(|e| retry_failed(options.retry_count, file_path, e, || {
+                        process_streaming_png(file_path, &image_model, &options, output_convention.as_deref(), &ort_config, &model_cache)
+                    })),
+            };
+            
+            match result {
+                Ok(result) => {
+                    log_message(&format!("✓ Success: {} -> {}", 
+                        file_path.file_name().unwrap_or_default().to_string_lossy(),
+                        result.output_path.file_name().unwrap_or_default().to_string_lossy()));
+                    
+                    progress.lock().unwrap().record(result.duration);
+                    results.push(result);
+                },
+                Err(e) => {
+                    log_error(&format!("✗ Failed to process {}: {}", file_path.display(), e));
+                    
+                    if options.fail_fast {
+                        let (results, _) = output_writer.finish(results, options.optimize_png);
+                        log_message(&format!("\n=== Batch Stopped: {}/{} completed before failure ===", results.len(), total));
+                        return Err(BatchError {
+                            completed: results,
+                            failed_on: file_path.clone(),
+                            error: e.to_string(),
+                            checksum_failed: None,
+                        });
+                    }
+                    // Continue processing other images instead of stopping
+                }
+            }
+        }
+        
+        let results = output_writer.finish_batch(results, &options, total)?;
+        if options.optimize_png {
+            let saved: u64 = results.iter().map(|r| r.bytes_saved).sum();
+            log_message(&format!("PNG optimization saved {} across the batch", format_file_size(saved)));
+        }
+        Ok(results)
+    })
+    .await
+    .map_err(|e| {
+        log_error(&format!("Task join error: {}", e));
+        e.to_string()
+    })?
+}
+
+// One batch_size chunk: inputs are prepared in parallel, stacked into a single inference
+// call when possible, then finished and queued for saving image by image
+fn process_chunk(
+    jobs: &[(PathBuf, ModelInfo)],
+    sessions: &mut HashMap<String, Session>,
+    opts: &ProcessOptions,
+    output_convention: Option<&str>,
+    ort_config: &OrtConfig,
+    model_cache: &ModelCache,
+    output_writer: &mpsc::Sender<SaveRequest>,
+) -> Vec<Result<ProcessResult>> {
+    // Streamed images are read row by row at processing time, None = process_streaming_png
+    let mut prepared: Vec<Option<Result<PreparedInput>>> = jobs.par_iter()
+        .map(|(path, job_model)| (!needs_streaming(path, job_model))
+            .then(|| prepare_input(path, job_model, opts)))
+        .collect();
+    
+    if let Err(e) = run_stacked_batch(jobs, &mut prepared, sessions, ort_config, model_cache) {
+        log_error(&format!("Batched inference failed, running images one by one: {}", e));
+    }
+    
+    jobs.iter().zip(prepared)
+        .map(|((path, job_model), prepared)| {
+            let mut run = |prepared: Option<Result<PreparedInput>>| match prepared {
+                Some(prepared) => {
+                    let prepared = prepared?;
+                    let session = cached_session(sessions, job_model, ExecutionDevice::Gpu, ort_config, model_cache)?;
+                    run_prepared_image(path, prepared, job_model, session, opts, output_convention, Some(output_writer))
+                }
+                None => process_streaming_png(path, job_model, opts, output_convention, ort_config, model_cache),
+            };
+            // Retries start over from the file, the stacked inference isn't repeated
+            let streaming = prepared.is_none();
+            run(prepared).or_else(|e| retry_failed(opts.retry_count, path, e, || {
+                run((!streaming).then(|| prepare_input(path, job_model, opts)))
+            }))
+        })
+        .collect()
+}
+
+// Stacks the chunk's tensors into one [N, C, H, W] input and splits the output back per image.
+// Only done when every image is a whole-image tensor of the same shape for the same model and
+// the model's batch axis is dynamic; otherwise the inputs are left as they are.
+fn run_stacked_batch(
+    jobs: &[(PathBuf, ModelInfo)],
+    prepared: &mut [Option<Result<PreparedInput>>],
+    sessions: &mut HashMap<String, Session>,
+    ort_config: &OrtConfig,
+    model_cache: &ModelCache,
+) -> Result<()> {
+    let model = &jobs[0].1;
+    let tensors: Vec<&Array4<f32>> = prepared.iter()
+        .filter_map(|p| match p {
+            Some(Ok(PreparedInput { pixels: PreparedPixels::Tensor(tensor), .. })) => Some(tensor),
+            _ => None,
+        })
+        .collect();
+    
+    // YCbCr and channel-independent models already split the tensor themselves
+    let stackable = tensors.len() > 1
+        && tensors.len() == jobs.len()
+        && jobs.iter().all(|(_, m)| m.name == model.name)
+        && tensors.iter().all(|t| t.shape() == tensors[0].shape())
+        && !model.ycbcr_mode
+        && !model.channel_independent;
+    if !stackable {
+        return Ok(());
+    }
+    
+    let session = cached_session(sessions, model, ExecutionDevice::Gpu, ort_config, model_cache)?;
+    if !supports_dynamic_batch(session) {
+        log_message(&format!("Model {} has a fixed batch size, running images one by one", model.name));
+        return Ok(());
+    }
+    
+    let start = std::time::Instant::now();
+    let views: Vec<_> = tensors.iter().map(|t| t.view()).collect();
+    let stacked = ndarray::concatenate(Axis(0), &views)?;
+    log_message(&format!("Batched inference: {} images as {:?}", jobs.len(), stacked.shape()));
+    
+    let output = run_inference(session, stacked, model)?;
+    verify_output_tensor(&output, model)?;
+    let images = output.axis_iter(Axis(0))
+        .map(|image_output| postprocess_tensor_for_model(image_output.insert_axis(Axis(0)).to_owned(), model))
+        .collect::<Result<Vec<_>>>()?;
+    
+    // Each image is charged an equal share of the shared inference time
+    let secs_per_image = start.elapsed().as_secs_f32() / images.len() as f32;
+    for (prepared, image) in prepared.iter_mut().zip(images) {
+        if let Some(Ok(prepared)) = prepared {
+            prepared.pixels = PreparedPixels::Inferred(image);
+            prepared.prepare_secs += secs_per_image;
+        }
+    }
+    Ok(())
+}
+
+// Exporters mark a variable batch axis as -1
+fn supports_dynamic_batch(session: &Session) -> bool {
+    match &session.inputs[0].input_type {
+        ort::value::ValueType::Tensor { shape, .. } => shape.first().is_some_and(|&dim| dim < 0),
+        _ => false,
+    }
+}
 
-            let before_col = column![
-                text("Before").size(16).font(HEADING_FONT).style(TEXT_COLOR),
-                Space::with_height(8),
-                before_preview,
-                Space::with_height(8),
-                text(format!("{}×{}", w, h)).size(12).style(TEXT_SECONDARY)
-            ]
-            .spacing(0)
-            .align_items(Alignment::Center);
+// A queued output that failed to save: (input, output, error)
+type WriteFailure = (PathBuf, PathBuf, String);
 
-            let after_col = if let Some(after_img) = &self.after_image {
-                let (w, h) = after_img.dimensions();
-                let display_w = (w as f32 * self.zoom_level) as u32;
-                let display_h = (h as f32 * self.zoom_level) as u32;
+// Saves outputs on a dedicated thread so disk latency overlaps the next image's inference
+struct OutputWriter {
+    sender: mpsc::Sender<SaveRequest>,
+    failures: Arc<Mutex<Vec<WriteFailure>>>, // Filled in as writes fail, not only at finish
+    thread: JoinHandle<()>,
+}
 
-                let after_rgba = after_img.to_rgba8();
-                let after_handle = iced_image::Handle::from_pixels(
-                    w,
-                    h,
-                    after_rgba.into_raw()
-                );
+impl OutputWriter {
+    fn spawn() -> Self {
+        let (sender, receiver) = mpsc::channel::<SaveRequest>();
+        let failures = Arc::new(Mutex::new(Vec::new()));
+        
+        let thread_failures = failures.clone();
+        let thread = std::thread::spawn(move || {
+            for (input_path, output_path, image, format, metadata) in receiver {
+                match save_output(&image, &output_path, &format, metadata) {
+                    Ok(()) => log_message(&format!("Saved: {}", output_path.display())),
+                    Err(e) => {
+                        log_error(&format!("✗ Failed to save {}: {}", output_path.display(), e));
+                        thread_failures.lock().unwrap().push((input_path, output_path, e.to_string()));
+                    }
+                }
+            }
+        });
+        
+        Self { sender, failures, thread }
+    }
+    
+    // The first write that has failed so far as (input, error), so fail_fast can stop the batch
+    // at the next image instead of after the last one
+    fn failure(&self) -> Option<(PathBuf, String)> {
+        self.failures.lock().unwrap().first().map(|(input, _, error)| (input.clone(), error.clone()))
+    }
+    
+    // Waits for queued writes, then drops results whose output never reached disk and
+    // fills in sizes (and PNG optimization) that needed the written file. Also returns the
+    // first failed write as (input, error), the batch's failure when fail_fast is on.
+    fn finish(self, results: Vec<ProcessResult>, optimize_png: bool) -> (Vec<ProcessResult>, Option<(PathBuf, String)>) {
+        drop(self.sender);
+        if self.thread.join().is_err() {
+            log_error("Output writer thread panicked");
+        }
+        let failures = std::mem::take(&mut *self.failures.lock().unwrap());
+        
+        let results = results.into_iter()
+            .filter(|r| !failures.iter().any(|(_, path, _)| *path == r.output_path))
+            .map(|mut result| {
+                if result.data_uri.is_none() {
+                    result.output_bytes = fs::metadata(&result.output_path).map(|m| m.len()).unwrap_or(0);
+                    if optimize_png {
+                        apply_png_optimization(&mut result);
+                    }
+                }
+                result
+            })
+            .collect();
+        let failure = failures.into_iter().next().map(|(input, _, error)| (input, error));
+        (results, failure)
+    }
+    
+    // finish for a batch that wasn't stopped by anything else; with fail_fast a failed write
+    // stops it like a failed image would have
+    fn finish_batch(self, results: Vec<ProcessResult>, options: &ProcessOptions, total: usize) -> Result<Vec<ProcessResult>, BatchError> {
+        let (results, failure) = self.finish(results, options.optimize_png);
+        match failure.filter(|_| options.fail_fast) {
+            Some((failed_on, error)) => {
+                log_message(&format!("\n=== Batch Stopped: {}/{} completed before failure ===", results.len(), total));
+                Err(BatchError { completed: results, failed_on, error, checksum_failed: None })
+            }
+            None => {
+                log_message(&format!("\n=== Batch Complete: {}/{} successful ===", results.len(), total));
+                Ok(results)
+            }
+        }
+    }
+}
 
-                let after_preview = scrollable(
-                    container(
-                        iced_image::Image::new(after_handle)
-                            .width(Length::Fixed(display_w as f32))
-                            .height(Length::Fixed(display_h as f32))
-                    )
-                    .center_x()
-                    .center_y()
-                )
-                .direction(Direction::Both {
-                    vertical: Properties::default(),
-                    horizontal: Properties::default(),
-                })
-                .width(Length::FillPortion(1))
-                .height(Length::Fixed(400.0));
+#[derive(Debug, Clone)]
+struct BatchError {
+    completed: Vec<ProcessResult>,
+    failed_on: PathBuf, // Empty when the batch failed before reaching any image
+    error: String,
+    checksum_failed: Option<String>, // Model whose download failed SHA-256 verification
+}
 
-                column![
-                    text("After").size(16).font(HEADING_FONT).style(TEXT_COLOR),
-                    Space::with_height(8),
-                    after_preview,
-                    Space::with_height(8),
-                    text(format!("{}×{}", w, h)).size(12).style(TEXT_SECONDARY)
-                ]
-                .spacing(0)
-                .align_items(Alignment::Center)
-            } else {
-                column![
-                    text("After").size(16).font(HEADING_FONT).style(TEXT_COLOR),
-                    Space::with_height(8),
-                    container(text("Process to see result").style(TEXT_SECONDARY))
-                        .width(Length::Fixed(500.0))
-                        .height(Length::Fixed(400.0))
-                        .center_x()
-                        .center_y()
-                ]
-                .spacing(0)
-                .align_items(Alignment::Center)
-                .width(Length::FillPortion(1))
-            };
+impl From<String> for BatchError {
+    fn from(error: String) -> Self {
+        Self { completed: Vec::new(), failed_on: PathBuf::new(), error, checksum_failed: None }
+    }
+}
 
-            card_container(
-                column![
-                    row![
-                        section_title("Preview"),
-                        Space::with_width(Length::Fill),
-                        zoom_controls,
-                    ],
-                    Space::with_height(16),
-                    row![before_col, Space::with_width(20), after_col]
-                        .align_items(Alignment::Start),
-                ].spacing(0)
-            )
+impl std::fmt::Display for BatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.failed_on.as_os_str().is_empty() {
+            write!(f, "{}", self.error)
         } else {
-            card_container(
-                column![
-                    section_title("Preview"),
-                    Space::with_height(16),
-                    text("Select an image to preview").size(14).style(TEXT_SECONDARY)
-                ].spacing(0)
-            )
-        };
+            write!(f, "stopped on {} after {} image(s): {}",
+                self.failed_on.display(), self.completed.len(), self.error)
+        }
+    }
+}
 
-        let content = scrollable(
-            column![
-                header,
-                container(
-                    column![
-                        input_card,
-                        settings_card,
-                        preview_card,
-                        Space::with_height(20),
-                    ].spacing(16)
-                )
-                .width(Length::Fill)
-                .center_x()
-                .padding([6, 14, 6, 6])
-            ].spacing(0)
-        );
+const CLASSIFIER_INPUT_SIZE: u32 = 224;
 
-        container(content)
-            .width(Length::Fill)
-            .height(Length::Fill)
-            .style(theme::Container::Custom(Box::new(BackgroundContainer)))
-            .into()
-    }
+// Built like the upscaling sessions; the classifier is small enough that running it on the CPU
+// beats failing the batch when the GPU provider can't load it
+fn create_classifier_session(classifier: &ModelInfo, ort_config: &OrtConfig, model_cache: &ModelCache) -> Result<Session> {
+    log_message(&format!("Loading classifier: {}", classifier.name));
+    create_session(classifier, ExecutionDevice::Gpu, ort_config, model_cache).or_else(|e| {
+        log_error(&format!("⚠ Classifier failed to load on the GPU, falling back to CPU: {}", e));
+        create_session(classifier, ExecutionDevice::Cpu, ort_config, model_cache)
+    })
+}
 
-    fn theme(&self) -> Theme {
-        Theme::Light
+impl AutoSelect {
+    fn classify(&self, session: &mut Session, input_path: &Path) -> Result<ImageClass> {
+        let img = fast_open(input_path)?.resize_exact(
+            CLASSIFIER_INPUT_SIZE,
+            CLASSIFIER_INPUT_SIZE,
+            image::imageops::FilterType::Triangle,
+        );
+        
+        let input_value = Value::from_array(preprocess_image_for_model(&img, &self.classifier)?)?;
+        let input_name = session.inputs[0].name.to_string();
+        let output_name = output_name(session, self.classifier.output_index);
+        
+        let outputs = session.run(ort::inputs![input_name.as_str() => input_value])?;
+        let (_, logits) = outputs[output_name.as_str()].try_extract_tensor::<f32>()?;
+        
+        let best = logits.iter()
+            .take(ImageClass::ALL.len())
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(idx, _)| idx)
+            .ok_or_else(|| anyhow::anyhow!("Classifier returned no scores"))?;
+        
+        Ok(ImageClass::ALL[best])
+    }
+    
+    fn select_model(&self, session: &mut Session, input_path: &Path) -> Result<&ModelInfo> {
+        let class = self.classify(session, input_path)?;
+        let (_, model) = self.routes.iter()
+            .find(|(c, _)| *c == class)
+            .ok_or_else(|| anyhow::anyhow!("No model routed for {} images", class))?;
+        
+        log_message(&format!("Auto-selected {} for {} ({})", model.name, input_path.display(), class));
+        Ok(model)
     }
 }
 
-fn section_title(title: &str) -> Element<'static, Message> {
-    text(title)
-        .size(14)
-        .font(HEADING_FONT)
-        .style(TEXT_COLOR)
-        .into()
+// Polls the GPU temperature every GPU_TEMP_POLL_INTERVAL until the returned sender is dropped.
+// NVML ships with the NVIDIA driver on both Windows and Linux; other GPUs report no reading.
+fn spawn_gpu_temperature_monitor(current: Arc<AtomicU32>) -> Option<mpsc::Sender<()>> {
+    let nvml = match nvml_wrapper::Nvml::init() {
+        Ok(nvml) => nvml,
+        Err(e) => {
+            log_message(&format!("GPU temperature monitoring unavailable: {}", e));
+            return None;
+        }
+    };
+    
+    let (stop_tx, stop_rx) = mpsc::channel::<()>();
+    
+    std::thread::spawn(move || loop {
+        let temp = nvml.device_by_index(0)
+            .and_then(|device| device.temperature(nvml_wrapper::enum_wrappers::device::TemperatureSensor::Gpu));
+        
+        match temp {
+            Ok(temp) => {
+                current.store(temp, Ordering::Relaxed);
+                log_message(&format!("GPU temperature: {}°C", temp));
+            }
+            Err(e) => log_error(&format!("Failed to read GPU temperature: {}", e)),
+        }
+        
+        if !matches!(stop_rx.recv_timeout(GPU_TEMP_POLL_INTERVAL), Err(mpsc::RecvTimeoutError::Timeout)) {
+            break;
+        }
+    });
+    
+    Some(stop_tx)
 }
 
-fn card_container<'a>(content: impl Into<Element<'a, Message>>) -> Element<'a, Message> {
-    container(content)
-        .width(Length::Fill)
-        .padding(14)
-        .style(theme::Container::Custom(Box::new(CardContainer)))
-        .into()
+// Batch position shared between process_images and the status bar
+#[derive(Debug, Default)]
+struct ProgressState {
+    current: usize,
+    total: usize,
+    processed: usize, // current without the images skipped because their output existed
+    recent_durations: VecDeque<Duration>, // Last PROGRESS_AVERAGE_WINDOW successful images
 }
 
-struct BackgroundContainer;
-impl container::StyleSheet for BackgroundContainer {
-    type Style = Theme;
+impl ProgressState {
+    fn record(&mut self, duration_secs: f32) {
+        self.current += 1;
+        self.processed += 1;
+        self.recent_durations.push_back(Duration::from_secs_f32(duration_secs.max(0.0)));
+        if self.recent_durations.len() > PROGRESS_AVERAGE_WINDOW {
+            self.recent_durations.pop_front();
+        }
+    }
     
-    fn appearance(&self, _style: &Self::Style) -> container::Appearance {
-        container::Appearance {
-            background: Some(Background::Color(BACKGROUND_COLOR)),
-            ..Default::default()
+    fn average(&self) -> Duration {
+        match self.recent_durations.len() {
+            0 => Duration::ZERO,
+            n => self.recent_durations.iter().sum::<Duration>() / n as u32,
         }
     }
 }
 
-struct CardContainer;
-impl container::StyleSheet for CardContainer {
-    type Style = Theme;
-    
-    fn appearance(&self, _style: &Self::Style) -> container::Appearance {
-        container::Appearance {
-            background: Some(Background::Color(CARD_COLOR)),
-            border: iced::Border {
-                color: Color::from_rgba(0.0, 0.0, 0.0, 0.08),
-                width: 1.0,
-                radius: 12.0.into(),
-            },
-            ..Default::default()
-        }
+// "3m 42s", "1h 05m" or "12s"
+fn format_eta(remaining: Duration) -> String {
+    let secs = remaining.as_secs();
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m {}s", secs / 60, secs % 60),
+        _ => format!("{}h {:02}m", secs / 3600, secs % 3600 / 60),
     }
 }
 
-struct GradientContainer;
-impl container::StyleSheet for GradientContainer {
-    type Style = Theme;
-    
-    fn appearance(&self, _style: &Self::Style) -> container::Appearance {
-        container::Appearance {
-            background: Some(Background::Color(PRIMARY_COLOR)),
-            ..Default::default()
+// Called between images; Cancel also ends the wait so a paused batch can be stopped
+fn wait_while_paused(pause_flag: &AtomicBool, cancel_token: &AtomicBool) {
+    if pause_flag.load(Ordering::Relaxed) {
+        log_message("Batch paused");
+        while pause_flag.load(Ordering::Relaxed) && !cancel_token.load(Ordering::Relaxed) {
+            std::thread::sleep(PAUSE_POLL_INTERVAL);
         }
+        log_message("Batch resumed");
     }
 }
 
-// Add this logging function at the top level
-fn log_message(message: &str) {
-    let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S");
-    let log_entry = format!("[{}] {}\n", timestamp, message);
-    
-    // Print to console
-    println!("{}", log_entry.trim());
-    
-    // Write to log file
-    if let Ok(mut file) = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open("image_processor.log")
-    {
-        let _ = file.write_all(log_entry.as_bytes());
+fn wait_for_gpu_cooldown(current: &AtomicU32, limit: u32) {
+    loop {
+        let temp = current.load(Ordering::Relaxed);
+        if temp <= limit {
+            break;
+        }
+        
+        log_message(&format!("⚠ GPU temperature {}°C exceeds the {}°C limit, pausing for {}s",
+            temp, limit, GPU_COOLDOWN_PAUSE.as_secs()));
+        std::thread::sleep(GPU_COOLDOWN_PAUSE);
     }
 }
 
-fn log_error(message: &str) {
-    let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S");
-    let log_entry = format!("[{}] ERROR: {}\n", timestamp, message);
+fn free_disk_space_gb(path: &Path) -> Option<f64> {
+    use sysinfo::{DiskExt, RefreshKind, System, SystemExt};
     
-    eprintln!("{}", log_entry.trim());
+    // The output folder usually doesn't exist before the first image is written, so measure
+    // the nearest folder above it that does
+    let path = std::path::absolute(path).ok()?;
+    let path = path.ancestors().find_map(|dir| dir.canonicalize().ok())?;
+    let system = System::new_with_specifics(RefreshKind::new().with_disks_list());
     
-    if let Ok(mut file) = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open("image_processor.log")
-    {
-        let _ = file.write_all(log_entry.as_bytes());
-    }
+    // The drive holding the path is the one with the longest matching mount point
+    system.disks().iter()
+        .filter(|disk| path.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| disk.available_space() as f64 / (1024.0 * 1024.0 * 1024.0))
+}
+
+// Unknown free space (e.g. network shares sysinfo cannot see) is treated as enough
+fn check_disk_space(output_dir: &Path, min_free_gb: f64) -> bool {
+    free_disk_space_gb(output_dir).is_none_or(|free_gb| free_gb >= min_free_gb)
 }
 
-// FIXED: Correct normalization for different model types
-fn preprocess_image_for_model(img: &DynamicImage, model: &ModelInfo) -> Result<Array4<f32>> {
-    let rgb = img.to_rgb8();
-    let (w, h) = rgb.dimensions();
+// Shared between the batch workers, which pause on low space, and the UI, which decides how to go on.
+// One gate per batch; the split GPU/CPU path has two workers that can be paused at the same time.
+#[derive(Debug, Default)]
+struct DiskSpaceGate {
+    state: Mutex<DiskSpaceState>,
+}
+
+#[derive(Debug, Default)]
+struct DiskSpaceState {
+    low_free_gb: Option<f64>, // Shown in the prompt while a worker waits for an answer
+    resumes: u64,             // Bumped by every "continue", wakes all workers paused before it
+    aborted: bool,            // Sticky until the batch ends, so every worker stops
+}
+
+impl DiskSpaceGate {
+    fn pending_prompt(&self) -> Option<f64> {
+        let state = self.state.lock().unwrap();
+        state.low_free_gb.filter(|_| !state.aborted)
+    }
     
-    // Choose normalization function
-    let normalize_fn: Box<dyn Fn(u8) -> f32> = match model.input_norm {
-        NormalizationRange::MinusOneOne => {
-            log_message(&format!("Input normalization: [-1, 1] for model: {}", model.name));
-            Box::new(|val: u8| (val as f32 / 127.5) - 1.0)
-        }
-        NormalizationRange::ZeroOne => {
-            log_message(&format!("Input normalization: [0, 1] for model: {}", model.name));
-            Box::new(|val: u8| val as f32 / 255.0)
+    fn decide(&self, resume: bool) {
+        let mut state = self.state.lock().unwrap();
+        state.low_free_gb = None;
+        if resume {
+            state.resumes += 1;
+        } else {
+            state.aborted = true;
         }
-    };
+    }
     
-    // Create tensor based on format
-    let tensor = match model.tensor_format {
-        TensorFormat::NCHW => {
-            log_message(&format!("Creating NCHW tensor [1, 3, {}, {}]", h, w));
-            let mut tensor = Array4::<f32>::zeros((1, 3, h as usize, w as usize));
-            
-            for y in 0..h {
-                for x in 0..w {
-                    let p = rgb.get_pixel(x, y);
-                    tensor[[0, 0, y as usize, x as usize]] = normalize_fn(p[0]);
-                    tensor[[0, 1, y as usize, x as usize]] = normalize_fn(p[1]);
-                    tensor[[0, 2, y as usize, x as usize]] = normalize_fn(p[2]);
+    fn wait_for_space(&self, output_dir: &Path, min_free_gb: f64, cancel: &AtomicBool) -> Result<(), String> {
+        while !check_disk_space(output_dir, min_free_gb) {
+            let free_gb = free_disk_space_gb(output_dir).unwrap_or(0.0);
+            let paused_at = {
+                let mut state = self.state.lock().unwrap();
+                if state.aborted {
+                    return Err(format!("aborted with {:.2} GB free on the output drive", free_gb));
                 }
-            }
-            tensor
-        }
-        TensorFormat::NHWC => {
-            log_message(&format!("Creating NHWC tensor [1, {}, {}, 3]", h, w));
-            let mut tensor = Array4::<f32>::zeros((1, h as usize, w as usize, 3));
+                state.low_free_gb = Some(state.low_free_gb.map_or(free_gb, |shown| shown.min(free_gb)));
+                state.resumes
+            };
+            log_message(&format!("⚠ Only {:.2} GB free on the output drive, pausing until space is freed", free_gb));
             
-            for y in 0..h {
-                for x in 0..w {
-                    let p = rgb.get_pixel(x, y);
-                    tensor[[0, y as usize, x as usize, 0]] = normalize_fn(p[0]);
-                    tensor[[0, y as usize, x as usize, 1]] = normalize_fn(p[1]);
-                    tensor[[0, y as usize, x as usize, 2]] = normalize_fn(p[2]);
+            loop {
+                if cancel.load(Ordering::Relaxed) {
+                    return Err("cancelled while waiting for disk space".to_string());
+                }
+                let state = self.state.lock().unwrap();
+                if state.aborted {
+                    return Err(format!("aborted with {:.2} GB free on the output drive", free_gb));
                 }
+                if state.resumes != paused_at {
+                    break;
+                }
+                drop(state);
+                std::thread::sleep(DISK_SPACE_POLL_INTERVAL);
             }
-            tensor
         }
+        
+        Ok(())
+    }
+}
+
+const RATINGS_FILE: &str = "ratings.json";
+
+// Ratings live in the batch's output root, keyed by each output's path below it so outputs in
+// mirrored subfolders don't share a key; "photo_4x.png" for outputs directly in the root
+fn rating_key(output_root: &Path, output_path: &Path) -> Option<String> {
+    let relative = match output_path.strip_prefix(output_root) {
+        Ok(relative) => relative,
+        Err(_) => Path::new(output_path.file_name()?),
     };
+    Some(relative.iter().map(|c| c.to_string_lossy()).collect::<Vec<_>>().join("/"))
+}
+
+fn load_ratings(output_dir: &Path) -> HashMap<String, u8> {
+    fs::read_to_string(output_dir.join(RATINGS_FILE))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save_ratings(output_dir: &Path, results: &[ProcessResult]) -> Result<()> {
+    // Merge so ratings from earlier batches in the same folder are kept
+    let mut ratings = load_ratings(output_dir);
+    
+    for result in results {
+        let Some(key) = rating_key(output_dir, &result.output_path) else {
+            continue;
+        };
+        match result.rating {
+            Some(rating) => { ratings.insert(key, rating); }
+            None => { ratings.remove(&key); }
+        }
+    }
+    
+    fs::create_dir_all(output_dir)?;
+    fs::write(output_dir.join(RATINGS_FILE), serde_json::to_string_pretty(&ratings)?)?;
+    Ok(())
+}
+
+fn export_rated_results(results: &[ProcessResult], min_rating: u8, export_dir: &Path) -> Result<usize> {
+    fs::create_dir_all(export_dir)?;
     
-    // Diagnostic: Check tensor range
-    let shape = tensor.shape();
-    let mut min_val = f32::MAX;
-    let mut max_val = f32::MIN;
-    let mut sum = 0.0;
     let mut count = 0;
+    for result in results.iter().filter(|r| r.rating.unwrap_or(0) >= min_rating) {
+        if let Some(name) = result.output_path.file_name() {
+            fs::copy(&result.output_path, export_dir.join(name))?;
+            count += 1;
+        }
+    }
     
-    for idx in tensor.iter() {
-        min_val = min_val.min(*idx);
-        max_val = max_val.max(*idx);
-        sum += *idx;
-        count += 1;
+    log_message(&format!("Exported {} rated image(s) to {}", count, export_dir.display()));
+    Ok(count)
+}
+
+const BLOB_STORAGE_DOMAINS: [&str; 3] = [".amazonaws.com", "storage.googleapis.com", ".blob.core.windows.net"];
+
+#[derive(Debug, Clone)]
+enum CloudLocation {
+    S3 { bucket: String, key: String },
+    Https(String),
+}
+
+fn is_cloud_url(url: &str) -> bool {
+    if url.starts_with("s3://") || url.starts_with("gs://") {
+        return true;
     }
     
-    let mean = sum / count as f32;
-    log_message(&format!("🔍 Input tensor - shape: {:?}, range: [{:.4}, {:.4}], mean: {:.4}", 
-        shape, min_val, max_val, mean));
-    
-    Ok(tensor)
+    url.strip_prefix("https://")
+        .and_then(|rest| rest.split('/').next())
+        .is_some_and(|host| BLOB_STORAGE_DOMAINS.iter().any(|domain| host.ends_with(domain)))
 }
 
-// Enhanced postprocessing that handles both formats
-fn postprocess_tensor_for_model(tensor: Array4<f32>, model: &ModelInfo) -> Result<DynamicImage> {
-    let shape = tensor.shape();
-    
-    // Diagnostic: Check output tensor range
-    let mut min_val = f32::MAX;
-    let mut max_val = f32::MIN;
-    let mut sum = 0.0;
-    let mut count = 0;
-    
-    for val in tensor.iter() {
-        if val.is_finite() {
-            min_val = min_val.min(*val);
-            max_val = max_val.max(*val);
-            sum += *val;
-            count += 1;
-        }
+fn parse_cloud_url(url: &str) -> Result<CloudLocation> {
+    if let Some(rest) = url.strip_prefix("s3://") {
+        let (bucket, key) = rest.split_once('/').unwrap_or((rest, ""));
+        return Ok(CloudLocation::S3 { bucket: bucket.to_string(), key: key.to_string() });
     }
     
-    let mean = if count > 0 { sum / count as f32 } else { 0.0 };
-    log_message(&format!("🔍 Output tensor - shape: {:?}, range: [{:.4}, {:.4}], mean: {:.4}", 
-        shape, min_val, max_val, mean));
+    // gs:// has no native client here, go through the GCS XML API instead
+    if let Some(rest) = url.strip_prefix("gs://") {
+        return Ok(CloudLocation::Https(format!("https://storage.googleapis.com/{}", rest)));
+    }
     
-    // Check for invalid values
-    if !min_val.is_finite() || !max_val.is_finite() {
-        return Err(anyhow::anyhow!("Output tensor contains NaN or Inf values"));
+    if is_cloud_url(url) {
+        return Ok(CloudLocation::Https(url.to_string()));
     }
     
-    // Choose denormalization function
-    let denormalize_fn: Box<dyn Fn(f32) -> u8> = match model.output_norm {
-        NormalizationRange::MinusOneOne => {
-            log_message("Output denormalization: [-1, 1] → [0, 255]");
-            Box::new(|val: f32| ((val + 1.0) * 127.5).clamp(0.0, 255.0) as u8)
-        }
-        NormalizationRange::ZeroOne => {
-            log_message("Output denormalization: [0, 1] → [0, 255]");
-            Box::new(|val: f32| (val * 255.0).clamp(0.0, 255.0) as u8)
+    Err(anyhow::anyhow!("Unsupported cloud URL: {}", url))
+}
+
+fn cloud_temp_dir() -> PathBuf {
+    std::env::temp_dir().join("super_resolution_upscaler_cloud")
+}
+
+fn zip_temp_root() -> PathBuf {
+    std::env::temp_dir().join("super_resolution_upscaler_zip")
+}
+
+fn zip_temp_dir(zip_path: &Path) -> PathBuf {
+    let stem = zip_path.file_stem().and_then(|s| s.to_str()).unwrap_or("archive");
+    zip_temp_root().join(stem)
+}
+
+// Replaces any earlier extraction of the same archive; nothing is left behind on failure
+async fn extract_zip_in_background(zip_path: PathBuf) -> Result<(PathBuf, Vec<PathBuf>), String> {
+    tokio::task::spawn_blocking(move || {
+        let temp_dir = zip_temp_dir(&zip_path);
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        
+        match extract_zip_images(&zip_path, &temp_dir) {
+            Ok(files) => Ok((zip_path, files)),
+            Err(e) => {
+                let _ = std::fs::remove_dir_all(&temp_dir);
+                Err(e.to_string())
+            }
         }
-    };
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+async fn repack_zip_in_background(zip_path: PathBuf, outputs: Vec<(PathBuf, PathBuf)>) -> Result<PathBuf, String> {
+    tokio::task::spawn_blocking(move || repack_zip(&zip_path, &outputs).map_err(|e| e.to_string()))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+// Extracts only image entries into temp_dir, keeping their folders; enclosed_name() rejects
+// paths escaping it
+fn extract_zip_images(zip_path: &Path, temp_dir: &Path) -> Result<Vec<PathBuf>> {
+    let extensions = ["jpg", "jpeg", "png", "bmp", "webp"];
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(zip_path)?)?;
+    let mut files = Vec::new();
     
-    // Extract dimensions and create image based on format
-    match model.tensor_format {
-        TensorFormat::NCHW => {
-            let (_, _, h, w) = (shape[0], shape[1], shape[2], shape[3]);
-            log_message(&format!("Postprocessing NCHW: {}x{}", w, h));
-            let mut img = ImageBuffer::new(w as u32, h as u32);
-            
-            for y in 0..h {
-                for x in 0..w {
-                    let r = denormalize_fn(tensor[[0, 0, y, x]]);
-                    let g = denormalize_fn(tensor[[0, 1, y, x]]);
-                    let b = denormalize_fn(tensor[[0, 2, y, x]]);
-                    img.put_pixel(x as u32, y as u32, Rgb([r, g, b]));
-                }
-            }
-            Ok(DynamicImage::ImageRgb8(img))
+    std::fs::create_dir_all(temp_dir)?;
+    
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        if !entry.is_file() {
+            continue;
         }
-        TensorFormat::NHWC => {
-            let (_, h, w, _) = (shape[0], shape[1], shape[2], shape[3]);
-            log_message(&format!("Postprocessing NHWC: {}x{}", w, h));
-            let mut img = ImageBuffer::new(w as u32, h as u32);
-            
-            for y in 0..h {
-                for x in 0..w {
-                    let r = denormalize_fn(tensor[[0, y, x, 0]]);
-                    let g = denormalize_fn(tensor[[0, y, x, 1]]);
-                    let b = denormalize_fn(tensor[[0, y, x, 2]]);
-                    img.put_pixel(x as u32, y as u32, Rgb([r, g, b]));
-                }
-            }
-            Ok(DynamicImage::ImageRgb8(img))
+        
+        let Some(name) = entry.enclosed_name() else {
+            log_error(&format!("Skipping unsafe ZIP entry: {}", entry.name()));
+            continue;
+        };
+        let is_image = name.extension()
+            .and_then(|e| e.to_str())
+            .map(|e| extensions.contains(&e.to_lowercase().as_str()))
+            .unwrap_or(false);
+        if !is_image {
+            continue;
+        }
+        
+        let out_path = temp_dir.join(name);
+        if let Some(parent) = out_path.parent() {
+            std::fs::create_dir_all(parent)?;
         }
+        let mut out_file = std::fs::File::create(&out_path)?;
+        std::io::copy(&mut entry, &mut out_file)?;
+        files.push(out_path);
     }
+    
+    sort_naturally(&mut files);
+    log_message(&format!("Extracted {} image(s) from {}", files.len(), zip_path.display()));
+    Ok(files)
 }
 
-// IMPROVED: Better error handling in process_single_image
-fn process_single_image(
-    input_path: &Path,
-    model: &ModelInfo,
-    output_dir: &Path,
-) -> Result<ProcessResult> {
-    log_message(&format!("=== Processing: {} ===", input_path.display()));
-    log_message(&format!("Model: {} ({})", model.name, model.category));
+// Writes {stem}_processed.zip next to the source archive. outputs are (extracted input, output)
+// pairs; each output goes in the folder its input had inside the archive.
+fn repack_zip(zip_path: &Path, outputs: &[(PathBuf, PathBuf)]) -> Result<PathBuf> {
+    use std::io::Write;
     
-    let start = std::time::Instant::now();
+    let stem = zip_path.file_stem().and_then(|s| s.to_str()).unwrap_or("archive");
+    let archive_path = zip_path.with_file_name(format!("{}_processed.zip", stem));
+    let temp_dir = zip_temp_dir(zip_path);
+    let mut writer = zip::ZipWriter::new(std::fs::File::create(&archive_path)?);
+    let options = zip::write::SimpleFileOptions::default();
+    let mut written = HashSet::new();
     
-    let model_path = format!("./models/{}.onnx", model.name);
-    if !Path::new(&model_path).exists() {
-        log_message(&format!("Model not found locally, downloading: {}", model.name));
-        download_model(&model.url, &model_path).map_err(|e| {
-            log_error(&format!("Failed to download model: {}", e));
-            e
-        })?;
-        log_message("Model downloaded successfully");
-    }
-
-    log_message("Creating ONNX session...");
-    let mut session = Session::builder()
-        .map_err(|e| {
-            log_error(&format!("Failed to create session builder: {}", e));
-            e
-        })?
-        .with_optimization_level(ort::session::builder::GraphOptimizationLevel::Level3)
-        .map_err(|e| {
-            log_error(&format!("Failed to set optimization level: {}", e));
-            e
-        })?
-        .with_execution_providers([
-            ort::execution_providers::DirectMLExecutionProvider::default().build()
-        ])
-        .map_err(|e| {
-            log_error(&format!("Failed to set execution provider: {}", e));
-            e
-        })?
-        .commit_from_file(&model_path)
-        .map_err(|e| {
-            log_error(&format!("Failed to load model from {}: {}", model_path, e));
-            e
-        })?;
+    for (input, output) in outputs {
+        let Some(file_name) = output.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        let folder = input.parent()
+            .and_then(|parent| parent.strip_prefix(&temp_dir).ok())
+            .map(|relative| relative.iter().map(|c| c.to_string_lossy()).collect::<Vec<_>>())
+            .unwrap_or_default();
+        let name = folder.into_iter().chain([file_name.into()]).collect::<Vec<_>>().join("/");
+        
+        if !written.insert(name.clone()) {
+            log_error(&format!("Skipping duplicate ZIP entry: {}", name));
+            continue;
+        }
+        writer.start_file(name, options)?;
+        writer.write_all(&std::fs::read(output)?)?;
+    }
+    
+    writer.finish()?;
+    log_message(&format!("Repacked {} image(s) into {}", outputs.len(), archive_path.display()));
+    Ok(archive_path)
+}
 
-    log_message("Loading input image...");
-    let img = image::open(input_path).map_err(|e| {
-        log_error(&format!("Failed to open image: {}", e));
-        e
-    })?;
+fn s3_client(credentials: &CloudCredentials) -> aws_sdk_s3::Client {
+    use aws_sdk_s3::config::{BehaviorVersion, Credentials, Region};
     
-    let (orig_w, orig_h) = img.dimensions();
-    log_message(&format!("Original image size: {}x{}", orig_w, orig_h));
+    let region = if credentials.aws_region.is_empty() { "us-east-1" } else { &credentials.aws_region };
+    let mut builder = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new(region.to_string()))
+        .credentials_provider(Credentials::new(
+            &credentials.aws_access_key_id,
+            &credentials.aws_secret_access_key,
+            None,
+            None,
+            "config.toml",
+        ));
     
-    // Apply model-specific minimum dimension requirement
-    let min_dim = model.min_dimension.unwrap_or(0);
-    let max_dim = 512.max(min_dim); // Use at least the minimum, or 512
+    if let Some(endpoint) = &credentials.aws_endpoint_url {
+        builder = builder.endpoint_url(endpoint).force_path_style(true);
+    }
     
-    let img = if orig_w > max_dim || orig_h > max_dim || orig_w < min_dim || orig_h < min_dim {
-        // Need to resize - either too large or too small
-        let target_dim = if orig_w < min_dim || orig_h < min_dim {
-            // Too small - upscale to minimum
-            let scale = (min_dim as f32 / orig_w.min(orig_h) as f32).max(1.0);
-            let new_w = (orig_w as f32 * scale) as u32;
-            let new_h = (orig_h as f32 * scale) as u32;
-            log_message(&format!("Image too small, upscaling to {}x{} (scale: {:.2})", new_w, new_h, scale));
-            (new_w, new_h)
-        } else {
-            // Too large - downscale to max_dim
-            let scale = (max_dim as f32 / orig_w.max(orig_h) as f32).min(1.0);
-            let new_w = (orig_w as f32 * scale) as u32;
-            let new_h = (orig_h as f32 * scale) as u32;
-            log_message(&format!("Resizing to {}x{} (scale: {:.2})", new_w, new_h, scale));
-            (new_w, new_h)
-        };
-        
-        img.resize_exact(target_dim.0, target_dim.1, image::imageops::FilterType::Lanczos3)
-    } else {
-        img
-    };
+    aws_sdk_s3::Client::from_conf(builder.build())
+}
 
-    let (padded_img, padded_dims, (pad_r, pad_b)) = if model.window_size > 1 {
-        log_message(&format!("Padding to multiple of {}", model.window_size));
-        pad_to_multiple(&img, model.window_size)?
-    } else {
-        (img.clone(), img.dimensions(), (0, 0))
-    };
+// Adds whatever auth the configured credentials provide for an HTTPS blob URL
+fn authorize_blob_request(url: &str, request: reqwest::RequestBuilder, credentials: &CloudCredentials) -> reqwest::RequestBuilder {
+    if url.contains("storage.googleapis.com") {
+        if let Some(token) = &credentials.gcs_access_token {
+            return request.bearer_auth(token);
+        }
+    }
+    request
+}
 
-    log_message(&format!("Padded dimensions: {}x{} (pad_r: {}, pad_b: {})", 
-        padded_dims.0, padded_dims.1, pad_r, pad_b));
-    
-    // Verify dimensions are valid
-    if padded_dims.0 == 0 || padded_dims.1 == 0 {
-        return Err(anyhow::anyhow!("Invalid padded dimensions: {}x{}", padded_dims.0, padded_dims.1));
+fn blob_url_with_sas(url: &str, credentials: &CloudCredentials) -> String {
+    match &credentials.azure_sas_token {
+        Some(sas) if url.contains(".blob.core.windows.net") && !url.contains('?') => {
+            format!("{}?{}", url, sas.trim_start_matches('?'))
+        }
+        _ => url.to_string(),
     }
+}
 
-    log_message(&format!("Preprocessing image {}x{} for model: {}", 
-        padded_img.dimensions().0, padded_img.dimensions().1, model.name));
+fn url_file_name(url: &str) -> &str {
+    let path = url.split('?').next().unwrap_or(url);
+    path.rsplit('/').next().unwrap_or(path)
+}
 
-    log_message("Preprocessing image...");
-    let input_tensor = preprocess_image_for_model(&padded_img, model).map_err(|e| {
-        log_error(&format!("Preprocessing failed: {}", e));
-        e
-    })?;
- 
-    log_message("Creating ONNX input value...");
-    let input_value = Value::from_array(input_tensor).map_err(|e| {
-        log_error(&format!("Failed to create input value: {}", e));
-        e
-    })?;
-    
-    let input_name = session.inputs[0].name.to_string();
-    let output_name = session.outputs[0].name.to_string();
-    log_message(&format!("Model input: '{}', output: '{}'", input_name, output_name));
+// Objects under different prefixes often share a basename (day1/img.jpg, day2/img.jpg), so the
+// local copy carries a short hash of the whole URL: img-<hash>.jpg
+fn local_cloud_file_name(url: &str) -> String {
+    let name = Path::new(url_file_name(url));
+    let stem = name.file_stem().and_then(|s| s.to_str()).unwrap_or("image");
+    match name.extension().and_then(|e| e.to_str()) {
+        Some(ext) => format!("{}{}.{}", stem, cloud_url_suffix(url), ext),
+        None => format!("{}{}", stem, cloud_url_suffix(url)),
+    }
+}
 
-    log_message("Running inference...");
-    let outputs = session.run(ort::inputs![input_name.as_str() => input_value]).map_err(|e| {
-        log_error(&format!("Inference failed: {}", e));
-        e
-    })?;
+// Removed again before uploading, so outputs land next to their source under the original name
+fn cloud_url_suffix(url: &str) -> String {
+    use sha2::{Digest, Sha256};
+    
+    let hash = format!("{:x}", Sha256::digest(url.as_bytes()));
+    format!("-{}", &hash[..12])
+}
 
-    log_message("Extracting output tensor...");
-    let (output_shape, output_data) = outputs[output_name.as_str()]
-        .try_extract_tensor::<f32>()
-        .map_err(|e| {
-            log_error(&format!("Failed to extract tensor: {}", e));
-            e
-        })?;
+// Expands s3:// prefixes into their image objects; other URLs are taken as single files
+async fn expand_cloud_urls(urls: Vec<String>, credentials: &CloudCredentials) -> Result<Vec<String>> {
+    let extensions = ["jpg", "jpeg", "png", "bmp", "webp"];
+    let mut expanded = Vec::new();
     
-    let shape_vec = output_shape.as_ref().to_vec();
-    log_message(&format!("Output tensor shape: {:?}", shape_vec));
+    for url in urls {
+        match parse_cloud_url(&url)? {
+            CloudLocation::S3 { bucket, key } if key.is_empty() || key.ends_with('/') => {
+                // A listing returns at most 1000 keys, follow the continuation token for the rest
+                let client = s3_client(credentials);
+                let mut continuation_token = None;
+                loop {
+                    let listing = client
+                        .list_objects_v2()
+                        .bucket(&bucket)
+                        .prefix(&key)
+                        .set_continuation_token(continuation_token)
+                        .send()
+                        .await?;
+                    
+                    expanded.extend(listing.contents().iter()
+                        .filter_map(|object| object.key())
+                        .filter(|k| k.rsplit('.').next()
+                            .is_some_and(|ext| extensions.contains(&ext.to_lowercase().as_str())))
+                        .map(|k| format!("s3://{}/{}", bucket, k)));
+                    
+                    if !listing.is_truncated().unwrap_or(false) {
+                        break;
+                    }
+                    continuation_token = listing.next_continuation_token().map(String::from);
+                    if continuation_token.is_none() {
+                        break;
+                    }
+                }
+            }
+            _ => expanded.push(url),
+        }
+    }
     
-    let output_array = Array4::from_shape_vec(
-        (shape_vec[0] as usize, shape_vec[1] as usize, 
-         shape_vec[2] as usize, shape_vec[3] as usize),
-        output_data.to_vec()
-    ).map_err(|e| {
-        log_error(&format!("Failed to create output array: {}", e));
-        e
-    })?;
+    Ok(expanded)
+}
 
-    log_message("Postprocessing tensor...");
-    let mut final_img = postprocess_tensor_for_model(output_array, model).map_err(|e| {
-        log_error(&format!("Postprocessing failed: {}", e));
-        e
-    })?;
+async fn download_cloud_image(url: &str, temp_dir: &Path, credentials: &CloudCredentials) -> Result<PathBuf> {
+    let bytes = match parse_cloud_url(url)? {
+        CloudLocation::S3 { bucket, key } => {
+            let object = s3_client(credentials)
+                .get_object()
+                .bucket(bucket)
+                .key(key)
+                .send()
+                .await?;
+            object.body.collect().await?.into_bytes().to_vec()
+        }
+        CloudLocation::Https(https_url) => {
+            let request = reqwest::Client::new().get(blob_url_with_sas(&https_url, credentials));
+            authorize_blob_request(&https_url, request, credentials)
+                .send()
+                .await?
+                .error_for_status()?
+                .bytes()
+                .await?
+                .to_vec()
+        }
+    };
+    
+    let local_path = temp_dir.join(local_cloud_file_name(url));
+    fs::write(&local_path, bytes)?;
+    log_message(&format!("Downloaded {} -> {}", url, local_path.display()));
+    Ok(local_path)
+}
 
-    if pad_r > 0 || pad_b > 0 {
-        let target_w = img.dimensions().0 * model.scale;
-        let target_h = img.dimensions().1 * model.scale;
-        log_message(&format!("Cropping padding: target {}x{}", target_w, target_h));
-        final_img = final_img.crop_imm(0, 0, target_w, target_h);
-    }
+async fn download_cloud_inputs(
+    urls: Vec<String>,
+    temp_dir: PathBuf,
+    credentials: CloudCredentials,
+) -> Result<Vec<(PathBuf, String)>, String> {
+    let run = async {
+        if OFFLINE_MODE.load(Ordering::Relaxed) {
+            return Err(anyhow::anyhow!("Offline mode: cloud download disabled"));
+        }
+        fs::create_dir_all(&temp_dir)?;
+        
+        let mut downloads = Vec::new();
+        for url in expand_cloud_urls(urls, &credentials).await? {
+            let path = download_cloud_image(&url, &temp_dir, &credentials).await?;
+            downloads.push((path, url));
+        }
+        Ok::<_, anyhow::Error>(downloads)
+    };
     
-    let (out_w, out_h) = final_img.dimensions();
-    log_message(&format!("Final output size: {}x{}", out_w, out_h));
+    run.await.map_err(|e| {
+        log_error(&format!("Cloud download failed: {}", e));
+        e.to_string()
+    })
+}
 
-    let output_filename = input_path.file_stem()
+// Uploads next to the source object, keeping the local output filename
+async fn upload_cloud_output(output_path: &Path, source_url: &str, credentials: &CloudCredentials) -> Result<String> {
+    let file_name = output_path.file_name()
         .and_then(|n| n.to_str())
-        .unwrap_or("output");
+        .ok_or_else(|| anyhow::anyhow!("Invalid output path: {}", output_path.display()))?;
+    let file_name = &file_name.replacen(&cloud_url_suffix(source_url), "", 1);
+    let bytes = fs::read(output_path)?;
     
-    let suffix = match model.model_type {
-        ModelType::Upscaling | ModelType::Enhancement if model.scale > 1 => format!("_{}x", model.scale),
-        ModelType::Denoising => "_denoised".to_string(),
-        _ => "_enhanced".to_string(),
+    let destination = match parse_cloud_url(source_url)? {
+        CloudLocation::S3 { bucket, key } => {
+            let dest_key = match key.rsplit_once('/') {
+                Some((prefix, _)) => format!("{}/{}", prefix, file_name),
+                None => file_name.to_string(),
+            };
+            
+            s3_client(credentials)
+                .put_object()
+                .bucket(&bucket)
+                .key(&dest_key)
+                .body(aws_sdk_s3::primitives::ByteStream::from(bytes))
+                .send()
+                .await?;
+            format!("s3://{}/{}", bucket, dest_key)
+        }
+        CloudLocation::Https(https_url) => {
+            let (base, query) = match https_url.split_once('?') {
+                Some((base, query)) => (base, Some(query)),
+                None => (https_url.as_str(), None),
+            };
+            let dest_url = match base.rsplit_once('/') {
+                Some((prefix, _)) => format!("{}/{}", prefix, file_name),
+                None => return Err(anyhow::anyhow!("Cannot derive upload URL from {}", source_url)),
+            };
+            // A SAS token in the source URL is what grants access, the configured one is the fallback.
+            // Only dest_url is logged and returned, so the token stays out of the log.
+            let request_url = match query {
+                Some(query) => format!("{}?{}", dest_url, query),
+                None => blob_url_with_sas(&dest_url, credentials),
+            };
+            
+            let request = reqwest::Client::new()
+                .put(request_url)
+                .header("x-ms-blob-type", "BlockBlob")
+                .body(bytes);
+            authorize_blob_request(&dest_url, request, credentials)
+                .send()
+                .await?
+                .error_for_status()?;
+            dest_url
+        }
     };
     
-    let output_path = output_dir.join(format!("{}{}.png", output_filename, suffix));
-    
-    log_message(&format!("Saving to: {}", output_path.display()));
-    final_img.save(&output_path).map_err(|e| {
-        log_error(&format!("Failed to save image: {}", e));
-        e
-    })?;
-
-    let duration = start.elapsed().as_secs_f32();
-    log_message(&format!("✓ Completed in {:.2}s", duration));
-
-    Ok(ProcessResult {
-        input_path: input_path.to_path_buf(),
-        output_path,
-        input_dims: (orig_w, orig_h),
-        output_dims: (out_w, out_h),
-        duration,
-    })
+    log_message(&format!("Uploaded {} -> {}", output_path.display(), destination));
+    Ok(destination)
 }
 
-// Update process_images to use better error handling
-async fn process_images(
-    files: Vec<PathBuf>,
-    model: ModelInfo,
-    output_dir: PathBuf,
-) -> Result<Vec<ProcessResult>, String> {
-    tokio::task::spawn_blocking(move || {
-        log_message("Initializing ONNX Runtime...");
-        ort::init().commit().map_err(|e| {
-            log_error(&format!("Failed to initialize ONNX Runtime: {}", e));
-            e.to_string()
-        })?;
-        
-        std::fs::create_dir_all(&output_dir).map_err(|e| {
-            log_error(&format!("Failed to create output directory: {}", e));
+async fn upload_cloud_outputs(uploads: Vec<(PathBuf, String)>, credentials: CloudCredentials) -> Result<usize, String> {
+    if OFFLINE_MODE.load(Ordering::Relaxed) {
+        return Err("Offline mode: cloud upload disabled".to_string());
+    }
+    for (output_path, source_url) in &uploads {
+        upload_cloud_output(output_path, source_url, &credentials).await.map_err(|e| {
+            log_error(&format!("Cloud upload failed for {}: {}", output_path.display(), e));
             e.to_string()
         })?;
-        
-        let mut results = Vec::new();
-        let total = files.len();
-        
-        for (idx, file_path) in files.iter().enumerate() {
-            log_message(&format!("\n>>> Processing {}/{}: {}", idx + 1, total, file_path.display()));
-            
-            match process_single_image(&file_path, &model, &output_dir) {
-                Ok(result) => {
-                    log_message(&format!("✓ Success: {} -> {}", 
-                        file_path.file_name().unwrap_or_default().to_string_lossy(),
-                        result.output_path.file_name().unwrap_or_default().to_string_lossy()));
-                    results.push(result);
-                },
-                Err(e) => {
-                    log_error(&format!("✗ Failed to process {}: {}", file_path.display(), e));
-                    // Continue processing other images instead of stopping
-                }
-            }
-        }
-        
-        log_message(&format!("\n=== Batch Complete: {}/{} successful ===", results.len(), total));
-        Ok(results)
-    })
-    .await
-    .map_err(|e| {
-        log_error(&format!("Task join error: {}", e));
-        e.to_string()
-    })?
+    }
+    Ok(uploads.len())
 }
 
-fn pad_to_multiple(img: &DynamicImage, multiple: u32) -> Result<(DynamicImage, (u32, u32), (u32, u32))> {
-    let (w, h) = img.dimensions();
-    let pad_w = ((w + multiple - 1) / multiple) * multiple;
-    let pad_h = ((h + multiple - 1) / multiple) * multiple;
-    let pad_r = pad_w - w;
-    let pad_b = pad_h - h;
+// Blocking wrapper around download_model_async for batch workers and the CLI, which have no
+// progress bar to feed
+fn download_model(url: &str, path: &Path, expected_sha256: Option<&str>, speed_limit_kbps: Option<u32>) -> Result<()> {
+    let (progress_tx, _) = iced::futures::channel::mpsc::channel(1);
+    let download = download_model_async(url, path, expected_sha256, speed_limit_kbps, progress_tx);
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) => handle.block_on(download),
+        // Rayon workers run outside any runtime
+        Err(_) => tokio::runtime::Builder::new_current_thread().enable_all().build()?.block_on(download),
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct DownloadProgress {
+    bytes_received: u64,
+    total_bytes: Option<u64>, // None when the server sends no Content-Length
+}
+
+// Every chunk is reported through progress_tx, the log gets a line every 10%
+async fn download_model_async(
+    url: &str,
+    path: &Path,
+    expected_sha256: Option<&str>,
+    speed_limit_kbps: Option<u32>,
+    mut progress_tx: iced::futures::channel::mpsc::Sender<DownloadProgress>,
+) -> Result<()> {
+    use iced::futures::StreamExt;
+    use tokio::io::AsyncWriteExt;
+    
+    if url == "local" { return Ok(()); }
+    if OFFLINE_MODE.load(Ordering::Relaxed) {
+        return Err(anyhow::anyhow!("Offline mode: download disabled"));
+    }
+    
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(600))
+        .user_agent("image-enhancement-tool/1.0")
+        .build()?;
+    
+    log_message(&format!("Downloading from: {}", url));
+    let tmp = path.with_extension("part");
+    let validator_path = path.with_extension("part.validator");
+    let existing = part_file_len(&tmp);
+    let mut request = client.get(url);
+    if existing > 0 {
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", existing));
+        // If the file changed on the server since the .part was started, it comes back whole as a 200
+        if let Ok(validator) = tokio::fs::read_to_string(&validator_path).await {
+            request = request.header(reqwest::header::IF_RANGE, validator);
+        }
+    }
+    let mut resp = request.send().await?;
+    let resumable = resp.status() != reqwest::StatusCode::PARTIAL_CONTENT || content_range_starts_at(&resp, existing);
+    if resp.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE || !resumable {
+        // The .part is no prefix of the file on the server (or already complete), fetch it again
+        log_message("Server can't resume from the partial download, starting over");
+        tokio::fs::remove_file(&tmp).await?;
+        resp = client.get(url).send().await?;
+    }
     
-    if pad_r == 0 && pad_b == 0 {
-        return Ok((img.clone(), (w, h), (0, 0)));
+    if !resp.status().is_success() {
+        return Err(anyhow::anyhow!("HTTP {} for {}", resp.status(), url));
     }
     
-    let mut padded = ImageBuffer::new(pad_w, pad_h);
-    let rgb = img.to_rgb8();
+    let resumed_from = resumed_offset(resp.status(), existing);
+    let total_bytes = resp.content_length().map(|len| len + resumed_from);
+    let mut out = tokio::fs::File::from_std(open_part_file(&tmp, resumed_from)?);
+    if resumed_from == 0 {
+        match resume_validator(&resp) {
+            Some(validator) => tokio::fs::write(&validator_path, validator).await?,
+            None => { let _ = tokio::fs::remove_file(&validator_path).await; }
+        }
+    }
     
-    for y in 0..pad_h {
-        for x in 0..pad_w {
-            let src_x = if x < w { x } else { w - 1 - (x - w).min(w - 1) };
-            let src_y = if y < h { y } else { h - 1 - (y - h).min(h - 1) };
-            padded.put_pixel(x, y, *rgb.get_pixel(src_x, src_y));
+    let start = std::time::Instant::now();
+    let mut bytes_received: u64 = resumed_from;
+    let mut last_logged_percent = 0;
+    let mut stream = resp.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        out.write_all(&chunk).await?;
+        bytes_received += chunk.len() as u64;
+        // A full buffer only means the UI is behind, the next chunk reports a newer count anyway
+        progress_tx.try_send(DownloadProgress { bytes_received, total_bytes }).ok();
+        
+        if let Some(total) = total_bytes.filter(|&t| t > 0) {
+            let percent = bytes_received * 100 / total;
+            if percent >= last_logged_percent + 10 {
+                last_logged_percent = percent - percent % 10;
+                log_message(&format!("Downloaded {} of {} ({}%)",
+                    format_file_size(bytes_received), format_file_size(total), percent));
+            }
+        }
+        
+        if let Some(limit) = speed_limit_kbps.filter(|&l| l > 0) {
+            let expected = Duration::from_secs_f64((bytes_received - resumed_from) as f64 / (limit as f64 * 1024.0));
+            if let Some(ahead) = expected.checked_sub(start.elapsed()) {
+                tokio::time::sleep(ahead).await;
+            }
         }
     }
+    out.flush().await?;
+    drop(out);
     
-    Ok((DynamicImage::ImageRgb8(padded), (pad_w, pad_h), (pad_r, pad_b)))
+    // Hashing a large model would stall the executor thread
+    let path = path.to_path_buf();
+    let expected_sha256 = expected_sha256.map(String::from);
+    tokio::task::spawn_blocking(move || finish_download(&tmp, &path, expected_sha256.as_deref())).await??;
+    let _ = tokio::fs::remove_file(&validator_path).await;
+    log_message(&format!("Model downloaded successfully: {}", format_file_size(bytes_received)));
+    
+    Ok(())
 }
 
-fn download_model(url: &str, path_str: &str) -> Result<()> {
-    if url == "local" { return Ok(()); }
-    
-    let path = Path::new(path_str);
-    
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)?;
-    }
+// Size of a .part file left by an interrupted download, 0 when there is none
+fn part_file_len(tmp: &Path) -> u64 {
+    fs::metadata(tmp).map(|m| m.len()).unwrap_or(0)
+}
 
-    let client = reqwest::blocking::Client::builder()
-        .timeout(Duration::from_secs(600))
-        .user_agent("image-enhancement-tool/1.0")
-        .build()?;
+// 206 means the server honoured the Range header; a 200 carries the whole file again
+fn resumed_offset(status: reqwest::StatusCode, existing: u64) -> u64 {
+    if status == reqwest::StatusCode::PARTIAL_CONTENT { existing } else { 0 }
+}
 
-    println!("Downloading from: {}", url);
-    let mut resp = client.get(url).send()?;
+// A 206 has to start where the .part ends, anything else would splice the wrong bytes in
+fn content_range_starts_at(resp: &reqwest::Response, offset: u64) -> bool {
+    resp.headers().get(reqwest::header::CONTENT_RANGE)
+        .and_then(|range| range.to_str().ok())
+        .is_some_and(|range| range.starts_with(&format!("bytes {}-", offset)))
+}
 
-    if !resp.status().is_success() {
-        return Err(anyhow::anyhow!("HTTP {} for {}", resp.status(), url));
+// If-Range only accepts a strong ETag, Last-Modified is the fallback
+fn resume_validator(resp: &reqwest::Response) -> Option<String> {
+    let headers = resp.headers();
+    headers.get(reqwest::header::ETAG)
+        .and_then(|etag| etag.to_str().ok())
+        .filter(|etag| !etag.starts_with("W/"))
+        .or_else(|| headers.get(reqwest::header::LAST_MODIFIED)?.to_str().ok())
+        .map(String::from)
+}
+
+// Appends to the .part when resuming, truncates it when starting over
+fn open_part_file(tmp: &Path, resumed_from: u64) -> io::Result<fs::File> {
+    if resumed_from > 0 {
+        log_message(&format!("Resuming download at {}", format_file_size(resumed_from)));
     }
+    OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed_from > 0)
+        .truncate(resumed_from == 0)
+        .open(tmp)
+}
 
-    let tmp = path.with_extension("part");
-    let mut out = fs::File::create(&tmp)?;
+// Verifies the .part file and moves it into place
+fn finish_download(tmp: &Path, path: &Path, expected_sha256: Option<&str>) -> Result<()> {
+    // A truncated or corrupted file would otherwise only fail later as an ORT parse error
+    if let Some(expected) = expected_sha256 {
+        let actual = sha256_file(tmp)?;
+        if !actual.eq_ignore_ascii_case(expected) {
+            let _ = fs::remove_file(tmp);
+            return Err(ChecksumMismatch {
+                file_name: path.file_name().unwrap_or_default().to_string_lossy().into_owned(),
+                expected: expected.to_string(),
+                actual,
+            }.into());
+        }
+        log_message(&format!("Checksum verified: {}", actual));
+    }
 
-    io::copy(&mut resp, &mut out)?;
+    fs::rename(tmp, path)?;
+    Ok(())
+}
 
-    fs::rename(&tmp, path)?;
+fn sha256_file(path: &Path) -> Result<String> {
+    use sha2::{Digest, Sha256};
     
-    println!("Model saved to: {}", path.display());
+    let mut hasher = Sha256::new();
+    io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
 
-    Ok(())
+#[derive(Debug)]
+struct ChecksumMismatch {
+    file_name: String,
+    expected: String,
+    actual: String,
 }
 
-fn preprocess_image(img: &DynamicImage) -> Result<Array4<f32>> {
-    let rgb = img.to_rgb8();
-    let (w, h) = rgb.dimensions();
-    let mut tensor = Array4::<f32>::zeros((1, 3, h as usize, w as usize));
-    
-    for y in 0..h {
-        for x in 0..w {
-            let p = rgb.get_pixel(x, y);
-            tensor[[0, 0, y as usize, x as usize]] = p[0] as f32 / 255.0;
-            tensor[[0, 1, y as usize, x as usize]] = p[1] as f32 / 255.0;
-            tensor[[0, 2, y as usize, x as usize]] = p[2] as f32 / 255.0;
+impl std::fmt::Display for ChecksumMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} failed checksum verification (expected {}, got {}), the download was deleted",
+            self.file_name, self.expected, self.actual)
+    }
+}
+
+impl std::error::Error for ChecksumMismatch {}
+
+// Per-run settings for process_video
+#[derive(Debug, Clone)]
+struct VideoProcessOptions {
+    segment_duration: Option<f32>, // Seconds per segment, None = process in one pass
+    deinterlace: bool,
+    start_frame: Option<u64>, // First source frame to upscale, None = from the start
+    end_frame: Option<u64>, // Last source frame (inclusive), None = to the end
+    resume: bool, // Keep frames a checkpoint says are done, single-pass runs only
+    crf: u8, // 0-51, lower is higher quality and a larger file
+    target_bitrate_kbps: Option<u32>, // Some = encode to a -b:v target instead of CRF
+    video_codec: Option<VideoCodec>, // None = first of libx264, h264, mpeg4 that ffmpeg has
+    preserve_subtitles: bool, // Stream-copy the source's subtitle tracks, switches the output to Matroska
+}
+
+impl Default for VideoProcessOptions {
+    fn default() -> Self {
+        VideoProcessOptions {
+            segment_duration: None,
+            deinterlace: false,
+            start_frame: None,
+            end_frame: None,
+            resume: false,
+            crf: DEFAULT_VIDEO_CRF,
+            target_bitrate_kbps: None,
+            video_codec: None,
+            preserve_subtitles: false,
         }
     }
-    
-    Ok(tensor)
 }
 
-fn postprocess_tensor(tensor: Array4<f32>) -> Result<DynamicImage> {
-    let shape = tensor.shape();
-    let (_, _, h, w) = (shape[0], shape[1], shape[2], shape[3]);
-    let mut img = ImageBuffer::new(w as u32, h as u32);
+impl VideoProcessOptions {
+    fn has_frame_range(&self) -> bool {
+        self.start_frame.is_some() || self.end_frame.is_some()
+    }
     
-    for y in 0..h {
-        for x in 0..w {
-            let r = (tensor[[0, 0, y, x]] * 255.0).clamp(0.0, 255.0) as u8;
-            let g = (tensor[[0, 1, y, x]] * 255.0).clamp(0.0, 255.0) as u8;
-            let b = (tensor[[0, 2, y, x]] * 255.0).clamp(0.0, 255.0) as u8;
-            img.put_pixel(x as u32, y as u32, Rgb([r, g, b]));
+    // MP4 only holds mov_text subtitles, so copying SRT, ASS or PGS tracks needs Matroska
+    fn output_extension(&self) -> &'static str {
+        if self.preserve_subtitles { "mkv" } else { "mp4" }
+    }
+}
+
+// Written next to the upscaled frames so an interrupted run can skip what it finished
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct VideoCheckpoint {
+    video: PathBuf,
+    model_name: String,
+    deinterlace: bool,
+    start_frame: Option<u64>,
+    end_frame: Option<u64>,
+    total_frames: usize,
+    completed: BTreeSet<String>, // File names of extracted frames whose upscale is on disk
+}
+
+impl VideoCheckpoint {
+    fn new(video_path: &Path, model: &ModelInfo, options: &VideoProcessOptions, total_frames: usize) -> Self {
+        VideoCheckpoint {
+            video: video_path.to_path_buf(),
+            model_name: model.name.clone(),
+            deinterlace: options.deinterlace,
+            start_frame: options.start_frame,
+            end_frame: options.end_frame,
+            total_frames,
+            completed: BTreeSet::new(),
         }
     }
     
-    Ok(DynamicImage::ImageRgb8(img))
+    fn load(temp_upscaled: &Path) -> Option<Self> {
+        let contents = std::fs::read_to_string(temp_upscaled.join(VIDEO_CHECKPOINT_FILE)).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+    
+    // Renamed into place so a kill mid-write leaves the previous checkpoint intact
+    fn save(&self, temp_upscaled: &Path) -> Result<(), String> {
+        let path = temp_upscaled.join(VIDEO_CHECKPOINT_FILE);
+        let tmp = path.with_extension("json.tmp");
+        let contents = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        std::fs::write(&tmp, contents).map_err(|e| e.to_string())?;
+        std::fs::rename(&tmp, &path).map_err(|e| e.to_string())
+    }
+    
+    // Anything that changes which frames are extracted or how they look invalidates it
+    fn matches(&self, video_path: &Path, model: &ModelInfo, options: &VideoProcessOptions) -> bool {
+        self.video == video_path
+            && self.model_name == model.name
+            && self.deinterlace == options.deinterlace
+            && self.start_frame == options.start_frame
+            && self.end_frame == options.end_frame
+    }
 }
 
 async fn process_video(
     video_path: PathBuf,
     model: ModelInfo,
+    options: VideoProcessOptions,
+    ort_config: OrtConfig,
+    model_cache: ModelCache,
+    download_speed_limit_kbps: Option<u32>,
 ) -> Result<String, String> {
     tokio::task::spawn_blocking(move || {
-        process_video_blocking(&video_path, &model)
+        ensure_model_file(&model, &model_cache, download_speed_limit_kbps).map_err(|e| e.to_string())?;
+        process_video_blocking(&video_path, &model, &options, &ort_config, &model_cache)
     })
     .await
     .map_err(|e| e.to_string())?
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VideoCodec {
+    Libx264,
+    Libx265,
+    LibvpxVp9,
+    LibaomAv1,
+}
+
+impl VideoCodec {
+    fn all() -> Vec<VideoCodec> {
+        vec![VideoCodec::Libx264, VideoCodec::Libx265, VideoCodec::LibvpxVp9, VideoCodec::LibaomAv1]
+    }
+    
+    fn encoder_name(&self) -> &'static str {
+        match self {
+            VideoCodec::Libx264 => "libx264",
+            VideoCodec::Libx265 => "libx265",
+            VideoCodec::LibvpxVp9 => "libvpx-vp9",
+            VideoCodec::LibaomAv1 => "libaom-av1",
+        }
+    }
+    
+    fn from_encoder_name(name: &str) -> Option<VideoCodec> {
+        VideoCodec::all().into_iter().find(|codec| codec.encoder_name() == name)
+    }
+    
+    fn is_fast(&self) -> bool {
+        *self == VideoCodec::Libx264
+    }
+    
+    // Speed preset plus CRF or bitrate rate control for this encoder
+    fn encoder_args(&self, options: &VideoProcessOptions) -> Vec<String> {
+        let mut args: Vec<String> = match self {
+            VideoCodec::Libx264 | VideoCodec::Libx265 => vec!["-preset".into(), "medium".into()],
+            VideoCodec::LibvpxVp9 => vec!["-deadline".into(), "good".into(), "-row-mt".into(), "1".into()],
+            VideoCodec::LibaomAv1 => vec!["-cpu-used".into(), "4".into(), "-row-mt".into(), "1".into()],
+        };
+        match options.target_bitrate_kbps {
+            Some(kbps) => args.extend(["-b:v".to_string(), format!("{}k", kbps)]),
+            None => {
+                args.extend(["-crf".to_string(), options.crf.to_string()]);
+                // libvpx and libaom only treat -crf as constant quality without a bitrate cap
+                if matches!(self, VideoCodec::LibvpxVp9 | VideoCodec::LibaomAv1) {
+                    args.extend(["-b:v".to_string(), "0".to_string()]);
+                }
+            }
+        }
+        // QuickTime and Safari only play HEVC in MP4 with the hvc1 tag
+        if *self == VideoCodec::Libx265 {
+            args.extend(["-tag:v".to_string(), "hvc1".to_string()]);
+        }
+        args
+    }
+}
+
+impl std::fmt::Display for VideoCodec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let speed = if self.is_fast() { "fast" } else { "slow, smaller" };
+        write!(f, "{} ({})", self.encoder_name(), speed)
+    }
+}
+
+// Asks ffmpeg for its encoder list once instead of running it per codec
+fn probe_video_codecs() -> Vec<VideoCodec> {
+    let Ok(output) = ProcessCommand::new("ffmpeg").args(["-hide_banner", "-encoders"]).output() else {
+        return Vec::new();
+    };
+    let encoders = String::from_utf8_lossy(&output.stdout);
+    let names: HashSet<&str> = encoders.lines()
+        .filter_map(|line| line.split_whitespace().nth(1))
+        .collect();
+    VideoCodec::all().into_iter().filter(|codec| names.contains(codec.encoder_name())).collect()
+}
+
+async fn detect_video_codecs() -> Vec<VideoCodec> {
+    tokio::task::spawn_blocking(probe_video_codecs)
+        .await
+        .unwrap_or_default()
+}
+
 fn check_codec_available(codec_name: &str) -> bool {
     ProcessCommand::new("ffmpeg")
-        .args(&["-codecs"])
+        .args(["-codecs"])
         .output()
         .map(|output| {
             let codecs_list = String::from_utf8_lossy(&output.stdout);
@@ -1792,19 +9659,18 @@ fn check_codec_available(codec_name: &str) -> bool {
 fn process_video_blocking(
     video_path: &Path,
     model: &ModelInfo,
+    options: &VideoProcessOptions,
+    ort_config: &OrtConfig,
+    model_cache: &ModelCache,
 ) -> Result<String, String> {
-    // Create temporary directories
-    let temp_frames = PathBuf::from("./temp_frames");
-    let temp_upscaled = PathBuf::from("./temp_upscaled");
-    
-    std::fs::create_dir_all(&temp_frames).map_err(|e| e.to_string())?;
-    std::fs::create_dir_all(&temp_upscaled).map_err(|e| e.to_string())?;
+    let temp_frames = PathBuf::from(VIDEO_TEMP_FRAMES_DIR);
+    let temp_upscaled = PathBuf::from(VIDEO_TEMP_UPSCALED_DIR);
     
 	// Configure Rayon thread pool for GPU processing
     // For GPU-based inference, fewer threads often work better
     // This uses 1/2 of CPU cores, or minimum of 2, max of 8
     let num_cpus = num_cpus::get();
-    let optimal_threads = (num_cpus / 2).max(2).min(8);
+    let optimal_threads = (num_cpus / 2).clamp(2, 8);
     
     rayon::ThreadPoolBuilder::new()
         .num_threads(4)
@@ -1812,17 +9678,235 @@ fn process_video_blocking(
         .ok(); // Ignore error if already initialized
     
     println!("Using {} parallel threads for video processing", optimal_threads);
+    
+    // Initialize ONNX Runtime
+    ort::init().commit().map_err(|e| e.to_string())?;
+    
+    let output_path = video_path
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join(format!(
+            "{}_upscaled.{}",
+            video_path.file_stem().and_then(|s| s.to_str()).unwrap_or("output"),
+            options.output_extension()
+        ));
+    
+    match probe_field_order(video_path) {
+        Some(order) if order != "progressive" && order != "unknown" => {
+            println!("Source is interlaced (field_order={}), deinterlacing: {}", order, options.deinterlace);
+        }
+        Some(order) => println!("Source field_order={}", order),
+        None => println!("Could not determine source field order"),
+    }
+    
+    // A frame range is a short test run, and segment boundaries would renumber its frames
+    match options.segment_duration.filter(|_| !options.has_frame_range()) {
+        Some(segment_secs) => process_video_segments(video_path, &output_path, model, segment_secs, options, ort_config, model_cache, &temp_frames)?,
+        None => upscale_video_file(video_path, &output_path, model, options, ort_config, model_cache, &temp_frames, &temp_upscaled)?,
+    }
+    
+    // Only reached on success, a failed run leaves its frames and checkpoint for Resume
+    let _ = std::fs::remove_dir_all(&temp_frames);
+    let _ = std::fs::remove_dir_all(&temp_upscaled);
+    
+    Ok(output_path.to_string_lossy().to_string())
+}
+
+// Splits the video with ffmpeg's segment muxer, upscales MAX_PARALLEL_SEGMENTS pieces at a time
+// and joins the results with the concat demuxer
+#[allow(clippy::too_many_arguments)]
+fn process_video_segments(
+    video_path: &Path,
+    output_path: &Path,
+    model: &ModelInfo,
+    segment_secs: f32,
+    options: &VideoProcessOptions,
+    ort_config: &OrtConfig,
+    model_cache: &ModelCache,
+    temp_frames: &Path,
+) -> Result<(), String> {
+    let segments_dir = temp_frames.join("segments");
+    let upscaled_segments_dir = temp_frames.join("upscaled_segments");
+    // Segment work dirs are deleted as it goes, so there is nothing to resume from.
+    // Segments carry no subtitles, those are copied from the source when joining
+    let segment_options = VideoProcessOptions { resume: false, preserve_subtitles: false, ..options.clone() };
+    
+    std::fs::create_dir_all(&segments_dir).map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&upscaled_segments_dir).map_err(|e| e.to_string())?;
+    
+    println!("Splitting video into {:.0}s segments...", segment_secs);
+    
+    // Stream copy cuts on keyframes, so segments are only approximately segment_secs long
+    let split_status = ProcessCommand::new("ffmpeg")
+        .args([
+            "-y",
+            "-i", video_path.to_str().unwrap(),
+            "-map", "0:v:0",
+            "-map", "0:a?",
+            "-c", "copy",
+            "-f", "segment",
+            "-segment_time", &format!("{:.3}", segment_secs),
+            "-reset_timestamps", "1",
+            &format!("{}/segment_%04d.mkv", segments_dir.display()),
+        ])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|e| format!("Failed to run ffmpeg: {}. Make sure ffmpeg is installed.", e))?;
+    
+    if !split_status.success() {
+        return Err("Failed to split video into segments".to_string());
+    }
+    
+    let mut segment_files: Vec<PathBuf> = std::fs::read_dir(&segments_dir)
+        .map_err(|e| e.to_string())?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("mkv"))
+        .collect();
+    
+    sort_naturally(&mut segment_files);
+    
+    if segment_files.is_empty() {
+        return Err("No segments produced from video".to_string());
+    }
+    
+    use std::sync::atomic::AtomicUsize;
+    
+    let total = segment_files.len();
+    let segment_name = |idx: usize| format!("segment_{:04}.mp4", idx);
+    
+    // Each worker takes the next unprocessed segment; after a failure no new segment is started
+    let next_segment = AtomicUsize::new(0);
+    let failure: Mutex<Option<String>> = Mutex::new(None);
+    std::thread::scope(|scope| {
+        for _ in 0..MAX_PARALLEL_SEGMENTS.min(total) {
+            scope.spawn(|| loop {
+                let idx = next_segment.fetch_add(1, Ordering::Relaxed);
+                if idx >= total || failure.lock().unwrap().is_some() {
+                    break;
+                }
+                println!("Processing segment {}/{}...", idx + 1, total);
+                
+                let segment_work_dir = temp_frames.join(format!("work_{:04}", idx));
+                let result = upscale_video_file(
+                    &segment_files[idx],
+                    &upscaled_segments_dir.join(segment_name(idx)),
+                    model,
+                    &segment_options,
+                    ort_config,
+                    model_cache,
+                    &segment_work_dir.join("frames"),
+                    &segment_work_dir.join("upscaled"),
+                );
+                
+                // Free the frame images before taking the next segment
+                let _ = std::fs::remove_dir_all(&segment_work_dir);
+                if let Err(e) = result {
+                    failure.lock().unwrap().get_or_insert(format!("Segment {}/{}: {}", idx + 1, total, e));
+                }
+            });
+        }
+    });
+    if let Some(e) = failure.into_inner().unwrap() {
+        return Err(e);
+    }
+    
+    // Paths in the concat list are resolved relative to the list file
+    let concat_list: String = (0..total)
+        .map(|idx| format!("file 'upscaled_segments/{}'\n", segment_name(idx)))
+        .collect();
+    
+    let concat_path = temp_frames.join("concat.txt");
+    std::fs::write(&concat_path, concat_list).map_err(|e| e.to_string())?;
+    
+    println!("Joining {} segments...", total);
+    
+    let mut concat_args = vec![
+        "-y",
+        "-f", "concat",
+        "-safe", "0",
+        "-i", concat_path.to_str().unwrap(),
+    ];
+    if options.preserve_subtitles {
+        concat_args.extend(["-i", video_path.to_str().unwrap(), "-map", "0", "-map", "1:s?"]);
+    }
+    concat_args.extend(["-c", "copy"]);
+    if !options.preserve_subtitles {
+        concat_args.extend(["-movflags", "+faststart"]);
+    }
+    concat_args.push(output_path.to_str().unwrap());
+    
+    let concat_output = ProcessCommand::new("ffmpeg")
+        .args(&concat_args)
+        .output()
+        .map_err(|e| format!("Failed to run ffmpeg: {}. Make sure ffmpeg is installed.", e))?;
+    
+    if !concat_output.status.success() {
+        let stderr = String::from_utf8_lossy(&concat_output.stderr);
+        eprintln!("FFmpeg error output:\n{}", stderr);
+        return Err(format!("Failed to join video segments. FFmpeg error:\n{}", stderr));
+    }
+    
+    Ok(())
+}
+
+// Returns ffprobe's field_order for the first video stream ("progressive", "tt", "bb", ...)
+fn probe_field_order(video_path: &Path) -> Option<String> {
+    let output = ProcessCommand::new("ffprobe")
+        .args([
+            "-v", "error",
+            "-select_streams", "v:0",
+            "-show_entries", "stream=field_order",
+            "-of", "default=noprint_wrappers=1:nokey=1",
+            video_path.to_str()?,
+        ])
+        .output()
+        .ok()?;
+    
+    let order = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!order.is_empty()).then_some(order)
+}
+
+fn extract_frames(video_path: &Path, options: &VideoProcessOptions, temp_frames: &Path) -> Result<(), String> {
+    let deinterlace = options.deinterlace;
     println!("Extracting frames from video...");
     
+    let mut extract_args = vec!["-i".to_string(), video_path.to_str().unwrap().to_string()];
+    
+    // Frames are selected by source index, before yadif doubles the count
+    let mut filters = Vec::new();
+    if options.has_frame_range() {
+        let start = options.start_frame.unwrap_or(0);
+        let expr = match options.end_frame {
+            Some(end) => format!("between(n\\,{}\\,{})", start, end),
+            None => format!("gte(n\\,{})", start),
+        };
+        println!("Extracting frames {} to {}", start, options.end_frame.map_or("end".to_string(), |end| end.to_string()));
+        filters.push(format!("select='{}'", expr));
+    }
+    // yadif mode=1 outputs one frame per field, doubling the frame count
+    if deinterlace {
+        filters.push("yadif=mode=1".to_string());
+    }
+    if !filters.is_empty() {
+        extract_args.extend(["-vf".to_string(), filters.join(",")]);
+    }
+    // select drops frames, keep ffmpeg from duplicating others to fill the gaps
+    if options.has_frame_range() {
+        extract_args.extend(["-fps_mode".to_string(), "passthrough".to_string()]);
+    }
+    
+    extract_args.extend([
+        "-qscale:v".to_string(), "1".to_string(),
+        "-qmin".to_string(), "1".to_string(),
+        "-qmax".to_string(), "1".to_string(),
+        format!("{}/frame_%06d.png", temp_frames.display()),
+    ]);
+    
     // Extract frames using ffmpeg
     let extract_status = ProcessCommand::new("ffmpeg")
-        .args(&[
-            "-i", video_path.to_str().unwrap(),
-            "-qscale:v", "1",
-            "-qmin", "1",
-            "-qmax", "1",
-            &format!("{}/frame_%06d.png", temp_frames.display())
-        ])
+        .args(&extract_args)
         .stdout(Stdio::null())
         .stderr(Stdio::null())
         .status()
@@ -1831,58 +9915,129 @@ fn process_video_blocking(
     if !extract_status.success() {
         return Err("Failed to extract frames from video".to_string());
     }
-    
-    // Get list of extracted frames
-    let mut frame_files: Vec<PathBuf> = std::fs::read_dir(&temp_frames)
+    Ok(())
+}
+
+fn list_frame_files(temp_frames: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut frame_files: Vec<PathBuf> = std::fs::read_dir(temp_frames)
         .map_err(|e| e.to_string())?
         .filter_map(|entry| entry.ok())
         .map(|entry| entry.path())
         .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("png"))
         .collect();
     
-    frame_files.sort();
+    sort_naturally(&mut frame_files);
+    Ok(frame_files)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn upscale_video_file(
+    video_path: &Path,
+    output_path: &Path,
+    model: &ModelInfo,
+    options: &VideoProcessOptions,
+    ort_config: &OrtConfig,
+    model_cache: &ModelCache,
+    temp_frames: &Path,
+    temp_upscaled: &Path,
+) -> Result<(), String> {
+    let deinterlace = options.deinterlace;
+    
+    let resumed = if options.resume {
+        VideoCheckpoint::load(temp_upscaled).filter(|checkpoint| checkpoint.matches(video_path, model, options))
+    } else {
+        None
+    };
+    match &resumed {
+        Some(checkpoint) => println!("Resuming: {}/{} frames already upscaled", checkpoint.completed.len(), checkpoint.total_frames),
+        None => {
+            // Leftover frames from another run would end up in this video
+            let _ = std::fs::remove_dir_all(temp_frames);
+            let _ = std::fs::remove_dir_all(temp_upscaled);
+        }
+    }
+    std::fs::create_dir_all(temp_frames).map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(temp_upscaled).map_err(|e| e.to_string())?;
+    
+    // Extraction is only repeated if the interrupted run didn't finish it
+    let mut frame_files = list_frame_files(temp_frames)?;
+    if resumed.as_ref().is_none_or(|checkpoint| frame_files.len() != checkpoint.total_frames) {
+        extract_frames(video_path, options, temp_frames)?;
+        frame_files = list_frame_files(temp_frames)?;
+    }
     
     if frame_files.is_empty() {
         return Err("No frames extracted from video".to_string());
     }
     
-    println!("Processing {} frames in parallel...", frame_files.len());
+    let checkpoint = resumed
+        .filter(|checkpoint| checkpoint.total_frames == frame_files.len())
+        .unwrap_or_else(|| VideoCheckpoint::new(video_path, model, options, frame_files.len()));
+    let pending: Vec<&PathBuf> = frame_files.iter()
+        .filter(|path| !path.file_name().and_then(|n| n.to_str()).is_some_and(|n| checkpoint.completed.contains(n)))
+        .collect();
+    checkpoint.save(temp_upscaled)?;
     
-    // Initialize ONNX Runtime
-    ort::init().commit().map_err(|e| e.to_string())?;
+    println!("Processing {} frames in parallel...", pending.len());
     
     // Use atomic counter for progress tracking across threads
     use std::sync::atomic::{AtomicUsize, Ordering};
-    let processed = AtomicUsize::new(0);
+    let processed = AtomicUsize::new(checkpoint.completed.len());
     let total = frame_files.len();
+    let checkpoint = Mutex::new((checkpoint, std::time::Instant::now()));
     
 	// Process frames IN PARALLEL using rayon
-    frame_files.par_iter().for_each(|frame_path| {
-        match process_single_image(frame_path, model, &temp_upscaled) {
-            Ok(_) => {
-                let count = processed.fetch_add(1, Ordering::Relaxed) + 1;
-                if count % 10 == 0 || count == total {
-                    println!("Processing frame {}/{}...", count, total);
-                }
-            },
-            Err(e) => eprintln!("Error processing frame: {}", e),
-        }
-    });
+    let frame_options = ProcessOptions {
+        output_dir: temp_upscaled.to_path_buf(),
+        ..ProcessOptions::default()
+    };
+    // One session per rayon worker rather than per frame
+    pending.par_iter().for_each_init(
+        || create_session(model, ExecutionDevice::Gpu, ort_config, model_cache).map(|mut session| {
+            if let Err(e) = warmup_session(&mut session, model) {
+                log_error(&format!("Session warm-up failed, continuing without it: {}", e));
+            }
+            session
+        }),
+        |session, frame_path| {
+            let result = session.as_mut()
+                .map_err(|e| anyhow::anyhow!("Failed to create session: {}", e))
+                .and_then(|session| process_single_image(session, frame_path, model, &frame_options));
+            match result {
+                Ok(_) => {
+                    let count = processed.fetch_add(1, Ordering::Relaxed) + 1;
+                    if count.is_multiple_of(10) || count == total {
+                        println!("Processing frame {}/{}...", count, total);
+                    }
+                    let mut guard = checkpoint.lock().unwrap();
+                    let (checkpoint, last_saved) = &mut *guard;
+                    if let Some(name) = frame_path.file_name().and_then(|n| n.to_str()) {
+                        checkpoint.completed.insert(name.to_string());
+                    }
+                    if last_saved.elapsed() >= VIDEO_CHECKPOINT_INTERVAL {
+                        if let Err(e) = checkpoint.save(temp_upscaled) {
+                            log_error(&format!("Failed to save video checkpoint: {}", e));
+                        }
+                        *last_saved = std::time::Instant::now();
+                    }
+                },
+                Err(e) => eprintln!("Error processing frame: {}", e),
+            }
+        },
+    );
     
-    println!("Reassembling video...");
+    let (checkpoint, _) = checkpoint.into_inner().unwrap();
+    checkpoint.save(temp_upscaled)?;
+    if checkpoint.completed.len() < total {
+        return Err(format!("{} of {} frames failed to upscale, Resume retries them",
+            total - checkpoint.completed.len(), total));
+    }
     
-    // Get video properties for output
-    let output_path = video_path
-        .parent()
-        .unwrap_or(Path::new("."))
-        .join(format!(
-            "{}_upscaled.mp4",
-            video_path.file_stem().and_then(|s| s.to_str()).unwrap_or("output")
-        ));
+    println!("Reassembling video...");
     
     // Get original FPS - handle fractional framerates properly
     let fps_output = ProcessCommand::new("ffprobe")
-        .args(&[
+        .args([
             "-v", "error",
             "-select_streams", "v:0",
             "-show_entries", "stream=r_frame_rate",
@@ -1913,6 +10068,17 @@ fn process_video_blocking(
         fps_str
     };
     
+    let source_fps = fps.parse::<f64>().ok();
+    
+    // Deinterlaced output has a frame per field, so play it back at twice the rate
+    let fps = if deinterlace {
+        fps.parse::<f64>()
+            .map(|rate| format!("{:.3}", rate * 2.0))
+            .unwrap_or(fps)
+    } else {
+        fps
+    };
+    
     println!("Video framerate: {} fps", fps);
     
     // Determine output suffix based on model type
@@ -1924,7 +10090,7 @@ fn process_video_blocking(
     
     // Check if audio stream exists
     let has_audio = ProcessCommand::new("ffprobe")
-        .args(&[
+        .args([
             "-v", "error",
             "-select_streams", "a:0",
             "-show_entries", "stream=codec_type",
@@ -1942,9 +10108,14 @@ fn process_video_blocking(
         "-y".to_string(), // Overwrite output file
         "-framerate".to_string(), fps.clone(),
         "-i".to_string(), format!("{}/frame_%06d{}.png", temp_upscaled.display(), suffix),
-        "-i".to_string(), video_path.to_str().unwrap().to_string(), // Always add original video
     ];
     
+    // Line the audio up with the first extracted frame
+    if let (Some(start), Some(rate)) = (options.start_frame.filter(|&f| f > 0), source_fps) {
+        ffmpeg_args.extend(["-ss".to_string(), format!("{:.3}", start as f64 / rate)]);
+    }
+    ffmpeg_args.extend(["-i".to_string(), video_path.to_str().unwrap().to_string()]); // Always add original video
+    
     // Map video from processed frames
     ffmpeg_args.extend([
         "-map".to_string(), "0:v:0".to_string(),
@@ -1978,8 +10149,18 @@ fn process_video_blocking(
         println!("No audio track found in source video - creating video-only output");
     }
     
+    // ? keeps ffmpeg from failing on sources without subtitles
+    if options.preserve_subtitles {
+        ffmpeg_args.extend([
+            "-map".to_string(), "1:s?".to_string(),
+            "-c:s".to_string(), "copy".to_string(),
+        ]);
+    }
+    
     // Video encoding settings with codec detection
-    let video_encoder = if check_codec_available("libx264") {
+    let video_encoder = if let Some(codec) = options.video_codec {
+        codec.encoder_name()
+    } else if check_codec_available("libx264") {
         "libx264"
     } else if check_codec_available("h264") {
         "h264"
@@ -1993,11 +10174,12 @@ fn process_video_blocking(
         "-c:v".to_string(), video_encoder.to_string(),
     ]);
     
-    // Only add x264-specific settings if using libx264
-    if video_encoder == "libx264" {
+    // A bitrate target works with every encoder, CRF only with the ones in VideoCodec
+    if let Some(codec) = VideoCodec::from_encoder_name(video_encoder) {
+        ffmpeg_args.extend(codec.encoder_args(options));
+    } else if let Some(kbps) = options.target_bitrate_kbps {
         ffmpeg_args.extend([
-            "-preset".to_string(), "medium".to_string(),
-            "-crf".to_string(), "18".to_string(),
+            "-b:v".to_string(), format!("{}k", kbps),
         ]);
     } else {
         // Generic quality settings for other codecs
@@ -2008,10 +10190,20 @@ fn process_video_blocking(
     
     ffmpeg_args.extend([
         "-pix_fmt".to_string(), "yuv420p".to_string(), // CRITICAL: Ensures compatibility
-        "-movflags".to_string(), "+faststart".to_string(), // Better for streaming/playback
         "-r".to_string(), fps,
-        output_path.to_str().unwrap().to_string(),
     ]);
+    // Only the MP4 muxer knows movflags
+    if output_path.extension().and_then(|e| e.to_str()) == Some("mp4") {
+        ffmpeg_args.extend([
+            "-movflags".to_string(), "+faststart".to_string(), // Better for streaming/playback
+        ]);
+    }
+    
+    // The audio runs past the last selected frame
+    if options.has_frame_range() {
+        ffmpeg_args.push("-shortest".to_string());
+    }
+    ffmpeg_args.push(output_path.to_str().unwrap().to_string());
     
     println!("Running ffmpeg with args: {:?}", ffmpeg_args);
     
@@ -2029,9 +10221,74 @@ fn process_video_blocking(
     
     println!("Video reassembly complete!");
     
-    // Cleanup temporary files
-    let _ = std::fs::remove_dir_all(&temp_frames);
-    let _ = std::fs::remove_dir_all(&temp_upscaled);
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
     
-    Ok(output_path.to_string_lossy().to_string())
-}
\ No newline at end of file
+    #[test]
+    fn render_template_rejects_unknown_variables() {
+        let vars = HashMap::from([("stem", "photo".to_string()), ("scale", "4".to_string())]);
+        
+        assert_eq!(render_template("{stem}_{scale}x}", &vars).unwrap(), "photo_4x}");
+        assert!(render_template("{stem}_{colour}", &vars).is_err());
+        assert!(render_template("{stem", &vars).is_err());
+    }
+    
+    #[test]
+    fn compute_psnr_is_infinite_for_identical_images() {
+        let img = DynamicImage::ImageRgb8(ImageBuffer::from_fn(8, 8, |x, y| Rgb([(x * 30) as u8, (y * 30) as u8, 7])));
+        
+        assert_eq!(compute_psnr(&img, &img), f64::INFINITY);
+        assert_eq!(compute_psnr_ignore_borders(&img, &img, 2), f64::INFINITY);
+    }
+    
+    // Only the corner pixel differs, so it counts unless the border skips it; a border that
+    // would cover the whole 8x8 image falls back to comparing everything
+    #[test]
+    fn compute_psnr_ignore_borders_skips_edges() {
+        let original = DynamicImage::ImageRgb8(ImageBuffer::from_pixel(8, 8, Rgb([100, 100, 100])));
+        let mut processed = original.to_rgb8();
+        processed.put_pixel(0, 0, Rgb([0, 0, 0]));
+        let processed = DynamicImage::ImageRgb8(processed);
+        
+        let whole = compute_psnr(&original, &processed);
+        assert!(whole.is_finite());
+        assert_eq!(compute_psnr_ignore_borders(&original, &processed, 1), f64::INFINITY);
+        assert_eq!(compute_psnr_ignore_borders(&original, &processed, 4), whole);
+    }
+    
+    // 128px tiles put the unfloored Hann corner weight below f32::EPSILON, which used to darken
+    // the image corners that only one tile covers
+    // The WIC speedup has to be measured on Windows with a large photo (the request asked for 20MP):
+    // SRU_BENCH_JPEG=photo.jpg cargo test --release fast_open_benchmark -- --ignored --nocapture
+    #[cfg(target_os = "windows")]
+    #[test]
+    #[ignore]
+    fn fast_open_benchmark() {
+        let path = PathBuf::from(std::env::var("SRU_BENCH_JPEG").expect("SRU_BENCH_JPEG must name a JPEG file"));
+        let best_of_3 = |decode: &dyn Fn() -> DynamicImage| (0..3)
+            .map(|_| {
+                let start = std::time::Instant::now();
+                let img = decode();
+                (start.elapsed(), img.dimensions())
+            })
+            .min()
+            .unwrap();
+        
+        let (wic, dims) = best_of_3(&|| fast_open(&path).unwrap());
+        let (pure_rust, _) = best_of_3(&|| image::open(&path).unwrap());
+        println!("{}x{}: fast_open {:?}, image::open {:?} ({:.1}x)", dims.0, dims.1, wic, pure_rust,
+            pure_rust.as_secs_f64() / wic.as_secs_f64());
+    }
+    
+    #[test]
+    fn stitch_tiles_keeps_constant_image_constant() {
+        let img = DynamicImage::ImageRgb8(ImageBuffer::from_pixel(300, 200, Rgb([200, 120, 40])));
+        let tiles = tile_image(&img, 128, 32, 1);
+        
+        let stitched = stitch_tiles(tiles, 300, 200, BitDepth::Bit8).to_rgb8();
+        assert!(stitched.pixels().all(|p| *p == Rgb([200, 120, 40])));
+    }
+}
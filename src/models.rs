@@ -0,0 +1,949 @@
+// The built-in model catalog and the remote registry that can extend it
+
+use crate::*;
+
+// Every model that ships with the app, before custom and registry models are merged in
+pub fn builtin_models() -> Vec<ModelInfo> {
+    vec![
+        // ===== UPSCALING MODELS =====
+        ModelInfo {
+            name: "swin2SR-realworld-sr-x4-64-bsrgan-psnr".to_string(),
+            url: "https://huggingface.co/Xenova/swin2SR-realworld-sr-x4-64-bsrgan-psnr/resolve/main/onnx/model.onnx".to_string(),
+            model_type: ModelType::Upscaling,
+            scale: 4,
+            window_size: 8,
+            description: "Real-world photos (4x)".to_string(),
+            category: "Swin2SR".to_string(),
+				tensor_format: TensorFormat::NCHW,
+				input_norm: NormalizationRange::ZeroOne,
+				output_norm: NormalizationRange::ZeroOne,
+				min_dimension: None, // No minimum for most models
+				channel_independent: false,
+				quantized: false,
+				ycbcr_mode: false,
+				max_tile_size: Some(DEFAULT_TILE_SIZE),
+				input_mean: None,
+				input_std: None,
+				output_mean: None,
+				output_std: None,
+				bit_depth: BitDepth::Bit8,
+				input_channels: 3,
+				output_channels: 3,
+				checksum: None,
+				output_index: 0,
+				tensor_dtype: TensorDtype::F32,
+				pad_mode: PadMode::Symmetric,
+        },
+        ModelInfo {
+            name: "swin2SR-classical-sr-x4-64".to_string(),
+            url: "https://huggingface.co/Xenova/swin2SR-classical-sr-x4-64/resolve/main/onnx/model.onnx".to_string(),
+            model_type: ModelType::Upscaling,
+            scale: 4,
+            window_size: 8,
+            description: "Clean images (4x)".to_string(),
+            category: "Swin2SR".to_string(),
+				tensor_format: TensorFormat::NCHW,
+				input_norm: NormalizationRange::ZeroOne,
+				output_norm: NormalizationRange::ZeroOne,
+				min_dimension: None, // No minimum for most models
+				channel_independent: false,
+				quantized: false,
+				ycbcr_mode: false,
+				max_tile_size: Some(DEFAULT_TILE_SIZE),
+				input_mean: None,
+				input_std: None,
+				output_mean: None,
+				output_std: None,
+				bit_depth: BitDepth::Bit8,
+				input_channels: 3,
+				output_channels: 3,
+				checksum: None,
+				output_index: 0,
+				tensor_dtype: TensorDtype::F32,
+				pad_mode: PadMode::Symmetric,
+        },
+        ModelInfo {
+            name: "swin2SR-lightweight-x2-64".to_string(),
+            url: "https://huggingface.co/Xenova/swin2SR-lightweight-x2-64/resolve/main/onnx/model.onnx".to_string(),
+            model_type: ModelType::Upscaling,
+            scale: 2,
+            window_size: 8,
+            description: "Lightweight (2x)".to_string(),
+            category: "Swin2SR".to_string(),
+				tensor_format: TensorFormat::NCHW,
+				input_norm: NormalizationRange::ZeroOne,
+				output_norm: NormalizationRange::ZeroOne,
+				min_dimension: None, // No minimum for most models
+				channel_independent: false,
+				quantized: false,
+				ycbcr_mode: false,
+				max_tile_size: Some(DEFAULT_TILE_SIZE),
+				input_mean: None,
+				input_std: None,
+				output_mean: None,
+				output_std: None,
+				bit_depth: BitDepth::Bit8,
+				input_channels: 3,
+				output_channels: 3,
+				checksum: None,
+				output_index: 0,
+				tensor_dtype: TensorDtype::F32,
+				pad_mode: PadMode::Symmetric,
+        },
+        ModelInfo {
+            name: "swin2SR-compressed-sr-x4-48".to_string(),
+            url: "https://huggingface.co/Xenova/swin2SR-compressed-sr-x4-48/resolve/main/onnx/model.onnx".to_string(),
+            model_type: ModelType::Upscaling,
+            scale: 4,
+            window_size: 8,
+            description: "Compressed/JPEG (4x)".to_string(),
+            category: "Swin2SR".to_string(),
+				tensor_format: TensorFormat::NCHW,
+				input_norm: NormalizationRange::ZeroOne,
+				output_norm: NormalizationRange::ZeroOne,
+				min_dimension: None, // No minimum for most models
+				channel_independent: false,
+				quantized: false,
+				ycbcr_mode: false,
+				max_tile_size: Some(DEFAULT_TILE_SIZE),
+				input_mean: None,
+				input_std: None,
+				output_mean: None,
+				output_std: None,
+				bit_depth: BitDepth::Bit8,
+				input_channels: 3,
+				output_channels: 3,
+				checksum: None,
+				output_index: 0,
+				tensor_dtype: TensorDtype::F32,
+				pad_mode: PadMode::Symmetric,
+        },
+        ModelInfo {
+            name: "2x_APISR_RRDB_GAN_generator".to_string(),
+            url: "https://huggingface.co/Xenova/2x_APISR_RRDB_GAN_generator-onnx/resolve/main/onnx/model.onnx".to_string(),
+            model_type: ModelType::Upscaling,
+            scale: 2,
+            window_size: 1,
+            description: "APISR GAN (2x) Anime".to_string(),
+            category: "APISR".to_string(),
+				tensor_format: TensorFormat::NCHW,
+				input_norm: NormalizationRange::ZeroOne,
+				output_norm: NormalizationRange::ZeroOne,
+				min_dimension: None, // No minimum for most models
+				channel_independent: false,
+				quantized: false,
+				ycbcr_mode: false,
+				max_tile_size: None,
+				input_mean: None,
+				input_std: None,
+				output_mean: None,
+				output_std: None,
+				bit_depth: BitDepth::Bit8,
+				input_channels: 3,
+				output_channels: 3,
+				checksum: None,
+				output_index: 0,
+				tensor_dtype: TensorDtype::F32,
+				pad_mode: PadMode::Symmetric,
+        },
+        ModelInfo {
+            name: "4x_APISR_GRL_GAN_generator".to_string(),
+            url: "https://huggingface.co/Xenova/4x_APISR_GRL_GAN_generator-onnx/resolve/main/onnx/model.onnx".to_string(),
+            model_type: ModelType::Upscaling,
+            scale: 4,
+            window_size: 1,
+            description: "APISR GAN (4x) Anime".to_string(),
+            category: "APISR".to_string(),
+				tensor_format: TensorFormat::NCHW,
+				input_norm: NormalizationRange::ZeroOne,
+				output_norm: NormalizationRange::ZeroOne,
+				min_dimension: None, // No minimum for most models
+				channel_independent: false,
+				quantized: false,
+				ycbcr_mode: false,
+				max_tile_size: None,
+				input_mean: None,
+				input_std: None,
+				output_mean: None,
+				output_std: None,
+				bit_depth: BitDepth::Bit8,
+				input_channels: 3,
+				output_channels: 3,
+				checksum: None,
+				output_index: 0,
+				tensor_dtype: TensorDtype::F32,
+				pad_mode: PadMode::Symmetric,
+        },
+        
+        // ===== RESTORATION & ENHANCEMENT MODELS (TensorStack) =====
+        ModelInfo {
+            name: "SwinIR-Noise".to_string(),
+            url: "https://huggingface.co/TensorStack/Upscale-amuse/resolve/main/SwinIR-Noise/model.onnx".to_string(),
+            model_type: ModelType::Denoising,
+            scale: 1,
+            window_size: 8,
+            description: "Noise reduction".to_string(),
+            category: "SwinIR".to_string(),
+				tensor_format: TensorFormat::NCHW,
+				input_norm: NormalizationRange::ZeroOne,
+				output_norm: NormalizationRange::ZeroOne,
+				min_dimension: None, // No minimum for most models
+				channel_independent: false,
+				quantized: false,
+				ycbcr_mode: false,
+				max_tile_size: Some(DEFAULT_TILE_SIZE),
+				input_mean: None,
+				input_std: None,
+				output_mean: None,
+				output_std: None,
+				bit_depth: BitDepth::Bit8,
+				input_channels: 3,
+				output_channels: 3,
+				checksum: None,
+				output_index: 0,
+				tensor_dtype: TensorDtype::F32,
+				pad_mode: PadMode::Symmetric,
+        },
+        ModelInfo {
+            name: "SwinIR-BSRGAN-4x".to_string(),
+            url: "https://huggingface.co/TensorStack/Upscale-amuse/resolve/main/SwinIR-BSRGAN-4x/model.onnx".to_string(),
+            model_type: ModelType::Enhancement,
+            scale: 4,
+            window_size: 8,
+            description: "Real degradations (4x)".to_string(),
+            category: "SwinIR".to_string(),
+				tensor_format: TensorFormat::NCHW,
+				input_norm: NormalizationRange::ZeroOne,
+				output_norm: NormalizationRange::ZeroOne,
+				min_dimension: None, // No minimum for most models
+				channel_independent: false,
+				quantized: false,
+				ycbcr_mode: false,
+				max_tile_size: Some(DEFAULT_TILE_SIZE),
+				input_mean: None,
+				input_std: None,
+				output_mean: None,
+				output_std: None,
+				bit_depth: BitDepth::Bit8,
+				input_channels: 3,
+				output_channels: 3,
+				checksum: None,
+				output_index: 0,
+				tensor_dtype: TensorDtype::F32,
+				pad_mode: PadMode::Symmetric,
+        },
+        ModelInfo {
+            name: "BSRGAN-2x".to_string(),
+            url: "https://huggingface.co/TensorStack/Upscale-amuse/resolve/main/BSRGAN-2x/model.onnx".to_string(),
+            model_type: ModelType::Enhancement,
+            scale: 2,
+            window_size: 1,
+            description: "Blind SR (2x)".to_string(),
+            category: "BSRGAN".to_string(),
+				tensor_format: TensorFormat::NCHW,
+				input_norm: NormalizationRange::ZeroOne,
+				output_norm: NormalizationRange::ZeroOne,
+				min_dimension: None, // No minimum for most models
+				channel_independent: false,
+				quantized: false,
+				ycbcr_mode: false,
+				max_tile_size: None,
+				input_mean: None,
+				input_std: None,
+				output_mean: None,
+				output_std: None,
+				bit_depth: BitDepth::Bit8,
+				input_channels: 3,
+				output_channels: 3,
+				checksum: None,
+				output_index: 0,
+				tensor_dtype: TensorDtype::F32,
+				pad_mode: PadMode::Symmetric,
+        },
+        ModelInfo {
+            name: "RealESRGAN-2x".to_string(),
+            url: "https://huggingface.co/TensorStack/Upscale-amuse/resolve/main/RealESRGAN-2x/model.onnx".to_string(),
+            model_type: ModelType::Enhancement,
+            scale: 2,
+            window_size: 1,
+            description: "Real-world SR (2x)".to_string(),
+            category: "RealESRGAN".to_string(),
+				tensor_format: TensorFormat::NCHW,
+				input_norm: NormalizationRange::ZeroOne,
+				output_norm: NormalizationRange::ZeroOne,
+				min_dimension: None, // No minimum for most models
+				channel_independent: false,
+				quantized: false,
+				ycbcr_mode: false,
+				max_tile_size: None,
+				input_mean: None,
+				input_std: None,
+				output_mean: None,
+				output_std: None,
+				bit_depth: BitDepth::Bit8,
+				input_channels: 3,
+				output_channels: 3,
+				checksum: None,
+				output_index: 0,
+				tensor_dtype: TensorDtype::F32,
+				pad_mode: PadMode::Symmetric,
+        },
+        ModelInfo {
+            name: "RealESRGAN-4x".to_string(),
+            url: "https://huggingface.co/TensorStack/Upscale-amuse/resolve/main/RealESRGAN-4x/model.onnx".to_string(),
+            model_type: ModelType::Enhancement,
+            scale: 4,
+            window_size: 1,
+            description: "Real-world SR (4x)".to_string(),
+            category: "RealESRGAN".to_string(),
+				tensor_format: TensorFormat::NCHW,
+				input_norm: NormalizationRange::ZeroOne,
+				output_norm: NormalizationRange::ZeroOne,
+				min_dimension: None, // No minimum for most models
+				channel_independent: false,
+				quantized: false,
+				ycbcr_mode: false,
+				max_tile_size: None,
+				input_mean: None,
+				input_std: None,
+				output_mean: None,
+				output_std: None,
+				bit_depth: BitDepth::Bit8,
+				input_channels: 3,
+				output_channels: 3,
+				checksum: None,
+				output_index: 0,
+				tensor_dtype: TensorDtype::F32,
+				pad_mode: PadMode::Symmetric,
+        },
+        ModelInfo {
+            name: "RealESR-General-4x".to_string(),
+            url: "https://huggingface.co/TensorStack/Upscale-amuse/resolve/main/RealESR-General-4x/model.onnx".to_string(),
+            model_type: ModelType::Enhancement,
+            scale: 4,
+            window_size: 1,
+            description: "General purpose (4x)".to_string(),
+            category: "RealESRGAN".to_string(),
+				tensor_format: TensorFormat::NCHW,
+				input_norm: NormalizationRange::ZeroOne,
+				output_norm: NormalizationRange::ZeroOne,
+				min_dimension: None, // No minimum for most models
+				channel_independent: false,
+				quantized: false,
+				ycbcr_mode: false,
+				max_tile_size: None,
+				input_mean: None,
+				input_std: None,
+				output_mean: None,
+				output_std: None,
+				bit_depth: BitDepth::Bit8,
+				input_channels: 3,
+				output_channels: 3,
+				checksum: None,
+				output_index: 0,
+				tensor_dtype: TensorDtype::F32,
+				pad_mode: PadMode::Symmetric,
+        },
+        ModelInfo {
+            name: "Swin2SR-Classical-2x".to_string(),
+            url: "https://huggingface.co/TensorStack/Upscale-amuse/resolve/main/Swin2SR-Classical-2x/model.onnx".to_string(),
+            model_type: ModelType::Upscaling,
+            scale: 2,
+            window_size: 8,
+            description: "Classical SR (2x)".to_string(),
+            category: "Swin2SR-TS".to_string(),
+				tensor_format: TensorFormat::NCHW,
+				input_norm: NormalizationRange::ZeroOne,
+				output_norm: NormalizationRange::ZeroOne,
+				min_dimension: None, // No minimum for most models
+				channel_independent: false,
+				quantized: false,
+				ycbcr_mode: false,
+				max_tile_size: Some(DEFAULT_TILE_SIZE),
+				input_mean: None,
+				input_std: None,
+				output_mean: None,
+				output_std: None,
+				bit_depth: BitDepth::Bit8,
+				input_channels: 3,
+				output_channels: 3,
+				checksum: None,
+				output_index: 0,
+				tensor_dtype: TensorDtype::F32,
+				pad_mode: PadMode::Symmetric,
+        },
+        ModelInfo {
+            name: "Swin2SR-Classical-4x".to_string(),
+            url: "https://huggingface.co/TensorStack/Upscale-amuse/resolve/main/Swin2SR-Classical-4x/model.onnx".to_string(),
+            model_type: ModelType::Upscaling,
+            scale: 4,
+            window_size: 8,
+            description: "Classical SR (4x)".to_string(),
+            category: "Swin2SR-TS".to_string(),
+				tensor_format: TensorFormat::NCHW,
+				input_norm: NormalizationRange::ZeroOne,
+				output_norm: NormalizationRange::ZeroOne,
+				min_dimension: None, // No minimum for most models
+				channel_independent: false,
+				quantized: false,
+				ycbcr_mode: false,
+				max_tile_size: Some(DEFAULT_TILE_SIZE),
+				input_mean: None,
+				input_std: None,
+				output_mean: None,
+				output_std: None,
+				bit_depth: BitDepth::Bit8,
+				input_channels: 3,
+				output_channels: 3,
+				checksum: None,
+				output_index: 0,
+				tensor_dtype: TensorDtype::F32,
+				pad_mode: PadMode::Symmetric,
+        },
+        ModelInfo {
+            name: "UltraSharp-4x".to_string(),
+            url: "https://huggingface.co/TensorStack/Upscale-amuse/resolve/main/UltraSharp-4x/model.onnx".to_string(),
+            model_type: ModelType::Enhancement,
+            scale: 4,
+            window_size: 1,
+            description: "Ultra sharp details (4x)".to_string(),
+            category: "Custom".to_string(),
+				tensor_format: TensorFormat::NCHW,
+				input_norm: NormalizationRange::ZeroOne,
+				output_norm: NormalizationRange::ZeroOne,
+				min_dimension: None, // No minimum for most models
+				channel_independent: false,
+				quantized: false,
+				ycbcr_mode: false,
+				max_tile_size: None,
+				input_mean: None,
+				input_std: None,
+				output_mean: None,
+				output_std: None,
+				bit_depth: BitDepth::Bit8,
+				input_channels: 3,
+				output_channels: 3,
+				checksum: None,
+				output_index: 0,
+				tensor_dtype: TensorDtype::F32,
+				pad_mode: PadMode::Symmetric,
+        },
+        ModelInfo {
+            name: "UltraMix-Smooth-4x".to_string(),
+            url: "https://huggingface.co/TensorStack/Upscale-amuse/resolve/main/UltraMix-Smooth-4x/model.onnx".to_string(),
+            model_type: ModelType::Enhancement,
+            scale: 4,
+            window_size: 1,
+            description: "Ultra smooth details (4x)".to_string(),
+            category: "Custom".to_string(),
+				tensor_format: TensorFormat::NCHW,
+				input_norm: NormalizationRange::ZeroOne,
+				output_norm: NormalizationRange::ZeroOne,
+				min_dimension: None, // No minimum for most models
+				channel_independent: false,
+				quantized: false,
+				ycbcr_mode: false,
+				max_tile_size: None,
+				input_mean: None,
+				input_std: None,
+				output_mean: None,
+				output_std: None,
+				bit_depth: BitDepth::Bit8,
+				input_channels: 3,
+				output_channels: 3,
+				checksum: None,
+				output_index: 0,
+				tensor_dtype: TensorDtype::F32,
+				pad_mode: PadMode::Symmetric,
+        },
+			ModelInfo {
+            name: "denoiser".to_string(),
+            url: "denoiser".to_string(),
+            model_type: ModelType::Denoising,
+            scale: 1,
+            window_size: 64,
+            description: "(Train)".to_string(),
+            category: "Denoiser".to_string(),
+				tensor_format: TensorFormat::NHWC,
+				input_norm: NormalizationRange::ZeroOne,
+				output_norm: NormalizationRange::ZeroOne,
+				min_dimension: None, // No minimum for most models
+				channel_independent: false,
+				quantized: false,
+				ycbcr_mode: false,
+				max_tile_size: None,
+				input_mean: None,
+				input_std: None,
+				output_mean: None,
+				output_std: None,
+				bit_depth: BitDepth::Bit8,
+				input_channels: 3,
+				output_channels: 3,
+				checksum: None,
+				output_index: 0,
+				tensor_dtype: TensorDtype::F32,
+				pad_mode: PadMode::Symmetric,
+        },
+        ModelInfo {
+            name: "deblurring_nafnet_2025may".to_string(),
+            url: "https://huggingface.co/opencv/deblurring_nafnet/resolve/main/deblurring_nafnet_2025may.onnx".to_string(),
+            model_type: ModelType::Deblur,
+            scale: 1,
+            window_size: 512,
+            description: "Motion deblur (GoPro)".to_string(),
+            category: "NAFNet - Motion deblur".to_string(),
+				tensor_format: TensorFormat::NCHW,							
+				input_norm: NormalizationRange::ZeroOne,  // Input: [-1, 1]
+				output_norm: NormalizationRange::ZeroOne,     // Output: [0, 1]
+				min_dimension: Some(512),
+				channel_independent: false,
+				quantized: false,
+				ycbcr_mode: false,
+				max_tile_size: None,
+				input_mean: None,
+				input_std: None,
+				output_mean: None,
+				output_std: None,
+				bit_depth: BitDepth::Bit8,
+				input_channels: 3,
+				output_channels: 3,
+				checksum: None,
+				output_index: 0,
+				tensor_dtype: TensorDtype::F32,
+				pad_mode: PadMode::Symmetric,
+        },
+			ModelInfo {
+				name: "deblurgan_mobilenet".to_string(),
+				url: "local".to_string(),
+				model_type: ModelType::Deblur,
+				scale: 1,
+				window_size: 16,
+				description: "Motion deblur (fast)".to_string(),
+				category: "DeblurGAN-v2".to_string(),
+				tensor_format: TensorFormat::NCHW,
+				input_norm: NormalizationRange::ZeroOne,  // Input: [-1, 1]
+				output_norm: NormalizationRange::ZeroOne,     // Output: [0, 1] ← FIX
+				min_dimension: None, // No minimum for most models
+				channel_independent: false,
+				quantized: false,
+				ycbcr_mode: false,
+				max_tile_size: None,
+				input_mean: None,
+				input_std: None,
+				output_mean: None,
+				output_std: None,
+				bit_depth: BitDepth::Bit8,
+				input_channels: 3,
+				output_channels: 3,
+				checksum: None,
+				output_index: 0,
+				tensor_dtype: TensorDtype::F32,
+				pad_mode: PadMode::Symmetric,
+			},
+        ModelInfo {
+            name: "restormer_deraining".to_string(),
+            url: "local".to_string(),
+            model_type: ModelType::Denoising,
+            scale: 1,
+            window_size: 64,
+            description: "Restormer deraining".to_string(),
+            category: "NAFNet".to_string(),
+				tensor_format: TensorFormat::NCHW,
+				input_norm: NormalizationRange::ZeroOne,
+				output_norm: NormalizationRange::ZeroOne,
+				min_dimension: None, // No minimum for most models
+				channel_independent: false,
+				quantized: false,
+				ycbcr_mode: false,
+				max_tile_size: None,
+				input_mean: None,
+				input_std: None,
+				output_mean: None,
+				output_std: None,
+				bit_depth: BitDepth::Bit8,
+				input_channels: 3,
+				output_channels: 3,
+				checksum: None,
+				output_index: 0,
+				tensor_dtype: TensorDtype::F32,
+				pad_mode: PadMode::Symmetric,
+        },
+        ModelInfo {
+            name: "restormer_denoising_real".to_string(),
+            url: "local".to_string(),
+            model_type: ModelType::Denoising,
+            scale: 1,
+            window_size: 64,
+            description: "Restormer denoising (real)".to_string(),
+            category: "NAFNet".to_string(),
+				tensor_format: TensorFormat::NCHW,
+				input_norm: NormalizationRange::ZeroOne,
+				output_norm: NormalizationRange::ZeroOne,
+				min_dimension: None, // No minimum for most models
+				channel_independent: false,
+				quantized: false,
+				ycbcr_mode: false,
+				max_tile_size: None,
+				input_mean: None,
+				input_std: None,
+				output_mean: None,
+				output_std: None,
+				bit_depth: BitDepth::Bit8,
+				input_channels: 3,
+				output_channels: 3,
+				checksum: None,
+				output_index: 0,
+				tensor_dtype: TensorDtype::F32,
+				pad_mode: PadMode::Symmetric,
+        },
+        ModelInfo {
+            name: "restormer_defocus_dual".to_string(),
+            url: "local".to_string(),
+            model_type: ModelType::Denoising,
+            scale: 1,
+            window_size: 64,
+            description: "Restormer defocus (dual)".to_string(),
+            category: "NAFNet".to_string(),
+				tensor_format: TensorFormat::NCHW,
+				input_norm: NormalizationRange::ZeroOne,
+				output_norm: NormalizationRange::ZeroOne,
+				min_dimension: None, // No minimum for most models
+				channel_independent: false,
+				quantized: false,
+				ycbcr_mode: false,
+				max_tile_size: None,
+				input_mean: None,
+				input_std: None,
+				output_mean: None,
+				output_std: None,
+				bit_depth: BitDepth::Bit8,
+				input_channels: 3,
+				output_channels: 3,
+				checksum: None,
+				output_index: 0,
+				tensor_dtype: TensorDtype::F32,
+				pad_mode: PadMode::Symmetric,
+        },
+        ModelInfo {
+            name: "restormer_defocus_single".to_string(),
+            url: "local".to_string(),
+            model_type: ModelType::Denoising,
+            scale: 1,
+            window_size: 64,
+            description: "Restormer defocus (single)".to_string(),
+            category: "NAFNet".to_string(),
+				tensor_format: TensorFormat::NCHW,
+				input_norm: NormalizationRange::ZeroOne,
+				output_norm: NormalizationRange::ZeroOne,
+				min_dimension: None, // No minimum for most models
+				channel_independent: false,
+				quantized: false,
+				ycbcr_mode: false,
+				max_tile_size: None,
+				input_mean: None,
+				input_std: None,
+				output_mean: None,
+				output_std: None,
+				bit_depth: BitDepth::Bit8,
+				input_channels: 3,
+				output_channels: 3,
+				checksum: None,
+				output_index: 0,
+				tensor_dtype: TensorDtype::F32,
+				pad_mode: PadMode::Symmetric,
+        },
+        ModelInfo {
+            name: "restormer_denoising_color_blind".to_string(),
+            url: "local".to_string(),
+            model_type: ModelType::Denoising,
+            scale: 1,
+            window_size: 64,
+            description: "Restormer denoising (color blind)".to_string(),
+            category: "NAFNet".to_string(),
+				tensor_format: TensorFormat::NCHW,
+				input_norm: NormalizationRange::ZeroOne,
+				output_norm: NormalizationRange::ZeroOne,
+				min_dimension: None, // No minimum for most models
+				channel_independent: false,
+				quantized: false,
+				ycbcr_mode: false,
+				max_tile_size: None,
+				input_mean: None,
+				input_std: None,
+				output_mean: None,
+				output_std: None,
+				bit_depth: BitDepth::Bit8,
+				input_channels: 3,
+				output_channels: 3,
+				checksum: None,
+				output_index: 0,
+				tensor_dtype: TensorDtype::F32,
+				pad_mode: PadMode::Symmetric,
+        },
+        ModelInfo {
+            name: "restormer_denoising_color_sigma15".to_string(),
+            url: "local".to_string(),
+            model_type: ModelType::Denoising,
+            scale: 1,
+            window_size: 64,
+            description: "Restormer denoising (color sigma15)".to_string(),
+            category: "NAFNet".to_string(),
+				tensor_format: TensorFormat::NCHW,
+				input_norm: NormalizationRange::ZeroOne,
+				output_norm: NormalizationRange::ZeroOne,
+				min_dimension: None, // No minimum for most models
+				channel_independent: false,
+				quantized: false,
+				ycbcr_mode: false,
+				max_tile_size: None,
+				input_mean: None,
+				input_std: None,
+				output_mean: None,
+				output_std: None,
+				bit_depth: BitDepth::Bit8,
+				input_channels: 3,
+				output_channels: 3,
+				checksum: None,
+				output_index: 0,
+				tensor_dtype: TensorDtype::F32,
+				pad_mode: PadMode::Symmetric,
+        },
+        ModelInfo {
+            name: "restormer_denoising_color_sigma25".to_string(),
+            url: "local".to_string(),
+            model_type: ModelType::Denoising,
+            scale: 1,
+            window_size: 64,
+            description: "Restormer denoising (color sigma25)".to_string(),
+            category: "NAFNet".to_string(),
+				tensor_format: TensorFormat::NCHW,
+				input_norm: NormalizationRange::ZeroOne,
+				output_norm: NormalizationRange::ZeroOne,
+				min_dimension: None, // No minimum for most models
+				channel_independent: false,
+				quantized: false,
+				ycbcr_mode: false,
+				max_tile_size: None,
+				input_mean: None,
+				input_std: None,
+				output_mean: None,
+				output_std: None,
+				bit_depth: BitDepth::Bit8,
+				input_channels: 3,
+				output_channels: 3,
+				checksum: None,
+				output_index: 0,
+				tensor_dtype: TensorDtype::F32,
+				pad_mode: PadMode::Symmetric,
+        },
+        ModelInfo {
+            name: "restormer_denoising_color_sigma50".to_string(),
+            url: "local".to_string(),
+            model_type: ModelType::Denoising,
+            scale: 1,
+            window_size: 64,
+            description: "Restormer denoising (color sigma50)".to_string(),
+            category: "NAFNet".to_string(),
+				tensor_format: TensorFormat::NCHW,
+				input_norm: NormalizationRange::ZeroOne,
+				output_norm: NormalizationRange::ZeroOne,
+				min_dimension: None, // No minimum for most models
+				channel_independent: false,
+				quantized: false,
+				ycbcr_mode: false,
+				max_tile_size: None,
+				input_mean: None,
+				input_std: None,
+				output_mean: None,
+				output_std: None,
+				bit_depth: BitDepth::Bit8,
+				input_channels: 3,
+				output_channels: 3,
+				checksum: None,
+				output_index: 0,
+				tensor_dtype: TensorDtype::F32,
+				pad_mode: PadMode::Symmetric,
+        },
+        ModelInfo {
+            name: "restormer_denoising_gray_blind".to_string(),
+            url: "local".to_string(),
+            model_type: ModelType::Denoising,
+            scale: 1,
+            window_size: 64,
+            description: "Restormer denoising (gray blind)".to_string(),
+            category: "NAFNet".to_string(),
+				tensor_format: TensorFormat::NCHW,
+				input_norm: NormalizationRange::ZeroOne,
+				output_norm: NormalizationRange::ZeroOne,
+				min_dimension: None, // No minimum for most models
+				channel_independent: false,
+				quantized: false,
+				ycbcr_mode: false,
+				max_tile_size: None,
+				input_mean: None,
+				input_std: None,
+				output_mean: None,
+				output_std: None,
+				bit_depth: BitDepth::Bit8,
+				input_channels: 1,
+				output_channels: 1,
+				checksum: None,
+				output_index: 0,
+				tensor_dtype: TensorDtype::F32,
+				pad_mode: PadMode::Symmetric,
+        },
+        ModelInfo {
+            name: "restormer_denoising_gray_sigma15".to_string(),
+            url: "local".to_string(),
+            model_type: ModelType::Denoising,
+            scale: 1,
+            window_size: 64,
+            description: "Restormer denoising (gray sigma15)".to_string(),
+            category: "NAFNet".to_string(),
+				tensor_format: TensorFormat::NCHW,
+				input_norm: NormalizationRange::ZeroOne,
+				output_norm: NormalizationRange::ZeroOne,
+				min_dimension: None, // No minimum for most models
+				channel_independent: false,
+				quantized: false,
+				ycbcr_mode: false,
+				max_tile_size: None,
+				input_mean: None,
+				input_std: None,
+				output_mean: None,
+				output_std: None,
+				bit_depth: BitDepth::Bit8,
+				input_channels: 1,
+				output_channels: 1,
+				checksum: None,
+				output_index: 0,
+				tensor_dtype: TensorDtype::F32,
+				pad_mode: PadMode::Symmetric,
+        },
+        ModelInfo {
+            name: "restormer_denoising_gray_sigma25".to_string(),
+            url: "local".to_string(),
+            model_type: ModelType::Denoising,
+            scale: 1,
+            window_size: 64,
+            description: "Restormer denoising (gray sigma25)".to_string(),
+            category: "NAFNet".to_string(),
+				tensor_format: TensorFormat::NCHW,
+				input_norm: NormalizationRange::ZeroOne,
+				output_norm: NormalizationRange::ZeroOne,
+				min_dimension: None, // No minimum for most models
+				channel_independent: false,
+				quantized: false,
+				ycbcr_mode: false,
+				max_tile_size: None,
+				input_mean: None,
+				input_std: None,
+				output_mean: None,
+				output_std: None,
+				bit_depth: BitDepth::Bit8,
+				input_channels: 1,
+				output_channels: 1,
+				checksum: None,
+				output_index: 0,
+				tensor_dtype: TensorDtype::F32,
+				pad_mode: PadMode::Symmetric,
+        },
+        ModelInfo {
+            name: "restormer_denoising_gray_sigma50".to_string(),
+            url: "local".to_string(),
+            model_type: ModelType::Denoising,
+            scale: 1,
+            window_size: 64,
+            description: "Restormer denoising (gray sigma50)".to_string(),
+            category: "NAFNet".to_string(),
+				tensor_format: TensorFormat::NCHW,
+				input_norm: NormalizationRange::ZeroOne,
+				output_norm: NormalizationRange::ZeroOne,
+				min_dimension: None, // No minimum for most models
+				channel_independent: false,
+				quantized: false,
+				ycbcr_mode: false,
+				max_tile_size: None,
+				input_mean: None,
+				input_std: None,
+				output_mean: None,
+				output_std: None,
+				bit_depth: BitDepth::Bit8,
+				input_channels: 1,
+				output_channels: 1,
+				checksum: None,
+				output_index: 0,
+				tensor_dtype: TensorDtype::F32,
+				pad_mode: PadMode::Symmetric,
+        },
+        
+        // ===== HELPER MODELS =====
+        ModelInfo {
+            name: "image_type_classifier".to_string(),
+            url: "local".to_string(),
+            model_type: ModelType::Classification,
+            scale: 1,
+            window_size: 1,
+            description: "Photo / anime / text / art".to_string(),
+            category: "Classifier".to_string(),
+				tensor_format: TensorFormat::NCHW,
+				input_norm: NormalizationRange::ZeroOne,
+				output_norm: NormalizationRange::ZeroOne,
+				min_dimension: None,
+				channel_independent: false,
+				quantized: false,
+				ycbcr_mode: false,
+				max_tile_size: None,
+				input_mean: None,
+				input_std: None,
+				output_mean: None,
+				output_std: None,
+				bit_depth: BitDepth::Bit8,
+				input_channels: 3,
+				output_channels: 3,
+				checksum: None,
+				output_index: 0,
+				tensor_dtype: TensorDtype::F32,
+				pad_mode: PadMode::Symmetric,
+        }
+    ]
+}
+
+// Appends the models whose name isn't listed yet, so built-in entries always win; returns how many were added
+pub fn merge_models(models: &mut Vec<ModelInfo>, extra: Vec<ModelInfo>) -> usize {
+    let before = models.len();
+    for model in extra {
+        if !models.iter().any(|m| m.name == model.name) {
+            models.push(model);
+        }
+    }
+    models.len() - before
+}
+
+// Downloads a models.json (a serialized Vec<ModelInfo>) from Config::registry_url. Entries that
+// fail validate_model_info are logged and dropped rather than failing the whole registry
+pub async fn fetch_model_registry(url: &str) -> Result<Vec<ModelInfo>> {
+    if OFFLINE_MODE.load(Ordering::Relaxed) {
+        return Err(anyhow::anyhow!("Offline mode: registry fetch disabled"));
+    }
+    
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .user_agent("image-enhancement-tool/1.0")
+        .build()?;
+    let resp = client.get(url).send().await?;
+    if !resp.status().is_success() {
+        return Err(anyhow::anyhow!("HTTP {} for {}", resp.status(), url));
+    }
+    
+    let models: Vec<ModelInfo> = serde_json::from_slice(&resp.bytes().await?)?;
+    Ok(models.into_iter()
+        .filter(|model| {
+            let problems = validate_model_info(model);
+            if !problems.is_empty() {
+                log_error(&format!("Skipping registry model '{}': {}", model.name, problems.join(", ")));
+            }
+            problems.is_empty()
+        })
+        .collect())
+}